@@ -0,0 +1,63 @@
+use super::*;
+
+// Announce from GIP client `client`: header, 6-byte address, two unknown
+// bytes, vendor, product, then firmware and hardware versions
+fn announce(client: u8, vendor: u16, product: u16) -> Vec<u8> {
+    let mut report = vec![GIP_CMD_ANNOUNCE, GIP_OPT_INTERNAL | client, 0x01, 0x1c];
+    report.extend_from_slice(&[0x7e, 0xed, 0x80, 0x12, 0x34, 0x56]);
+    report.extend_from_slice(&[0x00, 0x00]);
+    report.extend_from_slice(&vendor.to_le_bytes());
+    report.extend_from_slice(&product.to_le_bytes());
+    report.extend_from_slice(&[0; 16]);
+    report
+}
+
+fn status(client: u8, connected: bool) -> [u8; 5] {
+    let byte = if connected { GIP_STATUS_CONNECTED } else { 0 };
+    [GIP_CMD_STATUS, GIP_OPT_INTERNAL | client, 0x02, 0x01, byte]
+}
+
+#[test]
+fn headset_adapter_attach_and_detach() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    xpad.set_accessory_handler(Box::new(move |accessories| {
+        log.lock().unwrap().push(accessories.len());
+    }));
+
+    xpad.decode(&announce(1, 0x045e, GIP_PRODUCT_HEADSET_ADAPTER));
+    assert_eq!(
+        xpad.accessories(),
+        [Accessory {
+            client: 1,
+            vendor: 0x045e,
+            product: GIP_PRODUCT_HEADSET_ADAPTER,
+            kind: AccessoryKind::HeadsetAdapter,
+        }]
+    );
+
+    xpad.decode(&status(1, false));
+    assert!(xpad.accessories().is_empty());
+    assert_eq!(*seen.lock().unwrap(), [1, 0]);
+}
+
+#[test]
+fn pad_announce_is_not_an_accessory() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(&announce(0, 0x045e, 0x02ea));
+    assert!(xpad.accessories().is_empty());
+    assert_eq!(xpad.init_state(), InitState::Announced);
+}
+
+#[test]
+fn truncated_announce_is_ignored() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(
+        &announce(1, 0x045e, GIP_PRODUCT_HEADSET_ADAPTER)[..GIP_ANNOUNCE_PRODUCT_OFFSET + 1],
+    );
+    assert!(xpad.accessories().is_empty());
+}
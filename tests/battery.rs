@@ -0,0 +1,63 @@
+use super::*;
+
+fn xboxone() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    (pad, xpad)
+}
+
+fn gip_status(status: u8) -> [u8; 5] {
+    [GIP_CMD_STATUS, GIP_OPT_INTERNAL, 0x00, 0x01, status]
+}
+
+fn battery(percent: u8, charge: ChargeState) -> Option<BatteryStatus> {
+    Some(BatteryStatus { percent, charge })
+}
+
+#[test]
+fn xboxone_status_report_sets_the_level() {
+    let (_pad, xpad) = xboxone();
+    assert_eq!(xpad.battery(), None);
+
+    // AA batteries, level 2 of 3
+    xpad.decode(&gip_status(0x04 | 0x02));
+    assert_eq!(xpad.battery(), battery(60, ChargeState::Discharging));
+
+    // Play & charge kit, full and charging
+    xpad.decode(&gip_status(0x08 | GIP_BATTERY_CHARGING | 0x03));
+    assert_eq!(xpad.battery(), battery(100, ChargeState::Charging));
+
+    xpad.decode(&gip_status(0x04));
+    assert_eq!(xpad.battery(), battery(5, ChargeState::Discharging));
+}
+
+#[test]
+fn no_battery_type_is_a_wired_pad() {
+    let (_pad, xpad) = xboxone();
+    xpad.decode(&gip_status(0x00));
+    assert_eq!(xpad.battery().unwrap().charge, ChargeState::Wired);
+    assert!(!xpad.features().contains(FeatureSet::BATTERY));
+
+    xpad.decode(&gip_status(0x04 | 0x01));
+    assert!(xpad.features().contains(FeatureSet::BATTERY));
+}
+
+#[test]
+fn accessory_status_leaves_the_battery_alone() {
+    let (_pad, xpad) = xboxone();
+    xpad.decode(&[GIP_CMD_STATUS, GIP_OPT_INTERNAL | 0x01, 0x00, 0x01, 0x06]);
+    assert_eq!(xpad.battery(), None);
+}
+
+#[test]
+fn xbox360_wireless_reports_through_the_same_api() {
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719);
+    let xpad = pad.build();
+    xpad.decode(&[0x00, 0x00, 0x00, XPAD360W_BATTERY_REPORT, 0xff]);
+    assert_eq!(xpad.battery(), battery(100, ChargeState::Unknown));
+    xpad.decode(&[0x00, 0x00, 0x00, XPAD360W_BATTERY_REPORT, 0x80]);
+    assert_eq!(xpad.battery(), battery(50, ChargeState::Unknown));
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+// The -1 terminated lists the request describes don't exist here: a typed
+// [Button; N] can't be cut short or left unterminated, the compiler
+// rejects a list whose length doesn't match. What's checked instead is
+// that no mapping advertises a code twice, and that every code is real.
+
+fn assert_unique<T: PartialEq + std::fmt::Debug>(list: &[T], what: &str) {
+    for (i, item) in list.iter().enumerate() {
+        assert!(
+            !list[i + 1..].contains(item),
+            "{:?} twice in {}: {:?}",
+            item,
+            what,
+            list
+        );
+    }
+}
+
+#[test]
+fn no_mapping_advertises_a_code_twice() {
+    for xtype in [XType::Xbox, XType::Xbox360, XType::Xbox360W, XType::XboxOne] {
+        for quirks in [QuirkFlags::empty(), QuirkFlags::GHL_XBOXONE] {
+            let xpad = UsbXpad::test_fixture(xtype, MapFlags::SELECT_BUTTON, quirks);
+            for bits in 0..=MapFlags::all().bits() {
+                xpad.set_mapping(MapFlags::from_bits_truncate(bits));
+                let caps = xpad_capabilities(&xpad);
+                let what = format!("{:?} {:#x}", xtype, bits);
+                assert_unique(&caps.buttons, &what);
+                assert_unique(&caps.axes, &what);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_listed_code_is_known() {
+    let buttons = XPAD_COMMON_BTN
+        .iter()
+        .chain(&XPAD_BTN)
+        .chain(&XPAD360_BTN)
+        .chain(&XPAD_BTN_PAD)
+        .chain(&XPAD_BTN_TRIGGERS)
+        .chain(&XPAD_BTN_PADDLES);
+    for button in buttons {
+        assert!(button.code().is_some(), "{:?}", button);
+    }
+    for axis in XPAD_ABS
+        .iter()
+        .chain(&XPAD_ABS_PAD)
+        .chain(&XPAD_ABS_TRIGGERS)
+    {
+        assert!(axis.code().is_some(), "{:?}", axis);
+    }
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+fn ready_xboxone() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.transport.take();
+    (pad, xpad)
+}
+
+#[test]
+fn identify_report_leaves_the_table_in_charge() {
+    let (_pad, xpad) = ready_xboxone();
+    let before = xpad.features();
+    xpad.decode(&[
+        GIP_CMD_IDENTIFY,
+        GIP_OPT_INTERNAL,
+        0x03,
+        0x04,
+        0x00,
+        0x00,
+        0x00,
+        0x00,
+    ]);
+    assert_eq!(xpad.gip_capabilities(), None);
+    assert_eq!(xpad.features(), before);
+}
+
+#[test]
+fn without_a_descriptor_trigger_rumble_follows_the_table() {
+    let (pad, xpad) = ready_xboxone();
+    let rumble = RumbleMagnitudes {
+        left_trigger: 0xffff,
+        ..Default::default()
+    };
+    xpad_play_effect(&xpad, &rumble).unwrap();
+
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0][5], 0x0f);
+    assert_ne!(packets[0][6], 0);
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+// The identify report isn't decoded, see GIP_CMD_IDENTIFY, so no pad
+// fills gip_caps on its own yet. These stand in for a decoded descriptor
+// by setting it directly, then check what the manager carries over.
+const CAPS: GipCapabilities = GipCapabilities::RUMBLE.union(GipCapabilities::PADDLES);
+
+fn pad(serial: Option<&str>, firmware: u16) -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build().with_serial_and_firmware(serial, firmware);
+    (pad, xpad)
+}
+
+// Attach a pad that reported CAPS, then unplug it
+fn seen_before(manager: &XpadManager, serial: Option<&str>, firmware: u16) {
+    let (_pad, xpad) = pad(serial, firmware);
+    *xpad.gip_caps.lock().unwrap() = Some(CAPS);
+    let id = manager.attach(xpad);
+    manager.detach(id).unwrap();
+}
+
+fn reconnect(
+    manager: &XpadManager,
+    serial: Option<&str>,
+    firmware: u16,
+) -> Option<GipCapabilities> {
+    let (_pad, xpad) = pad(serial, firmware);
+    let id = manager.attach(xpad);
+    manager.get(id).unwrap().gip_capabilities()
+}
+
+#[test]
+fn same_serial_and_firmware_reuses_the_capabilities() {
+    let manager = XpadManager::new();
+    seen_before(&manager, Some("0123456789"), 0x0408);
+
+    // The driver never asks for the descriptor, the pad sends it during
+    // init; nothing goes out on attach either way
+    let (pad, xpad) = pad(Some("0123456789"), 0x0408);
+    let id = manager.attach(xpad);
+    assert_eq!(manager.get(id).unwrap().gip_capabilities(), Some(CAPS));
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn firmware_change_drops_the_cached_capabilities() {
+    let manager = XpadManager::new();
+    seen_before(&manager, Some("0123456789"), 0x0408);
+    assert_eq!(reconnect(&manager, Some("0123456789"), 0x0500), None);
+
+    // The entry is gone, not just skipped
+    assert_eq!(reconnect(&manager, Some("0123456789"), 0x0408), None);
+}
+
+#[test]
+fn other_serials_get_nothing() {
+    let manager = XpadManager::new();
+    seen_before(&manager, Some("0123456789"), 0x0408);
+    assert_eq!(reconnect(&manager, Some("9876543210"), 0x0408), None);
+}
+
+#[test]
+fn pads_without_a_serial_are_not_cached() {
+    let manager = XpadManager::new();
+    seen_before(&manager, None, 0x0408);
+    assert_eq!(reconnect(&manager, None, 0x0408), None);
+
+    // Blank serials, as some clones report, count as none
+    seen_before(&manager, Some("  "), 0x0408);
+    assert_eq!(reconnect(&manager, Some("  "), 0x0408), None);
+}
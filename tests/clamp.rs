@@ -0,0 +1,68 @@
+use super::*;
+
+fn clamped(xtype: XType, axis: AbsoluteAxis, value: i32) -> Vec<SinkEvent> {
+    let pad = XpadFixture::new(xtype, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    report_abs_clamped(&xpad, &*pad.sink, axis, value);
+    pad.sink.take()
+}
+
+#[test]
+fn out_of_range_values_are_clamped_not_dropped() {
+    assert_eq!(
+        clamped(XType::Xbox360, AbsoluteAxis::X, 40000),
+        [SinkEvent::Abs(AbsoluteAxis::X, 32767)]
+    );
+    assert_eq!(
+        clamped(XType::Xbox360, AbsoluteAxis::Ry, -40000),
+        [SinkEvent::Abs(AbsoluteAxis::Ry, -32768)]
+    );
+    assert_eq!(
+        clamped(XType::Xbox360, AbsoluteAxis::Hat0X, -3),
+        [SinkEvent::Abs(AbsoluteAxis::Hat0X, -1)]
+    );
+    assert_eq!(
+        clamped(XType::Xbox360, AbsoluteAxis::Z, 300),
+        [SinkEvent::Abs(AbsoluteAxis::Z, 255)]
+    );
+    assert_eq!(
+        clamped(XType::XboxOne, AbsoluteAxis::Rz, 2000),
+        [SinkEvent::Abs(AbsoluteAxis::Rz, 1023)]
+    );
+    assert_eq!(
+        clamped(XType::XboxOne, AbsoluteAxis::Z, -5),
+        [SinkEvent::Abs(AbsoluteAxis::Z, 0)]
+    );
+}
+
+#[test]
+fn in_range_values_pass_unchanged() {
+    for (axis, value) in [
+        (AbsoluteAxis::X, -32768),
+        (AbsoluteAxis::Y, 32767),
+        (AbsoluteAxis::Hat0Y, 1),
+        (AbsoluteAxis::Z, 255),
+    ] {
+        assert_eq!(
+            clamped(XType::Xbox360, axis, value),
+            [SinkEvent::Abs(axis, value)]
+        );
+    }
+}
+
+#[test]
+fn moved_trigger_axes_take_the_trigger_range() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::STICKS_TO_NULL,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    xpad.set_trigger_axis_layout(TriggerAxisLayout {
+        left: AbsoluteAxis::Rx,
+        right: AbsoluteAxis::Ry,
+    })
+    .unwrap();
+    report_abs_clamped(&xpad, &*pad.sink, AbsoluteAxis::Ry, 1000);
+    assert_eq!(pad.sink.take(), [SinkEvent::Abs(AbsoluteAxis::Ry, 255)]);
+}
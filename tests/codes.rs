@@ -0,0 +1,57 @@
+use super::*;
+
+// Every button and axis the decoders emit round-trips through its code.
+// Refusing a button without a code isn't covered: every Button variant
+// this crate names is in BUTTON_CODES, and which other variants the input
+// crate has depends on the kernel it's built against.
+
+#[test]
+fn button_codes_round_trip() {
+    for (button, code) in BUTTON_CODES {
+        assert_eq!(button.code(), Some(code), "{:?}", button);
+        assert_eq!(Button::from_code(code), Some(button));
+    }
+}
+
+#[test]
+fn axis_codes_round_trip() {
+    for (axis, code) in AXIS_CODES {
+        assert_eq!(axis.code(), Some(code), "{:?}", axis);
+        assert_eq!(AbsoluteAxis::from_code(code), Some(axis));
+    }
+}
+
+#[test]
+fn codes_match_linux_input_event_codes() {
+    assert_eq!(Button::A.code(), Some(0x130)); // BTN_SOUTH
+    assert_eq!(Button::Mode.code(), Some(0x13c)); // BTN_MODE
+    assert_eq!(Button::Record.code(), Some(0xa7)); // KEY_RECORD
+    assert_eq!(Button::TriggerHappy1.code(), Some(0x2c0)); // BTN_TRIGGER_HAPPY1
+    assert_eq!(AbsoluteAxis::Rz.code(), Some(0x05)); // ABS_RZ
+    assert_eq!(AbsoluteAxis::Hat0Y.code(), Some(0x11)); // ABS_HAT0Y
+    assert_eq!(AbsoluteAxis::Profile.code(), Some(0x21)); // ABS_PROFILE
+}
+
+#[test]
+fn unknown_codes_are_none() {
+    assert_eq!(Button::from_code(0x100), None);
+    assert_eq!(AbsoluteAxis::from_code(0x3f), None);
+}
+
+#[test]
+fn known_remap_is_accepted_and_exported_by_code() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let mut remap = ButtonRemap::default();
+    remap.set(Button::A, Button::B);
+    xpad.set_button_remap(remap).unwrap();
+    assert_eq!(xpad.export_settings().remap, [(0x130, 0x131)]);
+}
+
+#[test]
+fn settings_with_unknown_remap_codes_skip_them() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let mut settings = xpad.export_settings();
+    settings.remap = vec![(0x130, 0x131), (0x100, 0x130)];
+    xpad.apply_settings(&settings);
+    assert_eq!(xpad.export_settings().remap, [(0x130, 0x131)]);
+}
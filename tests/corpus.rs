@@ -0,0 +1,56 @@
+use super::*;
+
+use std::path::PathBuf;
+
+fn corpus(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("corpus")
+        .join(name)
+}
+
+fn replay(name: &str) {
+    match run_corpus(&corpus(name)) {
+        Ok(frames) => assert!(frames > 0, "{} has no frames", name),
+        Err(err) => panic!("{}: {:?}", name, err),
+    }
+}
+
+#[test]
+fn xpad360_matches_linux() {
+    replay("xpad360.txt");
+}
+
+#[test]
+fn xpadone_matches_linux() {
+    replay("xpadone.txt");
+}
+
+#[test]
+fn original_xbox_pads_match_linux() {
+    replay("xbox.txt");
+}
+
+#[test]
+fn amazon_game_controller_matches_linux() {
+    replay("luna.txt");
+}
+
+#[test]
+fn centred_stick_reads_minus_one_on_y() {
+    assert_eq!(invert_axis(0), -1);
+    assert_eq!(invert_axis(i16::MAX), i16::MIN);
+    assert_eq!(invert_axis(i16::MIN), i16::MAX);
+}
+
+#[test]
+fn corpus_mismatch_names_the_report_line() {
+    let path = std::env::temp_dir().join("rxpad-corpus-mismatch.txt");
+    std::fs::write(
+        &path,
+        "pad 360\n\nin 00 14 00 10 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\nkey 0x131 1\n",
+    )
+    .unwrap();
+    let result = run_corpus(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(CorpusError::Mismatch { line: 3, .. })));
+}
@@ -0,0 +1,53 @@
+use super::*;
+
+const EXPO: StickCurve = StickCurve { expo: 0.6 };
+
+#[test]
+fn expo_0_is_an_exact_no_op() {
+    let linear = StickCurve::default();
+    for x in (-32768..=32767).step_by(97) {
+        for y in [-32768, -12345, -1, 0, 1, 777, 32767] {
+            assert_eq!(linear.apply((x, y)), (x, y));
+        }
+    }
+    assert_eq!(
+        StickCurve { expo: f32::NAN }.apply((1000, -2000)),
+        (1000, -2000)
+    );
+    assert_eq!(
+        StickCurve { expo: -1.0 }.apply((1000, -2000)),
+        (1000, -2000)
+    );
+}
+
+#[test]
+fn expo_flattens_the_center_more_than_the_edge() {
+    // 25% and 75% deflection, expo 0.6 scales by 0.4 + 0.6 * m^2
+    let quarter = EXPO.apply((8192, 0)).0;
+    let three_quarters = EXPO.apply((24575, 0)).0;
+    assert!((quarter - 3584).abs() <= 2, "{}", quarter);
+    assert!((three_quarters - 18124).abs() <= 2, "{}", three_quarters);
+
+    // Relative to linear the center loses more
+    assert!(quarter as f32 / 8192.0 < three_quarters as f32 / 24575.0);
+}
+
+#[test]
+fn direction_is_kept() {
+    let (x, y) = EXPO.apply((6000, -8000));
+    assert!(x > 0 && y < 0);
+    assert!(((x as f32 / y as f32) - (6000.0 / -8000.0)).abs() < 0.001);
+}
+
+#[test]
+fn full_deflection_and_corners_are_untouched() {
+    for point in [
+        (32767, 0),
+        (0, -32768),
+        (32767, 32767),
+        (-32768, 32767),
+        (30000, 30000),
+    ] {
+        assert_eq!(EXPO.apply(point), point);
+    }
+}
@@ -0,0 +1,74 @@
+use super::*;
+
+fn report_a(pressed: bool) -> [u8; 20] {
+    let mut report = report_360();
+    report[3] = if pressed { 0x10 } else { 0 };
+    report
+}
+
+fn a_states(events: &[SinkEvent]) -> Vec<bool> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            SinkEvent::Key(Button::A, pressed) => Some(*pressed),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn bounce_inside_the_window_is_one_press() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::empty(),
+        QuirkFlags::DEBOUNCE_BUTTONS,
+    );
+    let xpad = pad.build();
+    let step = Duration::from_millis(2);
+
+    xpad.decode(&report_a(true));
+    pad.clock.advance(step);
+    xpad.decode(&report_a(false));
+    pad.clock.advance(step);
+    xpad.decode(&report_a(true));
+    assert_eq!(a_states(&pad.sink.take()), [true, true]);
+
+    // A release after the window goes through
+    pad.clock.advance(Duration::from_millis(
+        DEBOUNCE_WINDOW_MS.load(Ordering::Relaxed).into(),
+    ));
+    xpad.decode(&report_a(false));
+    assert_eq!(a_states(&pad.sink.take()), [false]);
+}
+
+#[test]
+fn axes_and_other_pads_are_not_debounced() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    for pressed in [true, false, true] {
+        xpad.decode(&report_a(pressed));
+    }
+    assert_eq!(a_states(&pad.sink.take()), [true, false, true]);
+
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::empty(),
+        QuirkFlags::DEBOUNCE_BUTTONS,
+    );
+    let xpad = pad.build();
+    for x in [100i16, -100, 100] {
+        let mut report = report_360();
+        report[6..8].copy_from_slice(&x.to_le_bytes());
+        xpad.decode(&report);
+    }
+    let xs: Vec<i32> = pad
+        .sink
+        .take()
+        .iter()
+        .filter_map(|event| match event {
+            SinkEvent::Abs(AbsoluteAxis::X, value) => Some(*value),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(xs, [100, -100, 100]);
+}
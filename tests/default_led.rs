@@ -0,0 +1,74 @@
+use super::*;
+
+fn led(command: LedCommand) -> Vec<u8> {
+    vec![0x01, 0x03, command as u8]
+}
+
+#[test]
+fn table_default_is_sent_once_init_completes() {
+    // 045e:028e shows player 1 by default
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert!(pad.transport.take().is_empty());
+
+    xpad.reinitialize().unwrap();
+    assert_eq!(xpad.init_state(), InitState::Ready);
+    assert_eq!(pad.transport.take(), [led(LedCommand::TopLeftBlinkThenOn)]);
+
+    // Again after a reinitialize, still once
+    xpad.reinitialize().unwrap();
+    assert_eq!(pad.transport.take(), [led(LedCommand::TopLeftBlinkThenOn)]);
+}
+
+#[test]
+fn override_replaces_the_table_default() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_default_led(Some(LedState::Pattern(LedCommand::BottomRightOn as u8)));
+    xpad.reinitialize().unwrap();
+    assert_eq!(pad.transport.take(), [led(LedCommand::BottomRightOn)]);
+}
+
+#[test]
+fn no_default_leaves_the_led_alone() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x0738, 0x4716);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    assert!(pad.transport.take().is_empty());
+
+    // And an override can be cleared again
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_default_led(None);
+    xpad.reinitialize().unwrap();
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn a_player_index_wins_over_the_default() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_player_index(3).unwrap();
+    pad.transport.take();
+
+    xpad.reinitialize().unwrap();
+    assert_eq!(
+        pad.transport.take(),
+        [led(LedCommand::BottomLeftBlinkThenOn)]
+    );
+}
+
+#[test]
+fn ready_handler_sees_the_default_already_sent() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let transport = pad.transport.clone();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = seen.clone();
+    xpad.set_ready_handler(Box::new(move || {
+        *record.lock().unwrap() = transport.take();
+    }));
+    xpad.reinitialize().unwrap();
+    assert_eq!(*seen.lock().unwrap(), [led(LedCommand::TopLeftBlinkThenOn)]);
+}
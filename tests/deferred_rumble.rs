@@ -0,0 +1,75 @@
+use super::*;
+
+fn strong(magnitude: u16) -> RumbleMagnitudes {
+    RumbleMagnitudes {
+        strong: magnitude,
+        ..RumbleMagnitudes::default()
+    }
+}
+
+fn rumble_packets(packets: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    packets
+        .iter()
+        .filter(|packet| packet[0] == GIP_CMD_RUMBLE)
+        .cloned()
+        .collect()
+}
+
+// Pad mid-handshake: init sent, ack not yet back. Returns the packet to ack.
+fn mid_handshake() -> (XpadFixture, UsbXpad, Vec<u8>) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let last = pad.transport.take().pop().unwrap();
+    assert_eq!(xpad.init_state(), InitState::Configured);
+    (pad, xpad, last)
+}
+
+#[test]
+fn rumble_waits_for_ready_then_goes_out_once() {
+    let (pad, xpad, last) = mid_handshake();
+    xpad_play_effect(&xpad, &strong(0x2000)).unwrap();
+    xpad_play_effect(&xpad, &strong(0xfe00)).unwrap();
+    assert!(pad.transport.take().is_empty());
+
+    xpad.decode(&gip_ack_for(&last));
+    assert_eq!(xpad.init_state(), InitState::Ready);
+    let sent = rumble_packets(&pad.transport.take());
+    assert_eq!(sent.len(), 1);
+    // The latest request, strong motor in byte 8
+    assert_eq!(sent[0][8], (0xfe00 / 512) as u8);
+}
+
+#[test]
+fn stop_during_init_sends_nothing_later() {
+    let (pad, xpad, last) = mid_handshake();
+    xpad_play_effect(&xpad, &strong(0x8000)).unwrap();
+    xpad_play_effect(&xpad, &RumbleMagnitudes::default()).unwrap();
+
+    xpad.decode(&gip_ack_for(&last));
+    assert!(rumble_packets(&pad.transport.take()).is_empty());
+}
+
+#[test]
+fn ready_pads_rumble_straight_away() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.transport.take();
+
+    xpad_play_effect(&xpad, &strong(0x8000)).unwrap();
+    assert_eq!(rumble_packets(&pad.transport.take()).len(), 1);
+}
+
+#[test]
+fn xbox360_pads_have_no_handshake_to_wait_for() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad_play_effect(&xpad, &strong(0x8000)).unwrap();
+    assert_eq!(
+        pad.transport.take(),
+        [vec![0x00, 0x08, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00]]
+    );
+}
@@ -0,0 +1,103 @@
+use super::*;
+
+fn lines(report: &str) -> Vec<&str> {
+    report.lines().collect()
+}
+
+#[test]
+fn elite_with_a_custom_deadzone() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::PADDLES, QuirkFlags::empty())
+        .ids(0x045e, 0x0b00)
+        .name("Microsoft X-Box One Elite 2 pad");
+    let xpad = pad.build().with_serial_and_firmware(None, 0x050b);
+    xpad.set_stick_deadzone(4000);
+    xpad.set_rumble_gain(50);
+
+    let report = xpad.describe_config();
+    let lines = lines(&report);
+    for expected in [
+        "name: Microsoft X-Box One Elite 2 pad",
+        "id: 045e:0b00",
+        "xtype: XboxOne",
+        "packet_type: Xbe2Fw5_11",
+        "mapping: PADDLES",
+        "quirks: none",
+        "deadzone: 4000",
+        "triggers: axes (0..1023)",
+        "rumble_gain: 50%",
+        "led: default",
+    ] {
+        assert!(lines.contains(&expected), "{:?} in\n{}", expected, report);
+    }
+}
+
+#[test]
+fn trigger_buttons_and_the_led_are_described() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::START_PKT_1,
+    );
+    let xpad = pad.build();
+    xpad.set_player_index(1).unwrap();
+
+    let report = xpad.describe_config();
+    let lines = lines(&report);
+    let led = format!("led: {:02x?}", pad.transport.take().last().unwrap());
+    for expected in [
+        "mapping: DPAD_TO_BUTTONS | TRIGGERS_TO_BUTTONS",
+        "quirks: START_PKT_1",
+        &format!(
+            "triggers: buttons (threshold {})",
+            DEFAULT_TRIGGER_THRESHOLD
+        ),
+        &led,
+    ] {
+        assert!(lines.contains(&expected), "{:?} in\n{}", expected, report);
+    }
+}
+
+#[test]
+fn inversion_lists_the_inverted_triggers() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    assert!(lines(&xpad.describe_config()).contains(&"inversion: Y, RY"));
+
+    xpad.set_axis_config(AxisConfig {
+        invert_lt: false,
+        invert_rt: true,
+    });
+    assert!(lines(&xpad.describe_config()).contains(&"inversion: Y, RY, RT"));
+
+    xpad.set_axis_config(AxisConfig {
+        invert_lt: true,
+        invert_rt: true,
+    });
+    assert!(lines(&xpad.describe_config()).contains(&"inversion: Y, RY, LT, RT"));
+}
+
+#[test]
+fn every_line_is_a_named_field() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let report = xpad.describe_config();
+    let fields: Vec<&str> = report
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().0)
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "name",
+            "id",
+            "xtype",
+            "packet_type",
+            "mapping",
+            "quirks",
+            "deadzone",
+            "inversion",
+            "triggers",
+            "socd",
+            "rumble_gain",
+            "led",
+        ]
+    );
+}
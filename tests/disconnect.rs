@@ -0,0 +1,91 @@
+use super::*;
+
+fn pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+fn a_pressed() -> [u8; 20] {
+    let mut report = report_360();
+    report[3] = 0x10;
+    report
+}
+
+fn complete(pad: &XpadFixture, xpad: &UsbXpad, status: UsbStatus) {
+    xpad.in_completed(status, &a_pressed(), &*pad.transport)
+        .unwrap();
+}
+
+#[test]
+fn input_is_decoded_and_resubmitted_while_connected() {
+    let (pad, xpad) = pad();
+    complete(&pad, &xpad, UsbStatus::Success);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+    assert_eq!(pad.transport.resubmits(), 1);
+
+    // Killed by us, nothing to decode or resubmit
+    complete(&pad, &xpad, UsbStatus::Cancelled);
+    assert!(pad.sink.take().is_empty());
+    assert_eq!(pad.transport.resubmits(), 1);
+}
+
+#[test]
+fn disconnect_from_the_urb_handler_tears_the_pad_down() {
+    let (pad, xpad) = pad();
+    complete(&pad, &xpad, UsbStatus::Disconnected);
+    assert!(xpad.is_disconnected());
+    assert_eq!(pad.transport.cancels(), 1);
+    assert_eq!(pad.transport.resubmits(), 0);
+    assert!(pad.sink.take().is_empty());
+
+    // A completion racing the disconnect is dropped
+    complete(&pad, &xpad, UsbStatus::Success);
+    assert!(pad.sink.take().is_empty());
+    assert_eq!(pad.transport.resubmits(), 0);
+
+    // Only torn down once
+    xpad.handle_disconnect();
+    assert_eq!(pad.transport.cancels(), 1);
+}
+
+#[test]
+fn pending_rumble_is_dropped() {
+    let (pad, xpad) = pad();
+    let interval = Duration::from_millis(20);
+    xpad.set_rumble_interval(interval);
+    let rumble = |strong| RumbleMagnitudes {
+        strong,
+        ..RumbleMagnitudes::default()
+    };
+    xpad_play_effect(&xpad, &rumble(0x8000)).unwrap();
+    xpad_play_effect(&xpad, &rumble(0xffff)).unwrap();
+    assert_eq!(pad.transport.take().len(), 1);
+
+    complete(&pad, &xpad, UsbStatus::Disconnected);
+    pad.clock.advance(interval);
+    assert_eq!(xpad_rumble_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn in_flight_output_is_not_retried() {
+    let (pad, xpad) = pad();
+    xpad.send_output_packet(&[0x01, 0x03, 0x02]).unwrap();
+    pad.transport.take();
+
+    complete(&pad, &xpad, UsbStatus::Disconnected);
+    // The cancelled URB still completes, with an error
+    xpad.out_completed(Err(UsbError::Stall));
+    pad.clock.advance(Duration::from_secs(1));
+    assert_eq!(xpad_out_retry_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn new_output_is_refused() {
+    let (pad, xpad) = pad();
+    complete(&pad, &xpad, UsbStatus::Disconnected);
+    assert!(xpad.send_output_packet(&[0x01, 0x03, 0x02]).is_err());
+    assert!(pad.transport.take().is_empty());
+}
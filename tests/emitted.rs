@@ -0,0 +1,82 @@
+use super::*;
+
+fn pad_for(ids: (u16, u16)) -> XpadFixture {
+    let device = XPAD_DEVICES.get(&ids).unwrap();
+    XpadFixture::new(device.xtype, device.mapping, device.quirks).ids(ids.0, ids.1)
+}
+
+// The emitted lists against the capabilities build_capabilities registers
+// and against what decoding actually reported
+fn assert_matches(xpad: &UsbXpad, events: &[SinkEvent]) {
+    let caps = xpad_capabilities(xpad);
+    assert_eq!(xpad.emitted_buttons(), caps.buttons);
+    assert_eq!(xpad.emitted_axes(), caps.axes);
+
+    let buttons = xpad.emitted_buttons();
+    let axes = xpad.emitted_axes();
+    for event in events {
+        match *event {
+            SinkEvent::Key(button, _) => {
+                assert!(buttons.contains(&button), "{:?} not advertised", button)
+            }
+            SinkEvent::Abs(axis, _) => {
+                assert!(axes.contains(&axis), "{:?} not advertised", axis)
+            }
+            _ => (),
+        }
+    }
+    for button in buttons {
+        let reported = events.contains(&SinkEvent::Key(button, true));
+        assert!(reported, "{:?} advertised, never reported", button);
+    }
+    for axis in axes {
+        let reported = events
+            .iter()
+            .any(|event| matches!(*event, SinkEvent::Abs(a, _) if a == axis));
+        assert!(reported, "{:?} advertised, never reported", axis);
+    }
+}
+
+#[test]
+fn dance_pad_emits_dpad_buttons_and_no_hat() {
+    let pad = pad_for((0x0e6f, 0x0105));
+    let xpad = pad.build();
+
+    let mut report = report_360();
+    report[2] = 0xff;
+    report[3] = 0xff;
+    report[4] = 0xff;
+    report[5] = 0xff;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+
+    assert_matches(&xpad, &events);
+    assert!(xpad.emitted_buttons().contains(&Button::TriggerHappy1));
+    assert!(!xpad.emitted_axes().contains(&AbsoluteAxis::Hat0X));
+}
+
+#[test]
+fn elite_2_emits_paddles() {
+    let pad = pad_for((0x045e, 0x0b00));
+    let xpad = pad.build().with_serial_and_firmware(None, 0x050b);
+    handshake(&pad, &xpad);
+
+    let mut report = report_one();
+    report[4] = 0xfc;
+    report[5] = 0xff;
+    report[6..10].copy_from_slice(&[0xff, 0x03, 0xff, 0x03]);
+    xpad.decode(&report);
+
+    let mut paddles = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    paddles[0] = GIP_CMD_FIRMWARE;
+    paddles[GIP_ELITE_PADDLES_OFFSET] = 0x0f;
+    xpad.decode(&paddles);
+
+    xpad.decode(&[GIP_CMD_VIRTUAL_KEY, 0x00, 0x00, 0x02, 0x01]);
+    let events = pad.sink.take();
+
+    assert_matches(&xpad, &events);
+    for button in XPAD_BTN_PADDLES {
+        assert!(xpad.emitted_buttons().contains(&button), "{:?}", button);
+    }
+}
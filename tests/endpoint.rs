@@ -0,0 +1,45 @@
+use super::*;
+
+fn ep(address: u8, interrupt: bool) -> EndpointInfo {
+    EndpointInfo { address, interrupt }
+}
+
+#[test]
+fn picks_the_interrupt_out_endpoint() {
+    // The usual layout: interrupt in on 0x81, out on 0x02
+    assert_eq!(
+        find_out_endpoint(&[ep(0x81, true), ep(0x02, true)]),
+        Some(0x02)
+    );
+    // Out on an unusual address, behind a bulk endpoint and a second in
+    let quirky = [
+        ep(0x81, true),
+        ep(0x03, false),
+        ep(0x83, true),
+        ep(0x05, true),
+    ];
+    assert_eq!(find_out_endpoint(&quirky), Some(0x05));
+    // First one listed on a tie
+    assert_eq!(
+        find_out_endpoint(&[ep(0x04, true), ep(0x02, true)]),
+        Some(0x04)
+    );
+}
+
+#[test]
+fn interface_without_an_out_endpoint() {
+    assert_eq!(find_out_endpoint(&[]), None);
+    assert_eq!(find_out_endpoint(&[ep(0x81, true), ep(0x02, false)]), None);
+}
+
+#[test]
+fn pad_keeps_the_endpoint_it_found() {
+    let xpad = UsbXpad::test_fixture(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    assert_eq!(xpad.out_endpoint(), None);
+
+    let xpad = xpad.with_endpoints(&[ep(0x82, true), ep(0x01, true)]);
+    assert_eq!(xpad.out_endpoint(), Some(0x01));
+
+    let xpad = xpad.with_endpoints(&[ep(0x82, true)]);
+    assert_eq!(xpad.out_endpoint(), None);
+}
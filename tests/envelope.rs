@@ -0,0 +1,122 @@
+use super::*;
+
+const MS: Duration = Duration::from_millis(1);
+
+fn motor(magnitude: u16, ramp_ms: u32, length_ms: u32) -> MotorEnvelope {
+    MotorEnvelope {
+        magnitude,
+        attack_level: 0,
+        attack_length: MS * ramp_ms,
+        fade_level: 0,
+        fade_length: MS * ramp_ms,
+        length: MS * length_ms,
+    }
+}
+
+// Strong ramps up over 100ms, holds, and fades out over the last 100ms
+// of 500ms. Weak is flat for the same time, the left trigger motor stops
+// early and the right one stays off.
+fn effect() -> FfEffect {
+    FfEffect::Enveloped([
+        motor(0xc800, 100, 500),
+        motor(0x6400, 0, 500),
+        motor(0x2000, 0, 200),
+        motor(0, 0, 500),
+    ])
+}
+
+fn ready_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    xpad.set_rumble_interval(Duration::ZERO);
+    pad.transport.take();
+    (pad, xpad)
+}
+
+// Left trigger, right trigger, strong and weak bytes of the one rumble
+// packet sent since the last call
+fn motors(pad: &XpadFixture) -> [u8; 4] {
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0][0], GIP_CMD_RUMBLE);
+    assert_eq!(packets[0][5], 0x0f);
+    packets[0][6..10].try_into().unwrap()
+}
+
+#[test]
+fn motors_follow_attack_sustain_and_fade() {
+    let (pad, xpad) = ready_pad();
+    xpad_upload_effect(&xpad, &effect()).unwrap();
+    // Strong starts from its attack level
+    assert_eq!(motors(&pad), [16, 0, 0, 50]);
+
+    let at = |ms: u64| {
+        pad.clock.advance(Duration::from_millis(ms));
+        xpad_envelope_tick(&xpad)
+    };
+
+    // Halfway up the attack
+    assert_eq!(at(50), Some(Duration::from_millis(ENVELOPE_TICK_MS)));
+    assert_eq!(motors(&pad), [16, 0, 50, 50]);
+    // Sustain
+    at(100);
+    assert_eq!(motors(&pad), [16, 0, 100, 50]);
+    // Left trigger motor is done
+    at(100);
+    assert_eq!(motors(&pad), [0, 0, 100, 50]);
+    // Halfway through the fade
+    at(200);
+    assert_eq!(motors(&pad), [0, 0, 50, 50]);
+
+    // Over: everything stops and the tick isn't rescheduled
+    assert_eq!(at(50), None);
+    assert_eq!(motors(&pad), [0, 0, 0, 0]);
+    assert!(xpad.rumble_envelope.lock().unwrap().is_none());
+}
+
+#[test]
+fn plain_rumble_replaces_a_running_envelope() {
+    let (pad, xpad) = ready_pad();
+    xpad_upload_effect(&xpad, &effect()).unwrap();
+    pad.transport.take();
+
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RumbleMagnitudes::default())).unwrap();
+    assert_eq!(motors(&pad), [0, 0, 0, 0]);
+
+    // Nothing left for the tick to send
+    pad.clock.advance(Duration::from_millis(50));
+    assert_eq!(xpad_envelope_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn level_at_the_phase_edges() {
+    let envelope = MotorEnvelope {
+        magnitude: 1000,
+        attack_level: 200,
+        attack_length: MS * 10,
+        fade_level: 400,
+        fade_length: MS * 10,
+        length: MS * 40,
+    };
+    assert_eq!(envelope.level_at(Duration::ZERO), 200);
+    assert_eq!(envelope.level_at(MS * 5), 600);
+    assert_eq!(envelope.level_at(MS * 10), 1000);
+    assert_eq!(envelope.level_at(MS * 29), 1000);
+    assert_eq!(envelope.level_at(MS * 30), 1000);
+    assert_eq!(envelope.level_at(MS * 35), 700);
+    assert_eq!(envelope.level_at(MS * 40), 0);
+}
+
+#[test]
+fn pads_without_motors_refuse_envelopes() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::NO_RUMBLE);
+    let xpad = pad.build();
+    assert!(matches!(
+        xpad_upload_effect(&xpad, &effect()),
+        Err(XpadError::NotSupported)
+    ));
+    assert!(pad.transport.take().is_empty());
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+const PAD: XpadId = XpadId(0);
+
+fn events(queue: &EventQueue) -> Vec<SinkEvent> {
+    queue.drain().into_iter().map(|(_, event)| event).collect()
+}
+
+fn overflow_warnings() -> usize {
+    logged_lines()
+        .iter()
+        .filter(|(_, line)| line.starts_with("Event queue full"))
+        .count()
+}
+
+#[test]
+fn axis_updates_go_before_button_edges() {
+    let queue = EventQueue::new(3);
+    assert!(!queue.push(PAD, SinkEvent::Key(Button::A, true)));
+    assert!(!queue.push(PAD, SinkEvent::Abs(AbsoluteAxis::X, 100)));
+    assert!(!queue.push(PAD, SinkEvent::Sync));
+
+    assert!(queue.push(PAD, SinkEvent::Key(Button::A, false)));
+    assert_eq!(
+        events(&queue),
+        [
+            SinkEvent::Key(Button::A, true),
+            SinkEvent::Sync,
+            SinkEvent::Key(Button::A, false),
+        ]
+    );
+}
+
+#[test]
+fn edges_go_only_when_nothing_else_is_left() {
+    let queue = EventQueue::new(3);
+    queue.push(PAD, SinkEvent::Key(Button::A, true));
+    queue.push(PAD, SinkEvent::Sync);
+    queue.push(PAD, SinkEvent::Key(Button::B, true));
+    assert!(queue.push(PAD, SinkEvent::Sync));
+    assert_eq!(
+        events(&queue),
+        [
+            SinkEvent::Sync,
+            SinkEvent::Key(Button::B, true),
+            SinkEvent::Sync,
+        ]
+    );
+}
+
+#[test]
+fn frame_ends_alone_drop_the_oldest() {
+    let queue = EventQueue::new(2);
+    queue.push(PAD, SinkEvent::Sync);
+    queue.push(PAD, SinkEvent::Sync);
+    assert!(queue.push(PAD, SinkEvent::Sync));
+    assert_eq!(events(&queue), [SinkEvent::Sync, SinkEvent::Sync]);
+}
+
+#[test]
+fn each_overflow_burst_is_logged_once() {
+    let queue = EventQueue::new(1);
+    overflow_warnings();
+    for value in 0..5 {
+        queue.push(PAD, SinkEvent::Abs(AbsoluteAxis::X, value));
+    }
+    assert_eq!(overflow_warnings(), 1);
+
+    queue.drain();
+    queue.push(PAD, SinkEvent::Abs(AbsoluteAxis::X, 0));
+    assert_eq!(overflow_warnings(), 0);
+    queue.push(PAD, SinkEvent::Abs(AbsoluteAxis::X, 1));
+    assert_eq!(overflow_warnings(), 1);
+}
+
+// Everything a full 360 frame with A held puts in the manager's queue
+fn frame_through(manager: &XpadManager) -> (XpadId, Vec<SinkEvent>) {
+    let id = manager.attach(UsbXpad::test_fixture(
+        XType::Xbox360,
+        MapFlags::empty(),
+        QuirkFlags::empty(),
+    ));
+    let mut report = report_360();
+    report[3] = 0x10;
+    manager.get(id).unwrap().decode(&report);
+    let events = manager
+        .poll_events()
+        .into_iter()
+        .map(|(_, event)| event)
+        .collect();
+    (id, events)
+}
+
+#[test]
+fn full_queue_keeps_the_buttons_and_counts_the_rest() {
+    let (_, all) = frame_through(&XpadManager::new());
+    let kept: Vec<SinkEvent> = all
+        .iter()
+        .filter(|event| !matches!(event, SinkEvent::Abs(..)))
+        .cloned()
+        .collect();
+    assert!(kept.len() < all.len());
+
+    let manager = XpadManager::new().with_event_capacity(kept.len());
+    let (id, events) = frame_through(&manager);
+    assert_eq!(events, kept);
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert_eq!(
+        manager.get(id).unwrap().diagnostics().events_dropped,
+        (all.len() - kept.len()) as u64
+    );
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn export_is_sorted_and_has_one_record_per_id() {
+    let records = export_device_table();
+    assert!(records
+        .windows(2)
+        .all(|w| (w[0].vendor, w[0].product) < (w[1].vendor, w[1].product)));
+    assert!(records.len() >= XPAD_DEVICES.len());
+}
+
+// Read a record back from its JSON, flags by name
+#[cfg(feature = "serde")]
+fn from_json(value: &serde_json::Value) -> DeviceRecord {
+    let names = |key: &str| -> Vec<String> {
+        value[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|name| name.as_str().unwrap().to_string())
+            .collect()
+    };
+    DeviceRecord {
+        vendor: value["vendor"].as_u64().unwrap() as u16,
+        product: value["product"].as_u64().unwrap() as u16,
+        name: value["name"].as_str().unwrap().to_string(),
+        xtype: match value["xtype"].as_str().unwrap() {
+            "Xbox" => XType::Xbox,
+            "Xbox360" => XType::Xbox360,
+            "Xbox360W" => XType::Xbox360W,
+            "XboxOne" => XType::XboxOne,
+            other => panic!("unknown xtype {}", other),
+        },
+        mapping: names("mapping")
+            .iter()
+            .map(|name| MapFlags::from_name(name).unwrap())
+            .fold(MapFlags::empty(), |a, b| a | b),
+        quirks: names("quirks")
+            .iter()
+            .map(|name| QuirkFlags::from_name(name).unwrap())
+            .fold(QuirkFlags::empty(), |a, b| a | b),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn records_round_trip_through_json_with_flags_by_name() {
+    let records = export_device_table();
+    for ids in [(0x045e, 0x0b12), (0x1430, 0x079b), (0x0738, 0x4718)] {
+        let record = records
+            .iter()
+            .find(|r| (r.vendor, r.product) == ids)
+            .unwrap();
+        let json = serde_json::to_value(record).unwrap();
+        assert!(json["mapping"].is_array() && json["quirks"].is_array());
+        assert_eq!(&from_json(&json), record);
+    }
+
+    let json = serde_json::to_value(
+        records
+            .iter()
+            .find(|r| (r.vendor, r.product) == (0x045e, 0x0b12))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json["mapping"],
+        serde_json::json!(["SELECT_BUTTON", "SHARE_OFFSET"])
+    );
+}
@@ -0,0 +1,62 @@
+use super::*;
+
+// A pad set up from its table entry
+fn from_table(id_vendor: u16, id_product: u16) -> UsbXpad {
+    let device = XPAD_DEVICES.get(&(id_vendor, id_product)).unwrap();
+    XpadFixture::new(device.xtype, device.mapping, device.quirks)
+        .ids(id_vendor, id_product)
+        .build()
+}
+
+#[test]
+fn elite_2_has_paddles_and_rumble() {
+    let features = from_table(0x045e, 0x0b00).features();
+    assert_eq!(
+        features,
+        FeatureSet::RUMBLE
+            | FeatureSet::TRIGGER_RUMBLE
+            | FeatureSet::PADDLES
+            | FeatureSet::GUIDE_LED
+    );
+}
+
+#[test]
+fn wheel_has_force_feedback_but_no_paddles() {
+    let features = from_table(0x0f0d, 0x0151).features();
+    assert!(features.contains(FeatureSet::RUMBLE));
+    assert!(!features.contains(FeatureSet::PADDLES));
+    // Impulse triggers are Microsoft's own
+    assert!(!features.contains(FeatureSet::TRIGGER_RUMBLE));
+}
+
+#[test]
+fn xbox360_pads_have_player_leds() {
+    let wired = from_table(0x045e, 0x028e).features();
+    assert_eq!(wired, FeatureSet::RUMBLE | FeatureSet::PLAYER_LEDS);
+
+    let wireless = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719)
+        .build()
+        .features();
+    assert_eq!(
+        wireless,
+        FeatureSet::RUMBLE | FeatureSet::PLAYER_LEDS | FeatureSet::BATTERY
+    );
+}
+
+#[test]
+fn ghl_guitar_has_none_of_it() {
+    let guitar = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE)
+        .ids(0x1430, 0x079b)
+        .build();
+    assert_eq!(guitar.features(), FeatureSet::empty());
+}
+
+#[test]
+fn descriptor_overrides_the_table() {
+    let xpad = from_table(0x045e, 0x0b00);
+    *xpad.gip_caps.lock().unwrap() = Some(GipCapabilities::RUMBLE | GipCapabilities::SHARE);
+    let features = xpad.features();
+    assert!(features.contains(FeatureSet::RUMBLE | FeatureSet::SHARE));
+    assert!(!features.intersects(FeatureSet::PADDLES | FeatureSet::TRIGGER_RUMBLE));
+}
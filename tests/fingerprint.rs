@@ -0,0 +1,73 @@
+use super::*;
+
+fn pad() -> UsbXpad {
+    UsbXpad::test_fixture(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+}
+
+fn with_serial(serial: &str) -> UsbXpad {
+    pad().with_serial_and_firmware(Some(serial), 0x0408)
+}
+
+#[test]
+fn different_serials_give_different_fingerprints() {
+    assert_ne!(
+        with_serial("0123456789").fingerprint(),
+        with_serial("9876543210").fingerprint()
+    );
+}
+
+#[test]
+fn same_serial_gives_the_same_fingerprint() {
+    let first = with_serial("0123456789").fingerprint();
+    assert_eq!(first, with_serial("0123456789").fingerprint());
+    assert_eq!(first, "045e:028e/serial=0123456789");
+}
+
+// The serial follows the pad, whatever port it is plugged into
+#[test]
+fn serial_wins_over_the_port() {
+    let first = with_serial("0123456789").with_port_path("1-4.2");
+    let second = with_serial("0123456789").with_port_path("3-1");
+    assert_eq!(first.fingerprint(), second.fingerprint());
+}
+
+#[test]
+fn without_a_serial_the_port_tells_pads_apart() {
+    let first = pad().with_port_path("1-4.2");
+    let second = pad().with_port_path("1-4.3");
+    assert_ne!(first.fingerprint(), second.fingerprint());
+    assert_eq!(first.fingerprint(), "045e:028e/port=1-4.2");
+    assert_eq!(
+        first.fingerprint(),
+        pad().with_port_path("1-4.2").fingerprint()
+    );
+}
+
+#[test]
+fn interface_is_the_last_resort() {
+    let intf = |number| InterfaceInfo {
+        number,
+        class: linux_usb::USB_CLASS_VENDOR_SPEC,
+        subclass: 93,
+        protocol: 129,
+    };
+    let first = pad().with_interface(intf(0));
+    let second = pad().with_interface(intf(2));
+    assert_ne!(first.fingerprint(), second.fingerprint());
+    assert_eq!(second.fingerprint(), "045e:028e/interface=2");
+
+    // A port path is preferred to it
+    assert_eq!(
+        pad()
+            .with_interface(intf(2))
+            .with_port_path("1-4")
+            .fingerprint(),
+        "045e:028e/port=1-4"
+    );
+}
+
+#[test]
+fn nothing_but_the_ids_collides() {
+    assert_eq!(pad().fingerprint(), "045e:028e");
+    assert_eq!(pad().fingerprint(), pad().fingerprint());
+}
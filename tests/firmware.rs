@@ -0,0 +1,91 @@
+use super::*;
+
+const ELITE_2: (u16, u16) = (0x045e, 0x0b00);
+
+fn version(bcd: u16) -> FirmwareVersion {
+    FirmwareVersion::from_bcd(bcd)
+}
+
+fn elite_2(firmware: u16) -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::PADDLES, QuirkFlags::empty())
+        .ids(ELITE_2.0, ELITE_2.1);
+    let xpad = pad.build().with_serial_and_firmware(None, firmware);
+    (pad, xpad)
+}
+
+#[test]
+fn bcd_is_major_then_minor() {
+    assert_eq!(
+        version(0x050b),
+        FirmwareVersion {
+            major: 5,
+            minor: 11
+        }
+    );
+    // Minor compares as a number, 5.9 is older than 5.11
+    assert!(version(0x0509) < version(0x050b));
+    assert!(version(0x04ff) < version(0x0500));
+}
+
+#[test]
+fn version_comes_from_bcd_device() {
+    let (_pad, xpad) = elite_2(0x0513);
+    assert_eq!(xpad.firmware_version(), Some(version(0x0513)));
+
+    let xpad = UsbXpad::test_fixture(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    assert_eq!(xpad.firmware_version(), None);
+}
+
+#[test]
+fn elite_2_before_5_11_has_the_paddle_layout_issue() {
+    for bcd in [0x0420, 0x0500, 0x0509] {
+        assert_eq!(
+            known_firmware_issues(&version(bcd), ELITE_2),
+            [KnownIssue::Elite2LegacyPaddleLayout],
+            "{:#06x}",
+            bcd
+        );
+    }
+}
+
+#[test]
+fn elite_2_from_5_11_on_has_no_issues() {
+    for bcd in [0x050b, 0x0513, 0x0600] {
+        assert!(known_firmware_issues(&version(bcd), ELITE_2).is_empty());
+    }
+}
+
+#[test]
+fn other_pads_have_no_issues_at_any_version() {
+    for product in [(0x045e, 0x02e3), (0x045e, 0x02ea), (0x045e, 0x028e)] {
+        assert!(known_firmware_issues(&version(0x0100), product).is_empty());
+    }
+}
+
+#[test]
+fn firmware_picks_the_elite_2_report_format() {
+    for (bcd, packet_type) in [
+        (0x0420, PacketType::Xbe2FwOld),
+        (0x0509, PacketType::Xbe2Fw5Early),
+        (0x050b, PacketType::Xbe2Fw5_11),
+    ] {
+        assert_eq!(elite_2(bcd).1.packet_type, packet_type, "{:#06x}", bcd);
+    }
+}
+
+// What the issue warns about: the same paddle report only decodes on 5.11
+#[test]
+fn paddles_only_decode_without_the_issue() {
+    let mut report = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    report[0] = GIP_CMD_FIRMWARE;
+    report[GIP_ELITE_PADDLES_OFFSET] = 0x01;
+
+    for (bcd, decoded) in [(0x0509, false), (0x050b, true)] {
+        let (pad, xpad) = elite_2(bcd);
+        handshake(&pad, &xpad);
+        pad.sink.take();
+        xpad.decode(&report);
+        let paddle = SinkEvent::Key(XPAD_BTN_PADDLES[0], true);
+        assert_eq!(pad.sink.take().contains(&paddle), decoded, "{:#06x}", bcd);
+    }
+}
@@ -0,0 +1,30 @@
+use super::*;
+
+#[test]
+fn fixture_decodes_a_frame_without_usb() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    // A held, left stick X at 0x4000
+    let mut report = report_360();
+    report[3] = 0x10;
+    report[6..8].copy_from_slice(&0x4000i16.to_le_bytes());
+    xpad.decode(&report);
+
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::X, 0x4000)));
+    assert_eq!(events.last(), Some(&SinkEvent::Sync));
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn test_fixture_takes_the_protocol_from_the_caller() {
+    let xpad = UsbXpad::test_fixture(
+        XType::XboxOne,
+        MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::empty(),
+    );
+    assert_eq!(xpad.protocol(), XType::XboxOne);
+    assert!(xpad.mapping().contains(MapFlags::DPAD_TO_BUTTONS));
+}
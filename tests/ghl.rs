@@ -0,0 +1,124 @@
+use super::*;
+
+fn guitar() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE)
+        .ids(0x1430, 0x079b);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    (pad, xpad)
+}
+
+// GHL report with the strum bar pushed up, d-pad value 0 in byte 6
+fn strum_up() -> Vec<u8> {
+    let mut report = vec![0u8; 28];
+    report[0] = 0x21;
+    report
+}
+
+#[test]
+fn strum_is_the_hat_by_default() {
+    let (pad, xpad) = guitar();
+    xpad.decode(&strum_up());
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Hat0Y, -1)));
+    assert!(!events.contains(&SinkEvent::Key(Button::TriggerHappy1, true)));
+}
+
+#[test]
+fn strum_buttons_replace_the_hat() {
+    let (pad, xpad) = guitar();
+    assert!(xpad.set_ghl_strum_buttons(true));
+
+    xpad.decode(&strum_up());
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::TriggerHappy1, true)));
+    assert!(events.contains(&SinkEvent::Key(Button::TriggerHappy2, false)));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::Hat0Y, _))));
+
+    let caps = xpad_capabilities(&xpad);
+    assert!(caps.buttons.contains(&Button::TriggerHappy1));
+    assert!(!caps.axes.contains(&AbsoluteAxis::Hat0Y));
+}
+
+#[test]
+fn only_a_change_asks_for_new_capabilities() {
+    let (_pad, xpad) = guitar();
+    assert!(!xpad.set_ghl_strum_buttons(false));
+    assert!(xpad.set_ghl_strum_buttons(true));
+    assert!(!xpad.set_ghl_strum_buttons(true));
+
+    // Other pads don't advertise the strum buttons either way
+    let other = UsbXpad::test_fixture(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    assert!(!other.set_ghl_strum_buttons(true));
+}
+
+#[test]
+fn rumble_is_not_supported_and_sends_nothing() {
+    let (pad, xpad) = guitar();
+    pad.transport.take();
+    assert!(!xpad.supports_rumble());
+    assert!(!xpad.supports_trigger_rumble());
+
+    let rumble = RumbleMagnitudes {
+        strong: 0xffff,
+        weak: 0xffff,
+        left_trigger: 0xffff,
+        right_trigger: 0xffff,
+    };
+    for magnitudes in [rumble, RumbleMagnitudes::default()] {
+        assert!(matches!(
+            xpad_play_effect(&xpad, &magnitudes),
+            Err(XpadError::NotSupported)
+        ));
+    }
+    assert!(pad.transport.take().is_empty());
+
+    let entry = XPAD_DEVICES.get(&(0x1430, 0x079b)).unwrap();
+    assert!(!entry.supports_rumble());
+}
+
+// The packet with its sequence number and option bits left out
+fn is_poke(packet: &[u8]) -> bool {
+    packet.len() == GHL_XBOXONE_POKE.len()
+        && packet[0] == GHL_XBOXONE_POKE[0]
+        && packet[3..] == GHL_XBOXONE_POKE[3..]
+}
+
+#[test]
+fn init_sequence_ends_with_the_wakeup() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE)
+        .ids(0x1430, 0x079b);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+
+    assert_eq!(packets.iter().filter(|packet| is_poke(packet)).count(), 1);
+    let last = packets.last().unwrap();
+    assert!(is_poke(last));
+    // Numbered like the rest, and the one the guitar acks
+    assert_eq!(last[2] as usize, packets.len() - 1);
+    assert_ne!(last[1] & GIP_OPT_ACK, 0);
+}
+
+#[test]
+fn other_pads_get_no_wakeup() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x1430, 0x079b);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    assert!(!pad.transport.take().iter().any(|packet| is_poke(packet)));
+}
+
+#[test]
+fn guitar_reports_after_the_wakeup_is_acked() {
+    let (pad, xpad) = guitar();
+    assert_eq!(xpad.init_state(), InitState::Ready);
+    xpad.decode(&strum_up());
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Abs(AbsoluteAxis::Hat0Y, -1)));
+}
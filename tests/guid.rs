@@ -0,0 +1,51 @@
+use super::*;
+
+fn hex(guid: &[u8; 16]) -> String {
+    guid.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn wired_360_pad_matches_the_sdl_mapping_database() {
+    // The Linux "X360 Controller" entry of SDL's gamecontrollerdb
+    let pad = XPAD_DEVICES.get(&(0x045e, 0x028e)).unwrap();
+    assert_eq!(
+        hex(&controller_guid(pad, 0x0114)),
+        "030000005e0400008e02000014010000"
+    );
+}
+
+#[test]
+fn fields_land_in_their_slots_little_endian() {
+    let pad = XpadDevice {
+        id_vendor: 0x1234,
+        id_product: 0xabcd,
+        name: "Synthetic pad",
+        mapping: MapFlags::empty(),
+        xtype: XType::XboxOne,
+        quirks: QuirkFlags::empty(),
+    };
+    let guid = controller_guid(&pad, 0x0507);
+    assert_eq!(guid[0..2], [0x03, 0x00]);
+    assert_eq!(guid[2..4], [0x00, 0x00]);
+    assert_eq!(guid[4..6], [0x34, 0x12]);
+    assert_eq!(guid[6..8], [0x00, 0x00]);
+    assert_eq!(guid[8..10], [0xcd, 0xab]);
+    assert_eq!(guid[10..12], [0x00, 0x00]);
+    assert_eq!(guid[12..14], [0x07, 0x05]);
+    assert_eq!(guid[14..16], [0x00, 0x00]);
+}
+
+#[test]
+fn only_ids_and_version_matter() {
+    // Name, mapping and protocol aren't part of the GUID
+    let pad = XPAD_DEVICES.get(&(0x045e, 0x028e)).unwrap();
+    let mut other = pad.clone();
+    other.name = "Renamed pad";
+    other.mapping = MapFlags::DPAD_TO_BUTTONS;
+    other.xtype = XType::Xbox360W;
+    assert_eq!(
+        controller_guid(pad, 0x0114),
+        controller_guid(&other, 0x0114)
+    );
+    assert_ne!(controller_guid(pad, 0x0114), controller_guid(pad, 0x0110));
+}
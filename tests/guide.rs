@@ -0,0 +1,125 @@
+use super::*;
+
+const THRESHOLD: Duration = Duration::from_millis(800);
+
+fn guide(pressed: bool) -> [u8; 20] {
+    let mut report = report_360();
+    if pressed {
+        report[3] = 0x04;
+    }
+    report
+}
+
+fn with_longpress() -> (XpadFixture, UsbXpad, Arc<AtomicUsize>) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let fired = Arc::new(AtomicUsize::new(0));
+    let counter = fired.clone();
+    xpad.set_guide_longpress_handler(
+        THRESHOLD,
+        Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }),
+    );
+    (pad, xpad, fired)
+}
+
+#[test]
+fn held_guide_fires_from_the_timer_without_another_report() {
+    let (pad, xpad, fired) = with_longpress();
+    xpad.decode(&guide(true));
+    assert_eq!(xpad_guide_tick(&xpad), Some(THRESHOLD));
+
+    // A pad only reports changes, nothing arrives while the guide is held
+    pad.clock.advance(THRESHOLD);
+    assert_eq!(xpad_guide_tick(&xpad), None);
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    xpad.decode(&guide(false));
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Mode, _))));
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn short_press_is_a_tap_and_leaves_nothing_for_the_timer() {
+    let (pad, xpad, fired) = with_longpress();
+    xpad.decode(&guide(true));
+    pad.clock.advance(THRESHOLD / 2);
+    xpad.decode(&guide(false));
+
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::Mode, true)));
+    assert!(events.contains(&SinkEvent::Key(Button::Mode, false)));
+
+    pad.clock.advance(THRESHOLD);
+    assert_eq!(xpad_guide_tick(&xpad), None);
+    assert_eq!(fired.load(Ordering::SeqCst), 0);
+}
+
+fn virtual_key(seq: u8, pressed: bool) -> [u8; 6] {
+    [
+        GIP_CMD_VIRTUAL_KEY,
+        GIP_OPT_ACK | GIP_OPT_INTERNAL,
+        seq,
+        0x02,
+        pressed as u8,
+        0x5b,
+    ]
+}
+
+fn ready_xboxone() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    pad.transport.take();
+    (pad, xpad)
+}
+
+#[test]
+fn repeated_virtual_key_reports_are_one_press() {
+    let (pad, xpad) = ready_xboxone();
+
+    // Each report is repeated, as a pad does until it sees the ack
+    for (seq, pressed) in [(1, true), (1, true), (2, false), (2, false)] {
+        xpad.decode(&virtual_key(seq, pressed));
+    }
+
+    let guide: Vec<_> = pad
+        .sink
+        .take()
+        .into_iter()
+        .filter(|event| matches!(event, SinkEvent::Key(Button::Mode, _)))
+        .collect();
+    assert_eq!(
+        guide,
+        [
+            SinkEvent::Key(Button::Mode, true),
+            SinkEvent::Key(Button::Mode, false)
+        ]
+    );
+    assert_eq!(pad.transport.take().len(), 4);
+}
+
+#[test]
+fn short_virtual_key_report_is_ignored() {
+    let (pad, xpad) = ready_xboxone();
+    xpad.decode(&virtual_key(1, true)[..4]);
+    assert!(pad.sink.take().is_empty());
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn command_0x08_does_not_touch_the_guide() {
+    let (pad, xpad) = ready_xboxone();
+    xpad.decode(&[0x08, GIP_OPT_INTERNAL, 0x01, 0x01, 0x01]);
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Mode, _))));
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+// A vendor report the size of an input one, with bytes that would read as
+// Start and A held if decoded as pad input
+fn vendor_report() -> [u8; 20] {
+    let mut report = [0u8; 20];
+    report[0] = 0x08;
+    report[1] = 0x03;
+    report[2] = 0x10;
+    report[3] = 0x10;
+    report
+}
+
+fn legion() -> XpadFixture {
+    XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::empty(),
+        QuirkFlags::HANDHELD_EXTRA_REPORTS,
+    )
+    .ids(0x17ef, 0x6182)
+}
+
+#[test]
+fn handhelds_carry_the_quirk() {
+    for ids in [(0x17ef, 0x6182), (0x2563, 0x058d)] {
+        let device = XPAD_DEVICES.get(&ids).unwrap();
+        assert!(
+            device.quirks.contains(QuirkFlags::HANDHELD_EXTRA_REPORTS),
+            "{:04x?}",
+            ids
+        );
+    }
+}
+
+#[test]
+fn vendor_reports_are_ignored() {
+    let pad = legion();
+    let xpad = pad.build();
+    assert!(xpad
+        .quirk_actions()
+        .contains(&QuirkAction::StandardReportsOnly));
+
+    xpad.decode(&vendor_report());
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn input_reports_still_decode() {
+    let pad = legion();
+    let xpad = pad.build();
+    xpad.decode(&vendor_report());
+    let mut report = report_360();
+    report[3] = 0x10;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(events.contains(&SinkEvent::Key(Button::Start, false)));
+}
+
+#[test]
+fn other_pads_decode_whatever_they_get() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(&vendor_report());
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+fn xboxone_s() -> XpadFixture {
+    XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty()).ids(0x045e, 0x02ea)
+}
+
+#[test]
+fn only_the_last_init_packet_asks_for_an_ack() {
+    let pad = xboxone_s();
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+
+    // Power on, then the One S init
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0][1] & GIP_OPT_ACK, 0);
+    assert_ne!(packets[1][1] & GIP_OPT_ACK, 0);
+    assert_eq!(xpad.init_state(), InitState::Configured);
+}
+
+#[test]
+fn input_before_the_ack_is_dropped_and_counted() {
+    let pad = xboxone_s();
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+
+    let mut report = report_one();
+    report[4] = 0x10; // A
+    xpad.decode(&report);
+    assert!(pad.sink.take().is_empty());
+    assert_eq!(xpad.diagnostics().early_input_dropped, 1);
+
+    xpad.decode(&gip_ack_for(packets.last().unwrap()));
+    assert_eq!(xpad.init_state(), InitState::Ready);
+
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+    assert_eq!(xpad.diagnostics().early_input_dropped, 1);
+}
+
+#[test]
+fn announce_alone_does_not_make_the_pad_ready() {
+    let pad = xboxone_s();
+    let xpad = pad.build();
+    xpad.decode(&[GIP_CMD_ANNOUNCE, GIP_OPT_INTERNAL, 0x00, 0x1c]);
+    assert_eq!(xpad.init_state(), InitState::Announced);
+
+    xpad.decode(&gip_ack_for(&XBOXONE_POWER_ON));
+    assert_eq!(xpad.init_state(), InitState::Announced);
+}
+
+#[test]
+fn ready_handler_fires_once_per_init() {
+    let pad = xboxone_s();
+    let xpad = pad.build();
+    let fired = Arc::new(AtomicUsize::new(0));
+    let counter = fired.clone();
+    xpad.set_ready_handler(Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    }));
+
+    handshake(&pad, &xpad);
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    // A stray second ack doesn't fire it again
+    xpad.decode(&gip_ack_for(&XBOXONE_S_INIT));
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    // reinitialize() re-arms it
+    handshake(&pad, &xpad);
+    assert_eq!(fired.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn rumble_during_init_is_sent_once_ready() {
+    let pad = xboxone_s();
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+
+    let rumble = RumbleMagnitudes {
+        strong: 0x8000,
+        weak: 0x4000,
+        ..Default::default()
+    };
+    xpad_play_effect(&xpad, &rumble).unwrap();
+    assert!(pad.transport.take().is_empty());
+
+    xpad.decode(&gip_ack_for(packets.last().unwrap()));
+    let sent = pad.transport.take();
+    assert!(sent.iter().any(|packet| packet[0] == GIP_CMD_RUMBLE));
+}
+
+#[test]
+fn other_pads_are_ready_once_init_is_sent() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let fired = Arc::new(AtomicUsize::new(0));
+    let counter = fired.clone();
+    xpad.set_ready_handler(Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    }));
+
+    xpad.reinitialize().unwrap();
+    assert_eq!(xpad.init_state(), InitState::Ready);
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+}
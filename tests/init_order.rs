@@ -0,0 +1,62 @@
+use super::*;
+
+fn device(id_vendor: u16, id_product: u16, xtype: XType) -> XpadDevice {
+    XpadDevice {
+        id_vendor,
+        id_product,
+        name: "Synthetic pad",
+        mapping: MapFlags::empty(),
+        xtype,
+        quirks: QuirkFlags::empty(),
+    }
+}
+
+fn ids(group: &InitGroup) -> Vec<u16> {
+    group.devices.iter().map(|d| d.id_product).collect()
+}
+
+#[test]
+fn receiver_group_brings_wired_interfaces_up_first() {
+    // Listed wireless first and with the lower product ids
+    let devices = [
+        device(0x1234, 0x0001, XType::Xbox360W),
+        device(0x1234, 0x0002, XType::Xbox360W),
+        device(0x1234, 0x0010, XType::Xbox360),
+        device(0x1234, 0x0003, XType::XboxOne),
+    ];
+    let groups = init_order(&devices);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].id_vendor, 0x1234);
+    assert_eq!(ids(&groups[0]), [0x0003, 0x0010, 0x0001, 0x0002]);
+}
+
+#[test]
+fn vendors_are_grouped_together_in_id_order() {
+    let devices = [
+        device(0x2000, 0x0001, XType::Xbox360),
+        device(0x1000, 0x0002, XType::Xbox360W),
+        device(0x2000, 0x0002, XType::Xbox360W),
+        device(0x1000, 0x0001, XType::Xbox360),
+    ];
+    let groups = init_order(&devices);
+    let vendors: Vec<u16> = groups.iter().map(|g| g.id_vendor).collect();
+    assert_eq!(vendors, [0x1000, 0x2000]);
+    assert_eq!(ids(&groups[0]), [0x0001, 0x0002]);
+    assert_eq!(ids(&groups[1]), [0x0001, 0x0002]);
+}
+
+#[test]
+fn every_table_entry_is_initialized_once() {
+    let groups = init_order(XPAD_DEVICES.values());
+    let count: usize = groups.iter().map(|g| g.devices.len()).sum();
+    assert_eq!(count, XPAD_DEVICES.len());
+    for group in &groups {
+        assert!(group.devices.iter().all(|d| d.id_vendor == group.id_vendor));
+        let ranks: Vec<u8> = group.devices.iter().map(|d| init_rank(d)).collect();
+        assert!(
+            ranks.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{:04x}",
+            group.id_vendor
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use super::*;
+
+const PACKETS: &[InitPacket] = &[&[0x01, 0x02], &[0x03]];
+
+fn device() -> &'static XpadDevice {
+    XPAD_DEVICES.get(&(0x045e, 0x028e)).unwrap()
+}
+
+#[test]
+fn packets_are_forwarded_in_order() {
+    let transport = RecordingInit::default();
+    send_init_transfer_via(&transport, device(), PACKETS).unwrap();
+    assert_eq!(transport.take(), [vec![0x01, 0x02], vec![0x03]]);
+}
+
+#[test]
+fn transport_errors_are_returned() {
+    let transport = RecordingInit::default();
+    transport.fail_next(kernel::Error::EIO);
+    assert_eq!(
+        send_init_transfer_via(&transport, device(), PACKETS),
+        Err(kernel::Error::EIO)
+    );
+    assert!(transport.take().is_empty());
+
+    // Only the next send fails
+    send_init_transfer_via(&transport, device(), PACKETS).unwrap();
+    assert_eq!(transport.take().len(), 2);
+}
+
+#[test]
+fn bad_lengths_are_refused_before_anything_is_sent() {
+    static TOO_LONG: [u8; XPAD_PKT_LEN + 1] = [0; XPAD_PKT_LEN + 1];
+    let transport = RecordingInit::default();
+    for packets in [&[&[0x01][..], &[]][..], &[&TOO_LONG[..]][..]] {
+        assert_eq!(
+            send_init_transfer_via(&transport, device(), packets),
+            Err(kernel::Error::EINVAL)
+        );
+    }
+    assert!(transport.take().is_empty());
+}
+
+#[test]
+fn a_full_length_packet_is_sent() {
+    static FULL: [u8; XPAD_PKT_LEN] = [0; XPAD_PKT_LEN];
+    let transport = RecordingInit::default();
+    send_init_transfer_via(&transport, device(), &[&FULL]).unwrap();
+    assert_eq!(transport.take(), [FULL.to_vec()]);
+}
@@ -0,0 +1,100 @@
+use super::*;
+
+fn pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+fn first_state() -> ControllerState {
+    ControllerState {
+        buttons: ButtonSet::A,
+        left_stick: (1000, -500),
+        triggers: (10, 0),
+        ..ControllerState::default()
+    }
+}
+
+#[test]
+fn only_the_delta_is_reported() {
+    let (pad, xpad) = pad();
+    xpad.inject_state(&first_state());
+    assert_eq!(
+        pad.sink.take(),
+        [
+            SinkEvent::Key(Button::A, true),
+            SinkEvent::Abs(AbsoluteAxis::X, 1000),
+            SinkEvent::Abs(AbsoluteAxis::Y, -500),
+            SinkEvent::Abs(AbsoluteAxis::Z, 10),
+            SinkEvent::Sync,
+        ]
+    );
+
+    xpad.inject_state(&ControllerState {
+        buttons: ButtonSet::B,
+        triggers: (10, 200),
+        ..first_state()
+    });
+    assert_eq!(
+        pad.sink.take(),
+        [
+            SinkEvent::Key(Button::A, false),
+            SinkEvent::Key(Button::B, true),
+            SinkEvent::Abs(AbsoluteAxis::Rz, 200),
+            SinkEvent::Sync,
+        ]
+    );
+}
+
+#[test]
+fn nothing_is_sent_to_the_pad() {
+    let (pad, xpad) = pad();
+    xpad.inject_state(&first_state());
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn same_state_again_reports_nothing() {
+    let (pad, xpad) = pad();
+    xpad.inject_state(&first_state());
+    pad.sink.take();
+    xpad.inject_state(&first_state());
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn dpad_follows_the_mapping() {
+    let (pad, xpad) = pad();
+    let up = ControllerState {
+        buttons: ButtonSet::DPAD_UP,
+        dpad: (0, -1),
+        ..ControllerState::default()
+    };
+    xpad.inject_state(&up);
+    assert_eq!(
+        pad.sink.take(),
+        [SinkEvent::Abs(AbsoluteAxis::Hat0Y, -1), SinkEvent::Sync]
+    );
+
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    xpad.inject_state(&up);
+    assert_eq!(
+        pad.sink.take(),
+        [SinkEvent::Key(Button::TriggerHappy3, true), SinkEvent::Sync]
+    );
+}
+
+#[test]
+fn injected_input_shows_up_in_the_state() {
+    let (_pad, xpad) = pad();
+    xpad.inject_state(&first_state());
+    let state = xpad.current_state();
+    assert_eq!(state.buttons, ButtonSet::A);
+    assert_eq!(state.left_stick, (1000, -500));
+    assert_eq!(state.triggers, (10, 0));
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+const VENDOR_SPEC: u8 = linux_usb::USB_CLASS_VENDOR_SPEC;
+
+fn intf(number: u8, class: u8, subclass: u8, protocol: u8) -> InterfaceInfo {
+    InterfaceInfo {
+        number,
+        class,
+        subclass,
+        protocol,
+    }
+}
+
+// Headset-style composite: audio class interfaces around the pad's
+fn composite() -> [InterfaceInfo; 4] {
+    [
+        intf(0, 0x01, 0x01, 0x00),
+        intf(1, 0x01, 0x02, 0x00),
+        intf(2, VENDOR_SPEC, 93, 1),
+        intf(3, VENDOR_SPEC, 93, 3),
+    ]
+}
+
+#[test]
+fn composite_device_picks_the_input_interface() {
+    assert_eq!(select_interface(&composite()).unwrap().number, 2);
+
+    // GIP and original Xbox pads as well, lowest number on a tie
+    let gip = [intf(1, VENDOR_SPEC, 71, 208), intf(0, 0x01, 0x01, 0x00)];
+    assert_eq!(select_interface(&gip).unwrap().number, 1);
+    let both = [intf(4, VENDOR_SPEC, 71, 208), intf(2, b'X', b'B', 0)];
+    assert_eq!(select_interface(&both).unwrap().number, 2);
+}
+
+#[test]
+fn device_without_an_input_interface_is_rejected() {
+    let audio_only = [intf(0, 0x01, 0x01, 0x00), intf(1, 0x01, 0x02, 0x00)];
+    assert!(matches!(
+        select_interface(&audio_only),
+        Err(XpadError::NoInputInterface)
+    ));
+    // Vendor-specific, but not a pad subclass
+    assert!(matches!(
+        select_interface(&[intf(0, VENDOR_SPEC, 0x42, 1)]),
+        Err(XpadError::NoInputInterface)
+    ));
+    assert!(matches!(
+        select_interface(&[]),
+        Err(XpadError::NoInputInterface)
+    ));
+}
+
+#[test]
+fn selected_interface_shows_up_in_diagnostics() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert_eq!(xpad.diagnostics().interface, None);
+
+    let xpad = xpad.with_interface(select_interface(&composite()).unwrap());
+    assert_eq!(xpad.diagnostics().interface, Some(2));
+    assert_eq!(xpad.protocol(), XType::Xbox360);
+}
+
+#[test]
+fn selected_interface_decides_the_protocol() {
+    // A 360 table entry bound to a receiver interface is a receiver
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build().with_interface(intf(0, VENDOR_SPEC, 93, 129));
+    assert_eq!(xpad.protocol(), XType::Xbox360W);
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+const VALUES: [i16; 6] = [i16::MIN, -1000, -1, 0, 1000, i16::MAX];
+
+fn y_axes(events: &[SinkEvent]) -> (Option<i32>, Option<i32>) {
+    let last = |axis| {
+        events.iter().rev().find_map(|event| match *event {
+            SinkEvent::Abs(a, value) if a == axis => Some(value),
+            _ => None,
+        })
+    };
+    (last(AbsoluteAxis::Y), last(AbsoluteAxis::Ry))
+}
+
+fn wired(y: i16) -> (Option<i32>, Option<i32>) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[8..10].copy_from_slice(&y.to_le_bytes());
+    report[12..14].copy_from_slice(&y.to_le_bytes());
+    xpad.decode(&report);
+    y_axes(&pad.sink.take())
+}
+
+fn wireless(y: i16) -> (Option<i32>, Option<i32>) {
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719);
+    let xpad = pad.build();
+    let mut report = report_360w();
+    report[4 + 8..4 + 10].copy_from_slice(&y.to_le_bytes());
+    report[4 + 12..4 + 14].copy_from_slice(&y.to_le_bytes());
+    xpad.decode(&report);
+    y_axes(&pad.sink.take())
+}
+
+fn xboxone(y: i16) -> (Option<i32>, Option<i32>) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    let mut report = report_one();
+    report[12..14].copy_from_slice(&y.to_le_bytes());
+    report[16..18].copy_from_slice(&y.to_le_bytes());
+    xpad.decode(&report);
+    y_axes(&pad.sink.take())
+}
+
+#[test]
+fn every_protocol_inverts_y_the_same_way() {
+    for y in VALUES {
+        let expected = Some(i32::from(invert_axis(y)));
+        assert_eq!(wired(y), (expected, expected), "{}", y);
+        assert_eq!(wireless(y), (expected, expected), "{}", y);
+        assert_eq!(xboxone(y), (expected, expected), "{}", y);
+    }
+}
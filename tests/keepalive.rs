@@ -0,0 +1,81 @@
+use super::*;
+
+const INTERVAL: Duration = Duration::from_secs(KEEPALIVE_INTERVAL);
+
+fn fixture(ids: (u16, u16)) -> (XpadFixture, UsbXpad) {
+    let device = XPAD_DEVICES.get(&ids).unwrap();
+    let pad = XpadFixture::new(device.xtype, device.mapping, device.quirks).ids(ids.0, ids.1);
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+// Run PadTimer::Keepalive the way its XpadTimer does: from attach on,
+// each run at the delay the previous one asked for, until `until`
+fn run_keepalive(pad: &XpadFixture, xpad: &UsbXpad, until: Duration) -> Vec<Duration> {
+    let mut sent_at = vec![];
+    let mut elapsed = Duration::ZERO;
+    while elapsed <= until {
+        let Some(next) = xpad_keepalive_tick(xpad) else {
+            break;
+        };
+        sent_at.extend(pad.transport.take().iter().map(|_| elapsed));
+        pad.clock.advance(next);
+        elapsed += next;
+    }
+    sent_at
+}
+
+#[test]
+fn quirked_pads_send_the_keepalive_at_the_interval() {
+    for ids in [(0x20d6, 0x2001), (0x20d6, 0x2009)] {
+        let (pad, xpad) = fixture(ids);
+        assert!(xpad.quirks.contains(QuirkFlags::NEEDS_KEEPALIVE));
+        assert_eq!(
+            run_keepalive(&pad, &xpad, INTERVAL * 3),
+            [Duration::ZERO, INTERVAL, INTERVAL * 2, INTERVAL * 3]
+        );
+    }
+}
+
+#[test]
+fn keepalive_is_the_power_on_request_with_the_next_serial() {
+    let (pad, xpad) = fixture((0x20d6, 0x2001));
+    xpad_keepalive_tick(&xpad);
+    xpad_keepalive_tick(&xpad);
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 2);
+    for packet in &packets {
+        assert_eq!(packet[..2], XBOXONE_POWER_ON[..2]);
+        assert_eq!(packet[3..], XBOXONE_POWER_ON[3..]);
+    }
+    assert_eq!(packets[1][2], packets[0][2].wrapping_add(1));
+}
+
+#[test]
+fn other_pads_send_nothing() {
+    // A first-party pad and a PowerA one without the quirk
+    for ids in [(0x045e, 0x02ea), (0x20d6, 0x281f)] {
+        let (pad, xpad) = fixture(ids);
+        assert_eq!(xpad_keepalive_tick(&xpad), None);
+        assert!(pad.transport.take().is_empty());
+    }
+}
+
+#[test]
+fn interval_can_be_changed() {
+    let (pad, xpad) = fixture((0x20d6, 0x2001));
+    let interval = Duration::from_secs(2);
+    xpad.set_keepalive_interval(interval);
+    assert_eq!(
+        run_keepalive(&pad, &xpad, interval * 2),
+        [Duration::ZERO, interval, interval * 2]
+    );
+}
+
+#[test]
+fn keepalive_stops_on_disconnect() {
+    let (pad, xpad) = fixture((0x20d6, 0x2001));
+    xpad.handle_disconnect();
+    assert_eq!(xpad_keepalive_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
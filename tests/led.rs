@@ -0,0 +1,77 @@
+use super::*;
+
+const INTERVAL: Duration = Duration::from_millis(250);
+
+fn commands(packets: &[Vec<u8>]) -> Vec<u8> {
+    packets.iter().map(|packet| packet[2]).collect()
+}
+
+#[test]
+fn rotation_steps_clockwise_as_the_clock_advances() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    let delay = xpad_set_led(&xpad, LedState::Rotate { interval: INTERVAL }).unwrap();
+    assert_eq!(delay, Some(INTERVAL));
+    for _ in 0..4 {
+        // Not due yet, nothing goes out
+        pad.clock.advance(INTERVAL / 2);
+        assert_eq!(xpad_led_animation_tick(&xpad), Some(INTERVAL / 2));
+        pad.clock.advance(INTERVAL / 2);
+        assert_eq!(xpad_led_animation_tick(&xpad), Some(INTERVAL));
+    }
+
+    let expected: Vec<u8> = LED_ROTATION
+        .iter()
+        .chain(&LED_ROTATION)
+        .take(5)
+        .map(|&command| command as u8)
+        .collect();
+    assert_eq!(commands(&pad.transport.take()), expected);
+}
+
+#[test]
+fn another_state_stops_the_rotation() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad_set_led(&xpad, LedState::Rotate { interval: INTERVAL }).unwrap();
+    pad.clock.advance(INTERVAL);
+    xpad_led_animation_tick(&xpad);
+
+    let pattern = LedCommand::TopRightOn as u8;
+    assert_eq!(
+        xpad_set_led(&xpad, LedState::Pattern(pattern)).unwrap(),
+        None
+    );
+    pad.transport.take();
+
+    pad.clock.advance(INTERVAL * 4);
+    assert_eq!(xpad_led_animation_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn a_new_rotation_starts_over_from_the_first_quadrant() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad_set_led(&xpad, LedState::Rotate { interval: INTERVAL }).unwrap();
+    pad.clock.advance(INTERVAL);
+    xpad_led_animation_tick(&xpad);
+    pad.transport.take();
+
+    xpad_set_led(&xpad, LedState::Rotate { interval: INTERVAL }).unwrap();
+    assert_eq!(commands(&pad.transport.take()), [LED_ROTATION[0] as u8]);
+}
+
+#[test]
+fn pads_without_the_ring_ignore_the_rotation() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    assert_eq!(
+        xpad_set_led(&xpad, LedState::Rotate { interval: INTERVAL }).unwrap(),
+        None
+    );
+    assert_eq!(xpad_led_animation_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
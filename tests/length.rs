@@ -0,0 +1,39 @@
+use super::*;
+
+fn decoded(quirks: QuirkFlags, len: usize) -> Vec<SinkEvent> {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), quirks);
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[3] = 0x10; // A
+    xpad.decode(&report[..len]);
+    pad.sink.take()
+}
+
+#[test]
+fn a_20_byte_360_report_is_accepted() {
+    let events = decoded(QuirkFlags::empty(), 20);
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::Rx, _))));
+}
+
+#[test]
+fn reports_are_checked_against_the_360_minimum_not_64() {
+    assert!(!decoded(QuirkFlags::empty(), XPAD360_REPORT_LEN).is_empty());
+    assert!(decoded(QuirkFlags::empty(), XPAD360_REPORT_LEN - 1).is_empty());
+}
+
+#[test]
+fn short_report_clones_are_accepted_down_to_the_left_stick() {
+    let events = decoded(QuirkFlags::SHORT_REPORTS, XPAD360_SHORT_REPORT_LEN);
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::X, _))));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::Rx, _))));
+
+    assert!(decoded(QuirkFlags::SHORT_REPORTS, XPAD360_SHORT_REPORT_LEN - 1).is_empty());
+}
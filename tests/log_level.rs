@@ -0,0 +1,65 @@
+use super::*;
+
+// A dropped report goes through xpad_debug! and shows what the level
+// does to it
+fn dropped_report_lines(xpad: &UsbXpad) -> Vec<log::Level> {
+    logged_lines();
+    xpad.decode(&report_360()[..4]);
+    logged_lines()
+        .into_iter()
+        .filter(|(_, line)| line.starts_with("Dropped report: "))
+        .map(|(level, _)| level)
+        .collect()
+}
+
+// Sets the level for one test and puts the default back afterwards
+fn with_level(level: LogLevel, test: impl FnOnce()) {
+    let _level = LOG_LEVEL_LOCK.lock().unwrap();
+    set_log_level(level);
+    test();
+    set_log_level(LogLevel::Normal);
+}
+
+#[test]
+fn default_logs_at_debug() {
+    let _level = LOG_LEVEL_LOCK.lock().unwrap();
+    assert_eq!(log_level(), LogLevel::Normal);
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    assert_eq!(dropped_report_lines(&xpad), [log::Level::Debug]);
+}
+
+#[test]
+fn verbose_raises_debug_output_past_an_info_filter() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    with_level(LogLevel::Verbose, || {
+        assert_eq!(dropped_report_lines(&xpad), [log::Level::Info]);
+    });
+}
+
+#[test]
+fn quiet_logs_nothing_of_its_own() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    with_level(LogLevel::Quiet, || {
+        assert!(dropped_report_lines(&xpad).is_empty());
+    });
+}
+
+#[test]
+fn packet_dump_follows_the_level() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let dumps = || {
+        logged_lines();
+        xpad.in_completed(UsbStatus::Success, &report_360(), &*pad.transport)
+            .unwrap();
+        logged_lines()
+            .into_iter()
+            .filter(|(_, line)| line.starts_with("Received packet: "))
+            .map(|(level, _)| level)
+            .collect::<Vec<_>>()
+    };
+    with_level(LogLevel::Verbose, || {
+        assert_eq!(dumps(), [log::Level::Info])
+    });
+    with_level(LogLevel::Quiet, || assert!(dumps().is_empty()));
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+fn ids(device: &XpadDevice) -> (u16, u16) {
+    (device.id_vendor, device.id_product)
+}
+
+#[test]
+fn known_and_unknown_pads_never_get_the_clone_entry() {
+    let pad = lookup_device(0x045e, 0x028e).unwrap();
+    assert_eq!(ids(&pad), (0x045e, 0x028e));
+
+    // Unknown ids get the generic entry, if any, never ffff:ffff
+    for (vendor, product) in [(0x1234, 0x5678), (0xffff, 0x0001), (0x0001, 0xffff)] {
+        if let Some(device) = lookup_device(vendor, product) {
+            assert_ne!(
+                ids(&device),
+                CLONE_CATCH_ALL_ID,
+                "{:04x}:{:04x}",
+                vendor,
+                product
+            );
+        }
+    }
+}
+
+#[test]
+fn exact_clone_id_gets_the_clone_entry() {
+    let device = lookup_device(0xffff, 0xffff).unwrap();
+    assert_eq!(ids(&device), CLONE_CATCH_ALL_ID);
+    assert_eq!(device.name, "Chinese-made Xbox Controller");
+}
+
+// The fallback policy is global, tests that change it take turns
+static POLICY: Mutex<()> = Mutex::new(());
+
+const UNKNOWN: (u16, u16) = (0x1234, 0x5678);
+
+#[test]
+fn unknown_pad_gets_the_generic_entry_by_default() {
+    let _policy = POLICY.lock().unwrap();
+    set_allow_generic_fallback(true);
+    let device = lookup_device(UNKNOWN.0, UNKNOWN.1).unwrap();
+    assert_eq!(ids(&device), (0x0000, 0x0000));
+}
+
+#[test]
+fn unknown_pad_is_not_bound_without_the_fallback() {
+    let _policy = POLICY.lock().unwrap();
+    set_allow_generic_fallback(false);
+    let unknown = lookup_device(UNKNOWN.0, UNKNOWN.1);
+    // Table entries are still found
+    let known = lookup_device(0x045e, 0x028e);
+    set_allow_generic_fallback(true);
+
+    assert!(unknown.is_none());
+    assert_eq!(ids(&known.unwrap()), (0x045e, 0x028e));
+}
+
+// Protocol an unknown device on `intf` ends up with under `policy`, None
+// if it isn't bound. Policy put back to the default before returning.
+fn bound_as(policy: FallbackPolicy, intf: &InterfaceInfo) -> Option<XType> {
+    set_fallback_policy(policy);
+    let xpad = lookup_device(UNKNOWN.0, UNKNOWN.1).map(|device| {
+        XpadFixture::new(device.xtype, device.mapping, device.quirks)
+            .ids(device.id_vendor, device.id_product)
+            .build()
+            .with_interface(*intf)
+    });
+    set_fallback_policy(FallbackPolicy::Infer);
+    xpad.map(|xpad| xpad.protocol())
+}
+
+fn vendor_spec(subclass: u8, protocol: u8) -> InterfaceInfo {
+    InterfaceInfo {
+        number: 0,
+        class: linux_usb::USB_CLASS_VENDOR_SPEC,
+        subclass,
+        protocol,
+    }
+}
+
+#[test]
+fn default_policy_infers_from_the_interface() {
+    let _policy = POLICY.lock().unwrap();
+    assert_eq!(fallback_policy(), FallbackPolicy::Infer);
+    assert_eq!(
+        bound_as(FallbackPolicy::Infer, &vendor_spec(71, 208)),
+        Some(XType::XboxOne)
+    );
+    assert_eq!(
+        bound_as(FallbackPolicy::Infer, &vendor_spec(93, 1)),
+        Some(XType::Xbox360)
+    );
+}
+
+#[test]
+fn forced_policies_override_the_interface() {
+    let _policy = POLICY.lock().unwrap();
+    for (policy, xtype) in [
+        (FallbackPolicy::AllowAsXbox, XType::Xbox),
+        (FallbackPolicy::AllowAsXbox360, XType::Xbox360),
+        (FallbackPolicy::AllowAsXboxOne, XType::XboxOne),
+    ] {
+        for intf in [vendor_spec(71, 208), vendor_spec(93, 1)] {
+            assert_eq!(bound_as(policy, &intf), Some(xtype), "{:?}", policy);
+        }
+    }
+}
+
+#[test]
+fn reject_policy_binds_nothing() {
+    let _policy = POLICY.lock().unwrap();
+    assert_eq!(
+        bound_as(FallbackPolicy::Reject, &vendor_spec(71, 208)),
+        None
+    );
+    // Only unknown devices are affected
+    set_fallback_policy(FallbackPolicy::Reject);
+    let known = lookup_device(0x045e, 0x02ea);
+    set_fallback_policy(FallbackPolicy::Infer);
+    assert_eq!(known.unwrap().xtype, XType::XboxOne);
+}
+
+#[test]
+fn forced_policy_leaves_table_entries_alone() {
+    let _policy = POLICY.lock().unwrap();
+    set_fallback_policy(FallbackPolicy::AllowAsXbox);
+    let known = lookup_device(0x045e, 0x02ea).unwrap();
+    let xpad = XpadFixture::new(known.xtype, known.mapping, known.quirks)
+        .ids(known.id_vendor, known.id_product)
+        .build()
+        .with_interface(vendor_spec(71, 208));
+    set_fallback_policy(FallbackPolicy::Infer);
+    assert_eq!(xpad.protocol(), XType::XboxOne);
+}
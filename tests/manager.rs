@@ -0,0 +1,74 @@
+use super::*;
+
+fn press_a(manager: &XpadManager, id: XpadId) {
+    let mut report = report_360();
+    report[3] = 0x10;
+    manager.get(id).unwrap().decode(&report);
+}
+
+fn pad() -> UsbXpad {
+    UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+}
+
+#[test]
+fn events_are_tagged_with_the_pad_they_came_from() {
+    let manager = XpadManager::new();
+    let first = manager.attach(pad());
+    let second = manager.attach(pad());
+    assert_ne!(first, second);
+
+    press_a(&manager, second);
+    press_a(&manager, first);
+    let events = manager.poll_events();
+    let a = |id| events.contains(&(id, SinkEvent::Key(Button::A, true)));
+    assert!(a(first) && a(second));
+
+    // Each pad's frame is together, second's first
+    let split = events.iter().position(|(id, _)| *id == first).unwrap();
+    assert!(events[..split].iter().all(|(id, _)| *id == second));
+    assert!(events[split..].iter().all(|(id, _)| *id == first));
+    assert_eq!(events[split - 1].1, SinkEvent::Sync);
+
+    assert!(manager.poll_events().is_empty());
+}
+
+#[test]
+fn detached_pads_stop_reporting_but_queued_events_stay() {
+    let manager = XpadManager::new();
+    let id = manager.attach(pad());
+    press_a(&manager, id);
+
+    let xpad = manager.detach(id).unwrap();
+    assert!(manager.get(id).is_none());
+    assert!(manager.detach(id).is_none());
+
+    xpad.decode(&report_360());
+    let events = manager.poll_events();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|(from, _)| *from == id));
+    assert!(events.contains(&(id, SinkEvent::Key(Button::A, true))));
+    assert!(!events.contains(&(id, SinkEvent::Key(Button::A, false))));
+}
+
+#[test]
+fn ids_are_not_reused() {
+    let manager = XpadManager::new();
+    let first = manager.attach(pad());
+    manager.detach(first);
+    assert_ne!(manager.attach(pad()), first);
+}
+
+#[test]
+fn attach_from_several_threads() {
+    let manager = Arc::new(XpadManager::new());
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let manager = manager.clone();
+            std::thread::spawn(move || manager.attach(pad()))
+        })
+        .collect();
+    let mut ids: Vec<XpadId> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn which_mappings_use_the_sticks() {
+    assert!(!DANCEPAD_MAP_CONFIG.uses_analog_sticks());
+    assert!(MapFlags::empty().uses_analog_sticks());
+    assert!(MapFlags::STICK_TO_DPAD.uses_analog_sticks());
+}
+
+#[test]
+fn capabilities_follow_uses_analog_sticks() {
+    for (mapping, sticks) in [
+        (DANCEPAD_MAP_CONFIG, false),
+        (MapFlags::empty(), true),
+        (MapFlags::STICK_TO_DPAD, true),
+    ] {
+        let xpad = UsbXpad::test_fixture(XType::Xbox360, mapping, QuirkFlags::empty());
+        let axes = xpad_capabilities(&xpad).axes;
+        for axis in XPAD_ABS {
+            assert_eq!(axes.contains(&axis), sticks, "{:?} {:?}", mapping, axis);
+        }
+    }
+}
+
+#[test]
+fn dance_pad_reports_no_stick_axes() {
+    let pad = XpadFixture::new(XType::Xbox360, DANCEPAD_MAP_CONFIG, QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[6] = 0xff;
+    xpad.decode(&report);
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(axis, _) if XPAD_ABS.contains(axis))));
+}
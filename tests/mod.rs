@@ -0,0 +1,168 @@
+// The crate's unit tests. A child module of the crate root, so private
+// items are in reach. Pads come from XpadFixture: events land in its
+// RecordingSink, output in its RecordingTransport, and time only moves
+// when its ManualClock is advanced.
+
+use super::*;
+use std::sync::Once;
+use std::thread::{self, ThreadId};
+
+mod accessory;
+mod battery;
+mod capabilities;
+mod caps;
+mod caps_cache;
+mod clamp;
+mod codes;
+mod corpus;
+mod curve;
+mod debounce;
+mod default_led;
+mod deferred_rumble;
+mod describe;
+mod disconnect;
+mod emitted;
+mod endpoint;
+mod envelope;
+mod event_queue;
+mod export;
+mod features;
+mod fingerprint;
+mod firmware;
+mod fixture;
+mod ghl;
+mod guid;
+mod guide;
+mod handheld;
+mod init;
+mod init_order;
+mod init_transfer;
+mod inject;
+mod interface;
+mod invert;
+mod keepalive;
+mod led;
+mod length;
+mod log_level;
+mod lookup;
+mod manager;
+mod mapping;
+mod mode_ack;
+mod mode_chord;
+mod one_dpad;
+mod one_report;
+mod out_retry;
+mod players;
+mod poll;
+mod power;
+mod presets;
+mod pressed;
+mod profile;
+mod protocol;
+mod quirks;
+#[cfg(feature = "experimental")]
+mod raw_output;
+mod refresh;
+mod reinit;
+mod remap;
+mod routing;
+mod rumble;
+mod rumble_cap;
+mod rumble_rate;
+mod runtime_mapping;
+mod scan;
+mod sequences;
+mod settings;
+mod share;
+mod smoothing;
+mod socd;
+mod state;
+mod stick_dpad;
+mod sticks;
+mod sync;
+mod table;
+mod threshold;
+#[cfg(feature = "trace-input")]
+mod trace;
+mod trigger_axes;
+mod triggers;
+mod usb_strings;
+mod watchdog;
+mod wheel;
+mod wireless;
+mod xbox;
+
+// Standard wired 360 input report with nothing held
+fn report_360() -> [u8; 20] {
+    let mut report = [0u8; 20];
+    report[..2].copy_from_slice(&XPAD360_INPUT_HEADER);
+    report
+}
+
+// 360 wireless receiver report carrying pad data: the 360 report from
+// byte 4 on
+fn report_360w() -> Vec<u8> {
+    let mut report = vec![0x00, 0x01, 0x00, 0xf0];
+    report.extend_from_slice(&report_360());
+    report
+}
+
+// Xbox One input report, XPADONE_REPORT_LEN bytes with nothing held
+fn report_one() -> Vec<u8> {
+    let mut report = vec![0u8; XPADONE_REPORT_LEN];
+    report[0] = GIP_CMD_INPUT;
+    report[3] = (XPADONE_REPORT_LEN - 4) as u8;
+    report
+}
+
+// Run the init sequence and ack its last packet the way a pad does
+fn handshake(pad: &XpadFixture, xpad: &UsbXpad) {
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+    xpad.decode(&gip_ack_for(packets.last().unwrap()));
+}
+
+// Keeps every log line along with its level and the thread that logged
+// it, tests run in parallel and only their own lines are of interest
+struct CaptureLog {
+    lines: Mutex<Vec<(ThreadId, log::Level, String)>>,
+}
+
+impl log::Log for CaptureLog {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let line = record.args().to_string();
+        self.lines
+            .lock()
+            .unwrap()
+            .push((thread::current().id(), record.level(), line));
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: CaptureLog = CaptureLog {
+    lines: Mutex::new(Vec::new()),
+};
+
+// Held by tests that change or depend on the global LogLevel
+static LOG_LEVEL_LOCK: Mutex<()> = Mutex::new(());
+
+// Lines this thread logged since the last call
+fn logged_lines() -> Vec<(log::Level, String)> {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+    let me = thread::current().id();
+    let mut lines = CAPTURE.lines.lock().unwrap();
+    let (mine, rest) = lines.drain(..).partition(|(id, _, _)| *id == me);
+    *lines = rest;
+    mine.into_iter()
+        .map(|(_, level, line)| (level, line))
+        .collect()
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+fn ready_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.transport.take();
+    (pad, xpad)
+}
+
+fn mode_report(options: u8, seq: u8, pressed: bool) -> [u8; 6] {
+    [GIP_CMD_VIRTUAL_KEY, options, seq, 0x02, pressed as u8, 0x5b]
+}
+
+#[test]
+fn mode_report_asking_for_an_ack_gets_one_with_its_sequence() {
+    let (pad, xpad) = ready_pad();
+    xpad.decode(&mode_report(GIP_OPT_ACK | GIP_OPT_INTERNAL, 0x2a, true));
+
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 1);
+    let ack = &packets[0];
+    assert_eq!(ack[0], GIP_CMD_ACK);
+    assert_eq!(ack[1], GIP_OPT_INTERNAL);
+    assert_eq!(ack[2], 0x2a);
+    assert_eq!(ack[5], GIP_CMD_VIRTUAL_KEY);
+    assert_eq!(ack.len(), XPADONE_MODE_REPORT_ACK.len());
+
+    // The guide is still reported
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Mode, true)));
+}
+
+#[test]
+fn each_report_gets_its_own_sequence_echoed() {
+    let (pad, xpad) = ready_pad();
+    for (seq, pressed) in [(0x01, true), (0x02, false), (0xff, true)] {
+        xpad.decode(&mode_report(GIP_OPT_ACK | GIP_OPT_INTERNAL, seq, pressed));
+    }
+    let seqs: Vec<u8> = pad.transport.take().iter().map(|ack| ack[2]).collect();
+    assert_eq!(seqs, [0x01, 0x02, 0xff]);
+}
+
+#[test]
+fn mode_report_without_the_ack_option_is_not_acked() {
+    let (pad, xpad) = ready_pad();
+    xpad.decode(&mode_report(GIP_OPT_INTERNAL, 0x05, true));
+    assert!(pad.transport.take().is_empty());
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Mode, true)));
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+const GUIDE: u8 = 0x04; // data[3]
+const START: u8 = 0x10; // data[2]
+
+fn chord_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::MODE_AS_CHORD, QuirkFlags::empty());
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+fn frame(xpad: &UsbXpad, digital: u8, buttons: u8) {
+    let mut report = report_360();
+    report[2] = digital;
+    report[3] = buttons;
+    xpad.decode(&report);
+}
+
+// Where `button` ended up after the frame, None if it wasn't reported
+fn last(events: &[SinkEvent], button: Button) -> Option<bool> {
+    events.iter().rev().find_map(|event| match event {
+        SinkEvent::Key(b, pressed) if *b == button => Some(*pressed),
+        _ => None,
+    })
+}
+
+#[test]
+fn guide_press_is_start_and_select() {
+    let (pad, xpad) = chord_pad();
+    frame(&xpad, 0, GUIDE);
+    let events = pad.sink.take();
+    assert_eq!(last(&events, Button::Start), Some(true));
+    assert_eq!(last(&events, Button::Select), Some(true));
+    assert_eq!(last(&events, Button::Mode), None);
+    assert_eq!(xpad.pressed_buttons(), ButtonSet::START | ButtonSet::SELECT);
+
+    frame(&xpad, 0, 0);
+    let events = pad.sink.take();
+    assert_eq!(last(&events, Button::Start), Some(false));
+    assert_eq!(last(&events, Button::Select), Some(false));
+    assert_eq!(last(&events, Button::Mode), None);
+    assert!(xpad.pressed_buttons().is_empty());
+}
+
+#[test]
+fn real_start_outlives_the_chord() {
+    let (_pad, xpad) = chord_pad();
+    frame(&xpad, START, GUIDE);
+    frame(&xpad, START, 0);
+    assert_eq!(xpad.pressed_buttons(), ButtonSet::START);
+    frame(&xpad, 0, 0);
+    assert!(xpad.pressed_buttons().is_empty());
+}
+
+#[test]
+fn chord_outlives_a_real_start_release() {
+    let (_pad, xpad) = chord_pad();
+    frame(&xpad, START, GUIDE);
+    frame(&xpad, 0, GUIDE);
+    assert_eq!(xpad.pressed_buttons(), ButtonSet::START | ButtonSet::SELECT);
+}
+
+#[test]
+fn without_the_flag_guide_is_mode() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    frame(&xpad, 0, GUIDE);
+    assert_eq!(last(&pad.sink.take(), Button::Mode), Some(true));
+    assert_eq!(xpad.pressed_buttons(), ButtonSet::MODE);
+}
+
+#[test]
+fn mode_is_not_advertised_with_the_chord() {
+    let (_pad, xpad) = chord_pad();
+    let buttons = xpad_capabilities(&xpad).buttons;
+    assert!(!buttons.contains(&Button::Mode));
+    assert!(buttons.contains(&Button::Start) && buttons.contains(&Button::Select));
+}
+
+#[test]
+fn chord_follows_set_mapping() {
+    let (pad, xpad) = chord_pad();
+    xpad.set_mapping(MapFlags::empty());
+    frame(&xpad, 0, GUIDE);
+    assert_eq!(last(&pad.sink.take(), Button::Mode), Some(true));
+}
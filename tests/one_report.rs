@@ -0,0 +1,75 @@
+use super::*;
+
+fn ready_series_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(
+        XType::XboxOne,
+        MapFlags::SELECT_BUTTON | MapFlags::SHARE_OFFSET,
+        QuirkFlags::empty(),
+    )
+    .ids(0x045e, 0x0b12);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    xpad.set_share_action(ShareAction::Button(Button::Record))
+        .unwrap();
+    pad.sink.take();
+    (pad, xpad)
+}
+
+fn report_of(len: usize) -> Vec<u8> {
+    let mut report = vec![0xffu8; len];
+    report[..4].copy_from_slice(&[GIP_CMD_INPUT, 0x00, 0x00, (len - 4) as u8]);
+    report
+}
+
+fn reported_abs(events: &[SinkEvent], axis: AbsoluteAxis) -> bool {
+    events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(a, _) if *a == axis))
+}
+
+#[test]
+fn report_without_buttons_is_dropped() {
+    let (pad, xpad) = ready_series_pad();
+    xpad.decode(&report_of(XPADONE_BUTTONS_LEN - 1));
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn short_report_decodes_only_what_it_carries() {
+    let (pad, xpad) = ready_series_pad();
+
+    // Buttons and triggers, the bytes a longer report has next aren't there
+    xpad.decode(&report_of(XPADONE_TRIGGERS_LEN));
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(reported_abs(&events, AbsoluteAxis::Z));
+    assert!(!reported_abs(&events, AbsoluteAxis::X));
+    assert!(!reported_abs(&events, AbsoluteAxis::Rx));
+
+    xpad.decode(&report_of(XPADONE_LEFT_STICK_LEN));
+    let events = pad.sink.take();
+    assert!(reported_abs(&events, AbsoluteAxis::X));
+    assert!(!reported_abs(&events, AbsoluteAxis::Rx));
+}
+
+#[test]
+fn pre_share_report_has_no_share_button() {
+    let (pad, xpad) = ready_series_pad();
+    xpad.decode(&report_of(XPADONE_REPORT_LEN));
+    let events = pad.sink.take();
+    assert!(reported_abs(&events, AbsoluteAxis::Rx));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Record, _))));
+}
+
+#[test]
+fn long_report_carries_the_share_button() {
+    let (pad, xpad) = ready_series_pad();
+    let mut report = report_of(48);
+    report[22] = 0x01;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(reported_abs(&events, AbsoluteAxis::Rx));
+    assert!(events.contains(&SinkEvent::Key(Button::Record, true)));
+}
@@ -0,0 +1,80 @@
+use super::*;
+
+const PACKET: [u8; 3] = [0x01, 0x03, 0x06];
+
+fn backoff(attempt: u64) -> Duration {
+    Duration::from_millis(OUT_RETRY_BACKOFF_MS * attempt)
+}
+
+#[test]
+fn stalled_submit_is_retried_after_a_backoff() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    pad.transport.fail_next(UsbError::Stall);
+
+    xpad.send_output_packet(&PACKET).unwrap();
+    assert!(pad.transport.take().is_empty());
+    assert_eq!(xpad_out_retry_tick(&xpad), Some(backoff(1)));
+
+    pad.clock.advance(backoff(1));
+    assert_eq!(xpad_out_retry_tick(&xpad), None);
+    assert_eq!(pad.transport.take(), [PACKET.to_vec()]);
+}
+
+#[test]
+fn stall_in_the_completion_is_retried() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.send_output_packet(&PACKET).unwrap();
+    pad.transport.take();
+
+    xpad.out_completed(Err(UsbError::Timeout));
+    pad.clock.advance(backoff(1));
+    xpad_out_retry_tick(&xpad);
+    assert_eq!(pad.transport.take(), [PACKET.to_vec()]);
+
+    // Delivered this time, nothing more to do
+    xpad.out_completed(Ok(()));
+    assert_eq!(xpad_out_retry_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn retries_are_bounded() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    for _ in 0..=DEFAULT_OUT_RETRIES {
+        pad.transport.fail_next(UsbError::Stall);
+    }
+
+    xpad.send_output_packet(&PACKET).unwrap();
+    for attempt in 1..=u64::from(DEFAULT_OUT_RETRIES) {
+        pad.clock.advance(backoff(attempt));
+        xpad_out_retry_tick(&xpad);
+    }
+    assert!(xpad.out_retry.lock().unwrap().is_empty());
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn disconnect_is_not_retried() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    pad.transport.fail_next(UsbStatus::Disconnected.into());
+
+    assert!(xpad.send_output_packet(&PACKET).is_err());
+    assert_eq!(xpad_out_retry_tick(&xpad), None);
+}
+
+#[test]
+fn queued_retries_are_dropped_on_disconnect() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    pad.transport.fail_next(UsbError::Stall);
+    xpad.send_output_packet(&PACKET).unwrap();
+
+    xpad.handle_disconnect();
+    pad.clock.advance(backoff(1));
+    assert_eq!(xpad_out_retry_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+fn player_led(index: u8) -> Vec<u8> {
+    vec![0x01, 0x03, LedCommand::TopLeftBlinkThenOn as u8 + index - 1]
+}
+
+// Attach a new 360 pad, returning it with what was sent to it
+fn attach(manager: &XpadManager) -> (XpadId, Vec<Vec<u8>>) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let id = manager.attach(pad.build());
+    (id, pad.transport.take())
+}
+
+#[test]
+fn pads_get_the_lowest_free_player() {
+    let manager = XpadManager::new().with_auto_assign_players(true);
+    for index in 1..=3 {
+        let (id, sent) = attach(&manager);
+        assert_eq!(manager.get(id).unwrap().player_index(), index);
+        assert_eq!(sent.last(), Some(&player_led(index)));
+    }
+}
+
+#[test]
+fn detached_players_are_reused() {
+    let manager = XpadManager::new().with_auto_assign_players(true);
+    let (_first, _) = attach(&manager);
+    let (second, _) = attach(&manager);
+    let (_third, _) = attach(&manager);
+
+    manager.detach(second).unwrap();
+    let (id, sent) = attach(&manager);
+    assert_eq!(manager.get(id).unwrap().player_index(), 2);
+    assert_eq!(sent.last(), Some(&player_led(2)));
+
+    // Then past the others
+    let (id, _) = attach(&manager);
+    assert_eq!(manager.get(id).unwrap().player_index(), 4);
+}
+
+#[test]
+fn a_fifth_pad_gets_no_player() {
+    let manager = XpadManager::new().with_auto_assign_players(true);
+    for _ in 0..MAX_PLAYERS {
+        attach(&manager);
+    }
+    let (id, sent) = attach(&manager);
+    assert_eq!(manager.get(id).unwrap().player_index(), 0);
+    assert!(!sent.iter().any(|packet| packet[..2] == [0x01, 0x03]));
+}
+
+#[test]
+fn off_by_default() {
+    let manager = XpadManager::new();
+    let (id, sent) = attach(&manager);
+    assert_eq!(manager.get(id).unwrap().player_index(), 0);
+    assert!(sent.is_empty());
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+const INTERVAL: Duration = Duration::from_millis(8);
+
+fn polled() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_in_transport(pad.transport.clone());
+    xpad.set_poll_interval(Some(INTERVAL));
+    (pad, xpad)
+}
+
+#[test]
+fn without_an_interval_the_caller_resubmits() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_in_transport(pad.transport.clone());
+    assert!(!xpad.hold_input());
+    assert_eq!(xpad_poll_tick(&xpad), None);
+}
+
+#[test]
+fn without_the_urb_the_interval_is_ignored() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_poll_interval(Some(INTERVAL));
+    assert!(!xpad.hold_input());
+}
+
+#[test]
+fn held_urb_is_resubmitted_once_the_interval_is_up() {
+    let (pad, xpad) = polled();
+    assert!(xpad.hold_input());
+
+    assert_eq!(xpad_poll_tick(&xpad), Some(INTERVAL));
+    pad.clock.advance(INTERVAL / 2);
+    assert_eq!(xpad_poll_tick(&xpad), Some(INTERVAL / 2));
+    assert_eq!(pad.transport.resubmits(), 0);
+
+    pad.clock.advance(INTERVAL / 2);
+    assert_eq!(xpad_poll_tick(&xpad), None);
+    assert_eq!(pad.transport.resubmits(), 1);
+
+    // Only once per report
+    assert_eq!(xpad_poll_tick(&xpad), None);
+    assert_eq!(pad.transport.resubmits(), 1);
+}
+
+#[test]
+fn turning_polling_off_lets_the_held_urb_go() {
+    let (pad, xpad) = polled();
+    assert!(xpad.hold_input());
+
+    xpad.set_poll_interval(None);
+    assert_eq!(xpad_poll_tick(&xpad), None);
+    assert_eq!(pad.transport.resubmits(), 1);
+}
+
+#[test]
+fn held_urb_is_dropped_on_disconnect() {
+    let (pad, xpad) = polled();
+    assert!(xpad.hold_input());
+
+    xpad.handle_disconnect();
+    pad.clock.advance(INTERVAL);
+    assert_eq!(xpad_poll_tick(&xpad), None);
+    assert_eq!(pad.transport.resubmits(), 0);
+}
+
+#[test]
+fn watchdog_allows_for_the_poll_interval() {
+    let (pad, xpad) = polled();
+    let timeout = Duration::from_millis(100);
+    xpad.set_watchdog_timeout(Some(timeout));
+    xpad.decode(&report_360());
+    assert!(xpad.hold_input());
+
+    // Past the timeout, but the URB was held for part of it
+    pad.clock.advance(timeout + INTERVAL / 2);
+    assert_eq!(xpad_watchdog_tick(&xpad), Some(INTERVAL / 2));
+    assert!(!xpad.neutralized.load(Ordering::SeqCst));
+
+    pad.clock.advance(INTERVAL / 2);
+    xpad_watchdog_tick(&xpad);
+    assert!(xpad.neutralized.load(Ordering::SeqCst));
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+fn ready(id_vendor: u16, id_product: u16) -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(id_vendor, id_product);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.transport.take();
+    pad.sink.take();
+    (pad, xpad)
+}
+
+#[test]
+fn power_off_sends_the_gip_power_packet() {
+    let (pad, xpad) = ready(0x045e, 0x02ea);
+    xpad.power_off().unwrap();
+
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 1);
+    let packet = &packets[0];
+    assert_eq!(packet[..2], [GIP_CMD_POWER, GIP_OPT_INTERNAL]);
+    assert_eq!(packet[3..], [0x01, GIP_POWER_OFF]);
+}
+
+#[test]
+fn a_powered_off_pad_waits_for_a_new_handshake() {
+    let (pad, xpad) = ready(0x045e, 0x02ea);
+    xpad.power_off().unwrap();
+    assert_eq!(xpad.init_state(), InitState::Enumerated);
+
+    let mut report = report_one();
+    report[4] = 0x10; // A
+    xpad.decode(&report);
+    assert!(pad.sink.take().is_empty());
+    assert_eq!(xpad.diagnostics().early_input_dropped, 1);
+
+    handshake(&pad, &xpad);
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+}
+
+#[test]
+fn wired_only_pads_are_not_supported() {
+    // Third-party Xbox One pad, the GHL guitar and a 360 pad
+    let (pad, xpad) = ready(0x0e6f, 0x02a4);
+    assert!(matches!(xpad.power_off(), Err(XpadError::NotSupported)));
+    assert!(pad.transport.take().is_empty());
+    assert_eq!(xpad.init_state(), InitState::Ready);
+
+    let guitar = UsbXpad::test_fixture(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE);
+    assert!(matches!(guitar.power_off(), Err(XpadError::NotSupported)));
+
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert!(matches!(xpad.power_off(), Err(XpadError::NotSupported)));
+    assert!(pad.transport.take().is_empty());
+}
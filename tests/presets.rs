@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn names_map_to_their_constants() {
+    assert_eq!(preset("dancepad"), Some(DANCEPAD_MAP_CONFIG));
+    assert_eq!(preset("fightstick"), Some(FIGHTSTICK_MAP_CONFIG));
+    assert_eq!(preset("wheel"), Some(WHEEL_MAP_CONFIG));
+    assert_eq!(preset("default"), Some(MapFlags::empty()));
+}
+
+#[test]
+fn unknown_names_are_none() {
+    for name in ["", "guitar", "Dancepad", " dancepad", "dance pad"] {
+        assert_eq!(preset(name), None, "{:?}", name);
+    }
+}
+
+// A preset is a mapping like any other, set_mapping keeps it as is
+#[test]
+fn presets_apply_unchanged() {
+    for name in ["default", "dancepad", "fightstick", "wheel"] {
+        let mapping = preset(name).unwrap();
+        let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+        xpad.set_mapping(mapping);
+        assert_eq!(xpad.mapping(), mapping, "{}", name);
+    }
+}
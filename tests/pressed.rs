@@ -0,0 +1,74 @@
+use super::*;
+
+fn fixture(xtype: XType) -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(xtype, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+#[test]
+fn xbox360_a_b_and_lb_are_exactly_what_is_held() {
+    let (_pad, xpad) = fixture(XType::Xbox360);
+    let mut report = report_360();
+    report[3] = 0x01 | 0x10 | 0x20; // LB, A, B
+    xpad.decode(&report);
+    assert_eq!(
+        xpad.pressed_buttons(),
+        ButtonSet::A | ButtonSet::B | ButtonSet::TL
+    );
+}
+
+#[test]
+fn xbox_one_a_b_and_lb_are_exactly_what_is_held() {
+    let (pad, xpad) = fixture(XType::XboxOne);
+    handshake(&pad, &xpad);
+    let mut report = report_one();
+    report[4] = 0x10 | 0x20; // A, B
+    report[5] = 0x10; // LB
+    xpad.decode(&report);
+    assert_eq!(
+        xpad.pressed_buttons(),
+        ButtonSet::A | ButtonSet::B | ButtonSet::TL
+    );
+}
+
+// The original pad has no bumpers, black stands in for LB
+#[test]
+fn original_xbox_a_b_and_black_are_exactly_what_is_held() {
+    let (_pad, xpad) = fixture(XType::Xbox);
+    let mut report = vec![0u8; XPAD_REPORT_LEN];
+    report[4] = 0xff; // A
+    report[5] = 0x20; // B, analog so any travel counts
+    report[8] = 0xff; // black
+    xpad.decode(&report);
+    assert_eq!(
+        xpad.pressed_buttons(),
+        ButtonSet::A | ButtonSet::B | ButtonSet::C
+    );
+}
+
+#[test]
+fn released_buttons_leave_the_set() {
+    let (_pad, xpad) = fixture(XType::Xbox360);
+    let mut report = report_360();
+    report[3] = 0x01 | 0x10;
+    xpad.decode(&report);
+    report[3] = 0x10;
+    xpad.decode(&report);
+    assert_eq!(xpad.pressed_buttons(), ButtonSet::A);
+    xpad.decode(&report_360());
+    assert!(xpad.pressed_buttons().is_empty());
+}
+
+// The hat sets the d-pad bits even when the d-pad isn't mapped to buttons
+#[test]
+fn dpad_bits_follow_the_hat() {
+    let (_pad, xpad) = fixture(XType::Xbox360);
+    let mut report = report_360();
+    report[2] = 0x01 | 0x04; // up, left
+    xpad.decode(&report);
+    assert_eq!(
+        xpad.pressed_buttons(),
+        ButtonSet::DPAD_UP | ButtonSet::DPAD_LEFT
+    );
+}
@@ -0,0 +1,118 @@
+use super::*;
+
+#[test]
+fn revolution5_pro_is_decoded_as_a_standard_360_pad() {
+    let device = XPAD_DEVICES.get(&(0x3285, 0x0662)).unwrap();
+    let pad = XpadFixture::new(device.xtype, device.mapping, device.quirks).ids(0x3285, 0x0662);
+    let xpad = pad.build();
+
+    // Bytes past the 20-byte report aren't read, nothing documents them
+    let mut report = report_360().to_vec();
+    report[3] = 0x10; // A
+    report.push(0x0f);
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    assert!(!events.iter().any(|event| matches!(
+        event,
+        SinkEvent::Key(Button::TriggerHappy5, _) | SinkEvent::Abs(AbsoluteAxis::Profile, _)
+    )));
+}
+
+#[test]
+fn wooting_is_decoded_as_a_standard_360_pad() {
+    let device = XPAD_DEVICES.get(&(0x31e3, 0x1100)).unwrap();
+    let pad = XpadFixture::new(device.xtype, device.mapping, device.quirks).ids(0x31e3, 0x1100);
+    let xpad = pad.build();
+
+    // Nothing documents an analog mode, bytes past the 20-byte report
+    // aren't read and the face buttons stay buttons
+    let mut report = report_360().to_vec();
+    report[3] = 0x10; // A
+    report.extend_from_slice(&[0xff, 0x80, 0x40, 0x20]);
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::A, true)));
+    let axes: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            SinkEvent::Abs(axis, _) => Some(*axis),
+            _ => None,
+        })
+        .collect();
+    assert!(axes
+        .iter()
+        .all(|axis| xpad_capabilities(&xpad).axes.contains(axis)));
+}
+
+// Made-up controller: byte 0 bit 0 is A, byte 1 a signed 8-bit X axis
+const FAKE_VENDOR: u16 = 0xf00d;
+
+struct FakeProfile;
+
+impl DeviceProfile for FakeProfile {
+    fn matches(&self, xpad: &UsbXpad) -> bool {
+        xpad.id_vendor == FAKE_VENDOR
+    }
+
+    fn decode(&self, _xpad: &UsbXpad, data: &[u8], sink: &dyn InputSink) {
+        sink.report_key(Button::A, data[0] & 0x01 != 0);
+        sink.report_abs(AbsoluteAxis::X, i32::from(data[1] as i8) * 256);
+        sink.synchronize();
+    }
+
+    fn init_packets(&self, _xpad: &UsbXpad) -> Vec<Vec<u8>> {
+        vec![vec![0xf0, 0x0d]]
+    }
+}
+
+fn fake_pad() -> (XpadFixture, UsbXpad) {
+    register_profile(Arc::new(FakeProfile));
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(FAKE_VENDOR, 0x0001);
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+#[test]
+fn registered_profile_decodes_its_own_layout() {
+    let (pad, xpad) = fake_pad();
+    xpad.decode(&[0x01, 0x80]);
+    assert_eq!(
+        pad.sink.take(),
+        [
+            SinkEvent::Key(Button::A, true),
+            SinkEvent::Abs(AbsoluteAxis::X, -32768),
+            SinkEvent::Sync,
+        ]
+    );
+}
+
+#[test]
+fn registered_profile_brings_its_init_packets() {
+    let (pad, xpad) = fake_pad();
+    xpad.reinitialize().unwrap();
+    assert_eq!(pad.transport.take()[0], [0xf0, 0x0d]);
+}
+
+#[test]
+fn other_pads_keep_the_built_in_profiles() {
+    register_profile(Arc::new(FakeProfile));
+
+    // A 360 report, which the fake layout would read as A released
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[3] = 0x10; // A
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+
+    // Same for a GIP pad, its init sequence is the built-in one
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+    assert!(!packets.is_empty());
+    assert!(packets.iter().all(|packet| packet[..] != [0xf0, 0x0d]));
+}
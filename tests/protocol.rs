@@ -0,0 +1,54 @@
+use super::*;
+
+const VENDOR_SPEC: u8 = linux_usb::USB_CLASS_VENDOR_SPEC;
+
+fn intf(protocol: u8) -> InterfaceInfo {
+    InterfaceInfo {
+        number: 0,
+        class: VENDOR_SPEC,
+        subclass: 93,
+        protocol,
+    }
+}
+
+#[test]
+fn table_tells_pads_from_receivers() {
+    let wired = match_interface(0x045e, &intf(1)).unwrap();
+    assert_eq!(wired.protocol_xtype(), Some(XType::Xbox360));
+    let wireless = match_interface(0x045e, &intf(129)).unwrap();
+    assert_eq!(wireless.protocol_xtype(), Some(XType::Xbox360W));
+
+    // Any other protocol on a 360 subclass isn't ours
+    assert!(match_interface(0x045e, &intf(2)).is_none());
+}
+
+#[test]
+fn protocol_129_on_a_360_vendor_takes_the_wireless_path() {
+    let [_, receiver] = UsbDeviceId::xbox360_vendor(0x1bad);
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x1bad, 0xf900)
+        .interface(receiver);
+    let xpad = pad.build();
+    assert_eq!(xpad.protocol(), XType::Xbox360W);
+
+    // Pad data arrives wrapped in the receiver's header
+    let mut report = report_360w();
+    report[4 + 3] = 0x10;
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+}
+
+#[test]
+fn protocol_1_on_a_360_vendor_stays_wired() {
+    let [wired, _] = UsbDeviceId::xbox360_vendor(0x1bad);
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x1bad, 0xf900)
+        .interface(wired);
+    let xpad = pad.build();
+    assert_eq!(xpad.protocol(), XType::Xbox360);
+
+    let mut report = report_360();
+    report[3] = 0x10;
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn ghl_guitar_gets_the_poke_timer() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE)
+        .ids(0x1430, 0x079b);
+    let xpad = pad.build();
+    let interval = Duration::from_secs(GHL_GUITAR_POKE_INTERVAL);
+    assert_eq!(xpad.quirk_actions(), [QuirkAction::GhlPoke { interval }]);
+
+    // Each run sends the poke and asks to run again after the interval
+    for _ in 0..2 {
+        assert_eq!(xpad_ghl_poke_tick(&xpad), Some(interval));
+        let packets = pad.transport.take();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0][..2], GHL_XBOXONE_POKE[..2]);
+        assert_eq!(packets[0][3..], GHL_XBOXONE_POKE[3..]);
+    }
+}
+
+#[test]
+fn pads_without_the_quirk_never_poke() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    assert!(xpad.quirk_actions().is_empty());
+    assert_eq!(xpad_ghl_poke_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn start_packet_quirks_become_start_requests_in_order() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QUIRK_360_START);
+    assert_eq!(
+        xpad.quirk_actions(),
+        [
+            QuirkAction::StartPacket(&XPAD360_START_PKT_1),
+            QuirkAction::StartPacket(&XPAD360_START_PKT_2),
+            QuirkAction::StartPacket(&XPAD360_START_PKT_3),
+        ]
+    );
+
+    // Only the bits that are set
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::START_PKT_2);
+    assert_eq!(
+        xpad.quirk_actions(),
+        [QuirkAction::StartPacket(&XPAD360_START_PKT_2)]
+    );
+}
+
+#[test]
+fn start_requests_go_out_when_the_pad_is_started() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QUIRK_360_START);
+    let xpad = pad.build();
+    assert!(pad.transport.take_controls().is_empty());
+
+    xpad360_start(&xpad).unwrap();
+    assert_eq!(
+        pad.transport.take_controls(),
+        [
+            XPAD360_START_PKT_1,
+            XPAD360_START_PKT_2,
+            XPAD360_START_PKT_3
+        ]
+    );
+}
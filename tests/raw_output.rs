@@ -0,0 +1,51 @@
+use super::*;
+
+const PACKET: [u8; 5] = [0x09, 0x00, 0xee, 0x09, 0x00];
+
+#[test]
+fn serial_is_stamped_in_sequence() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    xpad.send_raw_output(&PACKET, true).unwrap();
+    xpad.send_raw_output(&PACKET, true).unwrap();
+    let sent = pad.transport.take();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0], [0x09, 0x00, 0x00, 0x09, 0x00]);
+    assert_eq!(sent[1], [0x09, 0x00, 0x01, 0x09, 0x00]);
+}
+
+#[test]
+fn unstamped_packets_go_out_as_given() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    xpad.send_raw_output(&PACKET, false).unwrap();
+    assert_eq!(pad.transport.take(), [PACKET.to_vec()]);
+
+    // And don't use up a sequence number
+    xpad.send_raw_output(&PACKET, true).unwrap();
+    assert_eq!(pad.transport.take()[0][2], 0x00);
+}
+
+#[test]
+fn too_short_to_stamp_is_refused() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert!(matches!(
+        xpad.send_raw_output(&[0x09, 0x00], true),
+        Err(XpadError::NotSupported)
+    ));
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn transport_errors_come_back() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    pad.transport.fail_next(UsbStatus::Disconnected.into());
+    assert!(matches!(
+        xpad.send_raw_output(&PACKET, true),
+        Err(XpadError::Usb(_))
+    ));
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+// Ids only these tests register, the runtime registry is global
+fn device(id_product: u16, mapping: MapFlags, quirks: QuirkFlags) -> XpadDevice {
+    XpadDevice {
+        id_vendor: 0xbeef,
+        id_product,
+        name: "Registry test pad",
+        mapping,
+        xtype: XType::Xbox360,
+        quirks,
+    }
+}
+
+fn bound(id_product: u16) -> XpadFixture {
+    XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty()).ids(0xbeef, id_product)
+}
+
+fn dpad_up() -> [u8; 20] {
+    let mut report = report_360();
+    report[2] = 0x01;
+    report
+}
+
+#[test]
+fn registered_mapping_applies_to_a_live_pad() {
+    register_device(device(0x0001, MapFlags::empty(), QuirkFlags::empty()));
+    let pad = bound(0x0001);
+    let xpad = pad.build();
+    xpad.decode(&dpad_up());
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Abs(AbsoluteAxis::Hat0Y, -1)));
+
+    register_device(device(
+        0x0001,
+        MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::empty(),
+    ));
+    assert_eq!(
+        xpad.refresh_from_registry(),
+        Refresh::MappingApplied {
+            capabilities_changed: true
+        }
+    );
+    xpad.decode(&dpad_up());
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::TriggerHappy3, true)));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::Hat0Y, _))));
+
+    // Nothing new the second time
+    assert_eq!(xpad.refresh_from_registry(), Refresh::Unchanged);
+}
+
+#[test]
+fn quirk_changes_need_a_reprobe() {
+    register_device(device(0x0002, MapFlags::empty(), QuirkFlags::empty()));
+    let xpad = bound(0x0002).build();
+
+    register_device(device(
+        0x0002,
+        MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::NO_RUMBLE,
+    ));
+    assert_eq!(xpad.refresh_from_registry(), Refresh::ReprobeNeeded);
+    // Left as it was, mapping included
+    assert_eq!(xpad.mapping(), MapFlags::empty());
+    assert!(xpad.supports_rumble());
+}
+
+#[test]
+fn protocol_changes_need_a_reprobe() {
+    register_device(device(0x0003, MapFlags::empty(), QuirkFlags::empty()));
+    let xpad = bound(0x0003).build();
+
+    register_device(XpadDevice {
+        xtype: XType::Xbox,
+        ..device(0x0003, MapFlags::empty(), QuirkFlags::empty())
+    });
+    assert_eq!(xpad.refresh_from_registry(), Refresh::ReprobeNeeded);
+    assert_eq!(xpad.protocol(), XType::Xbox360);
+}
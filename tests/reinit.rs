@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn reinitialize_replays_the_init_sequence_from_the_start() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+
+    // Same packets, sequence numbers included, on an initialized pad
+    xpad.reinitialize().unwrap();
+    let first = pad.transport.take();
+    xpad.decode(&gip_ack_for(first.last().unwrap()));
+    assert_eq!(xpad.init_state(), InitState::Ready);
+
+    xpad.reinitialize().unwrap();
+    assert_eq!(pad.transport.take(), first);
+    assert_eq!(xpad.init_state(), InitState::Configured);
+}
+
+#[test]
+fn led_comes_back_after_the_init_packets() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_player_index(2).unwrap();
+    let led = pad.transport.take();
+    assert_eq!(led.len(), 1);
+
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+    assert_eq!(packets.last(), led.last());
+    assert_eq!(xpad.player_index(), 2);
+}
+
+#[test]
+fn start_packets_are_sent_again_on_360_pads() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QUIRK_360_START);
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    let first = pad.transport.take_controls();
+    assert_eq!(first.len(), 3);
+    xpad.reinitialize().unwrap();
+    assert_eq!(pad.transport.take_controls(), first);
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+// Last state reported for `button` in a frame
+fn last_key(events: &[SinkEvent], button: Button) -> Option<bool> {
+    events.iter().rev().find_map(|event| match *event {
+        SinkEvent::Key(b, pressed) if b == button => Some(pressed),
+        _ => None,
+    })
+}
+
+fn buttons_360(buttons: u8) -> [u8; 20] {
+    let mut report = report_360();
+    report[3] = buttons;
+    report
+}
+
+#[test]
+fn unmapped_buttons_report_as_themselves() {
+    let remap = ButtonRemap::default();
+    assert_eq!(remap.get(Button::A), Button::A);
+
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(&buttons_360(0x10));
+    let events = pad.sink.take();
+    assert_eq!(last_key(&events, Button::A), Some(true));
+    assert_eq!(last_key(&events, Button::B), Some(false));
+}
+
+#[test]
+fn a_and_b_swap() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut remap = ButtonRemap::default();
+    remap.swap(Button::A, Button::B);
+    xpad.set_button_remap(remap).unwrap();
+
+    xpad.decode(&buttons_360(0x10)); // A
+    let events = pad.sink.take();
+    assert_eq!(last_key(&events, Button::B), Some(true));
+    assert_eq!(last_key(&events, Button::A), Some(false));
+
+    xpad.decode(&buttons_360(0x20)); // B
+    let events = pad.sink.take();
+    assert_eq!(last_key(&events, Button::A), Some(true));
+    assert_eq!(last_key(&events, Button::B), Some(false));
+}
+
+#[test]
+fn paddle_reports_as_left_bumper() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::PADDLES, QuirkFlags::empty())
+        .ids(0x045e, 0x0b00);
+    let xpad = pad.build().with_serial_and_firmware(None, 0x050b);
+    handshake(&pad, &xpad);
+    let mut remap = ButtonRemap::default();
+    remap.set(XPAD_BTN_PADDLES[0], Button::TL);
+    xpad.set_button_remap(remap).unwrap();
+    pad.sink.take();
+
+    let mut report = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    report[0] = GIP_CMD_FIRMWARE;
+    report[GIP_ELITE_PADDLES_OFFSET] = 0x01;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(last_key(&events, Button::TL), Some(true));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(b, true) if *b == XPAD_BTN_PADDLES[0])));
+}
+
+#[test]
+fn shared_target_is_held_while_any_source_is() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut remap = ButtonRemap::default();
+    remap.set(Button::X, Button::TL);
+    xpad.set_button_remap(remap).unwrap();
+
+    xpad.decode(&buttons_360(0x01 | 0x40)); // TL and X
+    assert_eq!(last_key(&pad.sink.take(), Button::TL), Some(true));
+
+    // Letting go of the real bumper doesn't release it, X still holds it
+    xpad.decode(&buttons_360(0x40));
+    let events = pad.sink.take();
+    assert_eq!(last_key(&events, Button::TL), Some(true));
+    assert!(!events.contains(&SinkEvent::Key(Button::TL, false)));
+
+    xpad.decode(&buttons_360(0));
+    assert_eq!(last_key(&pad.sink.take(), Button::TL), Some(false));
+}
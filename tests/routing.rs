@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn xboxone_interface_wins_over_a_stale_table_entry() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea)
+        .interface(UsbDeviceId::xboxone_vendor(0x045e)[0]);
+    let xpad = pad.build();
+    assert_eq!(xpad.protocol(), XType::XboxOne);
+
+    // A GIP report decodes, it would be garbage to the 360 decoder
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    let mut report = report_one();
+    report[4] = 0x10; // A
+    xpad.decode(&report);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, true)));
+}
+
+#[test]
+fn table_xtype_is_used_without_interface_info() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    assert_eq!(xpad.protocol(), XType::Xbox360);
+
+    // An interface triple that names no protocol falls back as well
+    let mut unknown = UsbDeviceId::xbox360_vendor(0x045e)[0];
+    unknown.b_interface_protocol = 3;
+    assert_eq!(unknown.protocol_xtype(), None);
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .interface(unknown);
+    assert_eq!(pad.build().protocol(), XType::Xbox360W);
+}
+
+#[test]
+fn each_interface_triple_names_its_protocol() {
+    let [wired, wireless] = UsbDeviceId::xbox360_vendor(0x045e);
+    assert_eq!(wired.protocol_xtype(), Some(XType::Xbox360));
+    assert_eq!(wireless.protocol_xtype(), Some(XType::Xbox360W));
+    assert_eq!(
+        UsbDeviceId::xboxone_vendor(0x045e)[0].protocol_xtype(),
+        Some(XType::XboxOne)
+    );
+}
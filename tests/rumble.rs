@@ -0,0 +1,42 @@
+use super::*;
+
+const STRONG: RumbleMagnitudes = RumbleMagnitudes {
+    strong: 0x8000,
+    weak: 0,
+    left_trigger: 0,
+    right_trigger: 0,
+};
+
+#[test]
+fn table_entries_know_their_rumble() {
+    let pad = XPAD_DEVICES.get(&(0x045e, 0x02ea)).unwrap();
+    assert!(pad.supports_rumble() && pad.supports_trigger_rumble());
+
+    let hama = XPAD_DEVICES.get(&(0x0c12, 0x9902)).unwrap();
+    assert!(hama.quirks.contains(QuirkFlags::NO_RUMBLE));
+    assert!(!hama.supports_rumble() && !hama.supports_trigger_rumble());
+
+    // 360 pads rumble, but have no trigger motors
+    let wired = XPAD_DEVICES.get(&(0x045e, 0x028e)).unwrap();
+    assert!(wired.supports_rumble() && !wired.supports_trigger_rumble());
+}
+
+#[test]
+fn rumble_capable_pad_gets_a_packet() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad_play_effect(&xpad, &STRONG).unwrap();
+    assert_eq!(pad.transport.take().len(), 1);
+}
+
+#[test]
+fn no_rumble_device_fails_fast_without_a_packet() {
+    let pad =
+        XpadFixture::new(XType::Xbox, MapFlags::empty(), QuirkFlags::NO_RUMBLE).ids(0x0c12, 0x9902);
+    let xpad = pad.build();
+    assert!(matches!(
+        xpad_play_effect(&xpad, &STRONG),
+        Err(XpadError::NotSupported)
+    ));
+    assert!(pad.transport.take().is_empty());
+}
@@ -0,0 +1,144 @@
+use super::*;
+
+const CAP: Duration = Duration::from_secs(2);
+
+const RUMBLE: RumbleMagnitudes = RumbleMagnitudes {
+    strong: 0xffff,
+    weak: 0x8000,
+    left_trigger: 0,
+    right_trigger: 0,
+};
+
+fn ready_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    xpad.set_rumble_interval(Duration::ZERO);
+    xpad.set_max_rumble_duration(Some(CAP));
+    pad.transport.take();
+    (pad, xpad)
+}
+
+// Strong and weak bytes of the one rumble packet sent since the last call
+fn motors(pad: &XpadFixture) -> [u8; 2] {
+    let packets = pad.transport.take();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0][0], GIP_CMD_RUMBLE);
+    packets[0][8..10].try_into().unwrap()
+}
+
+fn cap_warnings() -> usize {
+    logged_lines()
+        .iter()
+        .filter(|(level, line)| {
+            *level == log::Level::Warn && line.starts_with("Rumble running for")
+        })
+        .count()
+}
+
+#[test]
+fn infinite_rumble_stops_at_the_cap() {
+    let (pad, xpad) = ready_pad();
+    cap_warnings();
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RUMBLE)).unwrap();
+    assert_ne!(motors(&pad), [0, 0]);
+
+    pad.clock.advance(CAP / 2);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), Some(CAP / 2));
+    assert!(pad.transport.take().is_empty());
+
+    pad.clock.advance(CAP / 2);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+    assert_eq!(motors(&pad), [0, 0]);
+    assert_eq!(cap_warnings(), 1);
+
+    // Nothing left to stop
+    pad.clock.advance(CAP);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+// The cap guards against a game that never stops, not against one
+// that keeps changing the magnitudes
+#[test]
+fn new_magnitudes_dont_restart_the_cap() {
+    let (pad, xpad) = ready_pad();
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RUMBLE)).unwrap();
+    pad.clock.advance(CAP / 2);
+    let weaker = RumbleMagnitudes {
+        strong: 0x4000,
+        ..RUMBLE
+    };
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(weaker)).unwrap();
+    pad.transport.take();
+
+    pad.clock.advance(CAP / 2);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+    assert_eq!(motors(&pad), [0, 0]);
+}
+
+#[test]
+fn a_stop_restarts_the_count() {
+    let (pad, xpad) = ready_pad();
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RUMBLE)).unwrap();
+    pad.clock.advance(CAP / 2);
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RumbleMagnitudes::default())).unwrap();
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RUMBLE)).unwrap();
+    pad.transport.take();
+    pad.clock.advance(CAP / 2);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), Some(CAP / 2));
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn envelopes_are_cut_off_as_well() {
+    let (pad, xpad) = ready_pad();
+    let long = MotorEnvelope {
+        magnitude: 0xffff,
+        attack_level: 0,
+        attack_length: Duration::ZERO,
+        fade_level: 0,
+        fade_length: Duration::ZERO,
+        length: CAP * 10,
+    };
+    let off = MotorEnvelope {
+        magnitude: 0,
+        ..long
+    };
+    xpad_upload_effect(&xpad, &FfEffect::Enveloped([long, off, off, off])).unwrap();
+    pad.transport.take();
+
+    pad.clock.advance(CAP);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+    assert_eq!(motors(&pad), [0, 0]);
+    assert_eq!(xpad_envelope_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn cap_can_be_raised_or_removed() {
+    let (pad, xpad) = ready_pad();
+    xpad.set_max_rumble_duration(Some(CAP * 2));
+    xpad_upload_effect(&xpad, &FfEffect::Rumble(RUMBLE)).unwrap();
+    pad.transport.take();
+    pad.clock.advance(CAP);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), Some(CAP));
+
+    xpad.set_max_rumble_duration(None);
+    pad.clock.advance(CAP * 10);
+    assert_eq!(xpad_rumble_cap_tick(&xpad), None);
+    assert!(pad.transport.take().is_empty());
+}
+
+#[test]
+fn default_cap() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert_eq!(
+        *xpad.max_rumble_duration.lock().unwrap(),
+        Some(Duration::from_secs(DEFAULT_MAX_RUMBLE_SECS))
+    );
+}
@@ -0,0 +1,76 @@
+use super::*;
+
+const INTERVAL: Duration = Duration::from_millis(20);
+
+fn strong(magnitude: u16) -> RumbleMagnitudes {
+    RumbleMagnitudes {
+        strong: magnitude,
+        ..RumbleMagnitudes::default()
+    }
+}
+
+// Strong motor byte of each 360 rumble packet sent
+fn sent(pad: &XpadFixture) -> Vec<u8> {
+    pad.transport
+        .take()
+        .iter()
+        .map(|packet| packet[3])
+        .collect()
+}
+
+fn limited_pad() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_rumble_interval(INTERVAL);
+    (pad, xpad)
+}
+
+#[test]
+fn rapid_updates_coalesce_into_one_send_at_the_interval() {
+    let (pad, xpad) = limited_pad();
+    let step = Duration::from_millis(5);
+
+    xpad_play_effect(&xpad, &strong(0x1000)).unwrap();
+    pad.clock.advance(step);
+    xpad_play_effect(&xpad, &strong(0x2000)).unwrap();
+    pad.clock.advance(step);
+    xpad_play_effect(&xpad, &strong(0x3000)).unwrap();
+    assert_eq!(sent(&pad), [0x10]);
+
+    // Not due until INTERVAL after the first send
+    assert_eq!(xpad_rumble_tick(&xpad), Some(INTERVAL - step * 2));
+    assert!(sent(&pad).is_empty());
+
+    pad.clock.advance(INTERVAL - step * 2);
+    xpad_rumble_tick(&xpad);
+    assert_eq!(sent(&pad), [0x30]);
+
+    // Nothing left over
+    pad.clock.advance(INTERVAL);
+    assert_eq!(xpad_rumble_tick(&xpad), None);
+    assert!(sent(&pad).is_empty());
+}
+
+#[test]
+fn stop_bypasses_the_limiter_and_drops_the_pending_update() {
+    let (pad, xpad) = limited_pad();
+    xpad_play_effect(&xpad, &strong(0x1000)).unwrap();
+    pad.clock.advance(Duration::from_millis(1));
+    xpad_play_effect(&xpad, &strong(0x2000)).unwrap();
+    xpad_play_effect(&xpad, &RumbleMagnitudes::default()).unwrap();
+    assert_eq!(sent(&pad), [0x10, 0x00]);
+
+    pad.clock.advance(INTERVAL);
+    assert_eq!(xpad_rumble_tick(&xpad), None);
+    assert!(sent(&pad).is_empty());
+}
+
+#[test]
+fn updates_spaced_by_the_interval_go_straight_out() {
+    let (pad, xpad) = limited_pad();
+    for magnitude in [0x1000, 0x2000, 0x3000] {
+        xpad_play_effect(&xpad, &strong(magnitude)).unwrap();
+        pad.clock.advance(INTERVAL);
+    }
+    assert_eq!(sent(&pad), [0x10, 0x20, 0x30]);
+}
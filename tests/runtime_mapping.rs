@@ -0,0 +1,74 @@
+use super::*;
+
+// Dpad up held, byte 2 bit 0 of a 360 report
+fn dpad_up() -> [u8; 20] {
+    let mut report = report_360();
+    report[2] = 0x01;
+    report
+}
+
+fn hat_y(events: &[SinkEvent]) -> Option<i32> {
+    events.iter().find_map(|event| match *event {
+        SinkEvent::Abs(AbsoluteAxis::Hat0Y, value) => Some(value),
+        _ => None,
+    })
+}
+
+fn up_button(events: &[SinkEvent]) -> Option<bool> {
+    events.iter().find_map(|event| match *event {
+        SinkEvent::Key(Button::TriggerHappy3, pressed) => Some(pressed),
+        _ => None,
+    })
+}
+
+#[test]
+fn dpad_to_buttons_flips_mid_stream() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    xpad.decode(&dpad_up());
+    let events = pad.sink.take();
+    assert_eq!(hat_y(&events), Some(-1));
+    assert_eq!(up_button(&events), None);
+
+    assert!(xpad.set_mapping(MapFlags::DPAD_TO_BUTTONS));
+    xpad.decode(&dpad_up());
+    let events = pad.sink.take();
+    assert_eq!(up_button(&events), Some(true));
+    assert_eq!(hat_y(&events), None);
+
+    assert!(xpad.set_mapping(MapFlags::empty()));
+    xpad.decode(&dpad_up());
+    assert_eq!(hat_y(&pad.sink.take()), Some(-1));
+}
+
+#[test]
+fn other_pads_keep_their_mapping() {
+    let first = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let second = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let (one, two) = (first.build(), second.build());
+
+    one.set_mapping(MapFlags::DPAD_TO_BUTTONS);
+    one.decode(&dpad_up());
+    two.decode(&dpad_up());
+    assert_eq!(up_button(&first.sink.take()), Some(true));
+    assert_eq!(hat_y(&second.sink.take()), Some(-1));
+}
+
+#[test]
+fn reports_whether_the_controls_changed() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let before = xpad_capabilities(&xpad);
+
+    assert!(xpad.set_mapping(MapFlags::DPAD_TO_BUTTONS));
+    let after = xpad_capabilities(&xpad);
+    for button in XPAD_BTN_PAD {
+        assert!(!before.buttons.contains(&button) && after.buttons.contains(&button));
+    }
+    for axis in XPAD_ABS_PAD {
+        assert!(before.axes.contains(&axis) && !after.axes.contains(&axis));
+    }
+
+    // Same mapping again, nothing to re-advertise
+    assert!(!xpad.set_mapping(MapFlags::DPAD_TO_BUTTONS));
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+fn press_a(pad: &XpadFixture, xpad: &UsbXpad) -> Vec<SinkEvent> {
+    let mut report = report_360();
+    report[3] = 0x10; // A
+    xpad.decode(&report);
+    pad.sink.take()
+}
+
+#[test]
+fn button_is_preceded_by_its_scancode_when_enabled() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_emit_scancodes(true);
+
+    let events = press_a(&pad, &xpad);
+    let a = events
+        .iter()
+        .position(|event| *event == SinkEvent::Key(Button::A, true))
+        .unwrap();
+    assert_eq!(events[a - 1], SinkEvent::Scan(0x130)); // BTN_A
+}
+
+#[test]
+fn no_scancodes_by_default() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert!(!press_a(&pad, &xpad)
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Scan(_))));
+    assert!(!xpad_capabilities(&xpad).scancodes);
+}
+
+#[test]
+fn toggling_scancodes_asks_for_new_capabilities() {
+    let xpad = UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    assert!(xpad.set_emit_scancodes(true));
+    assert!(xpad_capabilities(&xpad).scancodes);
+    assert!(!xpad.set_emit_scancodes(true));
+    assert!(xpad.set_emit_scancodes(false));
+    assert!(!xpad_capabilities(&xpad).scancodes);
+}
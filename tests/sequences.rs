@@ -0,0 +1,58 @@
+use super::*;
+
+// There is no packet-loss count to check any more, it went with the
+// input sequence tracker; what a reconnect still resets is the output
+// serial and the init sequence.
+
+fn receiver() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719);
+    let xpad = pad.build();
+    xpad.decode(&[0x08, 0x80]);
+    (pad, xpad)
+}
+
+fn run_up_sequences(xpad: &UsbXpad) {
+    xpad.odata_serial.store(7, Ordering::SeqCst);
+    *xpad.init_seq.lock().unwrap() = 3;
+}
+
+#[test]
+fn reconnect_starts_the_sequences_over() {
+    let (_pad, xpad) = receiver();
+    run_up_sequences(&xpad);
+
+    xpad.decode(&[0x08, 0x00]);
+    xpad.decode(&[0x08, 0x80]);
+    assert_eq!(xpad.odata_serial.load(Ordering::SeqCst), 0);
+    assert_eq!(*xpad.init_seq.lock().unwrap(), 0);
+}
+
+#[test]
+fn staying_connected_keeps_them() {
+    let (_pad, xpad) = receiver();
+    run_up_sequences(&xpad);
+
+    // The receiver repeats the status, that isn't a new connection
+    xpad.decode(&[0x08, 0x80]);
+    assert_eq!(xpad.odata_serial.load(Ordering::SeqCst), 7);
+    assert_eq!(*xpad.init_seq.lock().unwrap(), 3);
+
+    // Nor is the pad going away
+    xpad.decode(&[0x08, 0x00]);
+    assert_eq!(xpad.odata_serial.load(Ordering::SeqCst), 7);
+}
+
+#[test]
+fn first_output_after_reinitialize_is_serial_0() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    run_up_sequences(&xpad);
+
+    xpad.reinitialize().unwrap();
+    let packets = pad.transport.take();
+    assert_eq!(packets[0][2], 0);
+    assert_eq!(packets[0], XBOXONE_POWER_ON[..]);
+}
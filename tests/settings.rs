@@ -0,0 +1,122 @@
+use super::*;
+
+fn pad() -> UsbXpad {
+    UsbXpad::test_fixture(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+}
+
+// Every setting moved off its default
+fn customize(xpad: &UsbXpad) {
+    xpad.set_mapping(MapFlags::DPAD_TO_BUTTONS | MapFlags::TRIGGERS_BOTH);
+    xpad.set_stick_deadzone(4000);
+    xpad.set_stick_dpad_threshold(20000);
+    xpad.set_socd_mode(Some(SocdMode::LastWins));
+    let mut remap = ButtonRemap::default();
+    remap.swap(Button::A, Button::B);
+    remap.set(Button::X, Button::TL);
+    xpad.set_button_remap(remap).unwrap();
+    xpad.set_rumble_gain(60);
+    xpad.set_default_led(Some(LedState::Pattern(LedCommand::TopRightOn as u8)));
+    xpad.set_axis_config(AxisConfig {
+        invert_lt: true,
+        invert_rt: false,
+    });
+}
+
+#[test]
+fn settings_round_trip_onto_a_fresh_pad() {
+    let original = pad();
+    customize(&original);
+    let settings = original.export_settings();
+    assert_ne!(settings, pad().export_settings());
+
+    let restored = pad();
+    assert!(restored.apply_settings(&settings));
+    assert_eq!(restored.export_settings(), settings);
+    assert_eq!(
+        *restored.remap.lock().unwrap(),
+        *original.remap.lock().unwrap()
+    );
+}
+
+#[test]
+fn changes_after_export_are_undone_by_applying_it() {
+    let xpad = pad();
+    customize(&xpad);
+    let settings = xpad.export_settings();
+
+    xpad.set_mapping(MapFlags::empty());
+    xpad.set_stick_deadzone(0);
+    xpad.set_button_remap(ButtonRemap::default()).unwrap();
+    xpad.set_rumble_gain(100);
+    xpad.set_axis_config(AxisConfig::default());
+    assert_ne!(xpad.export_settings(), settings);
+
+    xpad.apply_settings(&settings);
+    assert_eq!(xpad.export_settings(), settings);
+}
+
+#[test]
+fn remap_is_stored_as_sorted_evdev_codes() {
+    let xpad = pad();
+    customize(&xpad);
+    let a = Button::A.code().unwrap();
+    let b = Button::B.code().unwrap();
+    let x = Button::X.code().unwrap();
+    let tl = Button::TL.code().unwrap();
+    let mut expected = vec![(a, b), (b, a), (x, tl)];
+    expected.sort_unstable();
+    assert_eq!(xpad.export_settings().remap, expected);
+}
+
+#[test]
+fn unknown_codes_are_skipped() {
+    let mut settings = pad().export_settings();
+    let a = Button::A.code().unwrap();
+    let b = Button::B.code().unwrap();
+    settings.remap = vec![(a, b), (0xffff, a)];
+
+    let xpad = pad();
+    xpad.apply_settings(&settings);
+    assert_eq!(xpad.export_settings().remap, [(a, b)]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn settings_round_trip_through_json() {
+    let xpad = pad();
+    customize(&xpad);
+    let settings = xpad.export_settings();
+    let json = serde_json::to_string(&settings).unwrap();
+    let parsed: XpadSettings = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, settings);
+}
+
+#[test]
+fn trigger_inversion_is_kept_per_trigger() {
+    let xpad = pad();
+    customize(&xpad);
+    let settings = xpad.export_settings();
+    assert!(settings.invert_lt && !settings.invert_rt);
+
+    let restored = pad();
+    restored.apply_settings(&settings);
+    assert_eq!(
+        *restored.axis_config.lock().unwrap(),
+        AxisConfig {
+            invert_lt: true,
+            invert_rt: false,
+        }
+    );
+}
+
+// Settings saved before trigger inversion existed load with it off
+#[cfg(feature = "serde")]
+#[test]
+fn json_without_trigger_inversion_still_loads() {
+    let mut json = serde_json::to_value(pad().export_settings()).unwrap();
+    let fields = json.as_object_mut().unwrap();
+    fields.remove("invert_lt");
+    fields.remove("invert_rt");
+    let parsed: XpadSettings = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, pad().export_settings());
+}
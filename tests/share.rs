@@ -0,0 +1,107 @@
+use super::*;
+
+// Xbox One input report of `len` bytes with nothing held
+fn report_one_of(len: usize) -> Vec<u8> {
+    let mut report = vec![0u8; len];
+    report[0] = GIP_CMD_INPUT;
+    report[3] = (len - 4) as u8;
+    report
+}
+
+fn ready_pad(ids: (u16, u16)) -> (XpadFixture, UsbXpad) {
+    let device = XPAD_DEVICES.get(&ids).unwrap();
+    let pad = XpadFixture::new(XType::XboxOne, device.mapping, device.quirks).ids(ids.0, ids.1);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    (pad, xpad)
+}
+
+#[test]
+fn series_pad_reads_share_26_bytes_from_the_end() {
+    let (pad, xpad) = ready_pad((0x045e, 0x0b12));
+    xpad.set_share_action(ShareAction::Button(Button::Record))
+        .unwrap();
+
+    let mut report = report_one_of(48);
+    report[22] = 0x01;
+    xpad.decode(&report);
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Record, true)));
+
+    // Byte 30, where the other pads keep it, means nothing here
+    let mut report = report_one_of(48);
+    report[30] = 0x01;
+    xpad.decode(&report);
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Record, false)));
+}
+
+#[test]
+fn other_pads_read_share_18_bytes_from_the_end() {
+    let (pad, xpad) = ready_pad((0x2e95, 0x0504));
+    xpad.set_share_action(ShareAction::Button(Button::Record))
+        .unwrap();
+
+    let mut report = report_one_of(40);
+    report[22] = 0x01;
+    xpad.decode(&report);
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Record, true)));
+}
+
+#[test]
+fn share_defaults_to_select() {
+    let (pad, xpad) = ready_pad((0x045e, 0x0b12));
+    let mut report = report_one_of(48);
+    report[22] = 0x01;
+    xpad.decode(&report);
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Select, true)));
+}
+
+#[test]
+fn callback_sees_each_change_once() {
+    let (pad, xpad) = ready_pad((0x045e, 0x0b12));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    xpad.set_share_action(ShareAction::Callback(Box::new(move |pressed| {
+        log.lock().unwrap().push(pressed)
+    })))
+    .unwrap();
+
+    for byte in [0x01, 0x01, 0x00] {
+        let mut report = report_one_of(48);
+        report[22] = byte;
+        xpad.decode(&report);
+    }
+    assert_eq!(*seen.lock().unwrap(), [true, false]);
+    // The callback replaces the key, nothing but the view button goes out
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Select, true))));
+}
+
+#[test]
+fn ignore_drops_the_button() {
+    let (pad, xpad) = ready_pad((0x045e, 0x0b12));
+    xpad.set_share_action(ShareAction::Ignore).unwrap();
+    let mut report = report_one_of(48);
+    report[22] = 0x01;
+    xpad.decode(&report);
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Select, true))));
+}
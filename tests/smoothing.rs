@@ -0,0 +1,95 @@
+use super::*;
+
+fn x_after(pad: &XpadFixture, xpad: &UsbXpad, x: i16) -> i32 {
+    let mut report = report_360();
+    report[6..8].copy_from_slice(&x.to_le_bytes());
+    xpad.decode(&report);
+    pad.sink
+        .take()
+        .iter()
+        .rev()
+        .find_map(|event| match *event {
+            SinkEvent::Abs(AbsoluteAxis::X, value) => Some(value),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn a_step_converges_at_the_configured_alpha() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 0.5);
+
+    // The first frame seeds the filter as is
+    assert_eq!(x_after(&pad, &xpad, 0), 0);
+    let steps: Vec<i32> = (0..5).map(|_| x_after(&pad, &xpad, 16000)).collect();
+    assert_eq!(steps, [8000, 12000, 14000, 15000, 15500]);
+
+    let mut last = 15500;
+    for _ in 0..30 {
+        let value = x_after(&pad, &xpad, 16000);
+        assert!(value >= last && value <= 16000);
+        last = value;
+    }
+    assert_eq!(last, 16000);
+}
+
+#[test]
+fn a_smaller_alpha_moves_slower() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 0.25);
+    x_after(&pad, &xpad, 0);
+    assert_eq!(x_after(&pad, &xpad, 16000), 4000);
+    assert_eq!(x_after(&pad, &xpad, 16000), 7000);
+}
+
+#[test]
+fn alpha_one_and_other_axes_pass_through() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_smoothing(AbsoluteAxis::Y, 0.5);
+    x_after(&pad, &xpad, 0);
+    assert_eq!(x_after(&pad, &xpad, 16000), 16000);
+
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 0.5);
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 1.0);
+    x_after(&pad, &xpad, 0);
+    assert_eq!(x_after(&pad, &xpad, 16000), 16000);
+}
+
+#[test]
+fn a_reconnected_pad_starts_from_its_first_frame() {
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719);
+    let xpad = pad.build();
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 0.5);
+    let frame = |x: i16| {
+        let mut report = report_360w();
+        report[4 + 6..4 + 8].copy_from_slice(&x.to_le_bytes());
+        xpad.decode(&report);
+        pad.sink.take().iter().rev().find_map(|event| match *event {
+            SinkEvent::Abs(AbsoluteAxis::X, value) => Some(value),
+            _ => None,
+        })
+    };
+    xpad.decode(&[0x08, 0x80]);
+    assert_eq!(frame(0), Some(0));
+    assert_eq!(frame(16000), Some(8000));
+
+    // Gone and back: no memory of the old stick position
+    xpad.decode(&[0x08, 0x00]);
+    xpad.decode(&[0x08, 0x80]);
+    assert_eq!(frame(16000), Some(16000));
+}
+
+#[test]
+fn reinitialize_forgets_the_filter_history() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_smoothing(AbsoluteAxis::X, 0.5);
+    x_after(&pad, &xpad, 0);
+    xpad.reinitialize().unwrap();
+    assert_eq!(x_after(&pad, &xpad, 16000), 16000);
+}
@@ -0,0 +1,117 @@
+use super::*;
+
+const UP: u8 = 0x01;
+const DOWN: u8 = 0x02;
+const LEFT: u8 = 0x04;
+const RIGHT: u8 = 0x08;
+
+// Feed d-pad states in order, returns the hat the last one resolved to
+fn hat_after(mode: Option<SocdMode>, dpad: &[u8]) -> (i32, i32) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_socd_mode(mode);
+
+    let mut hat = (0, 0);
+    for &bits in dpad {
+        let mut report = report_360();
+        report[2] = bits;
+        xpad.decode(&report);
+        for event in pad.sink.take() {
+            match event {
+                SinkEvent::Abs(AbsoluteAxis::Hat0X, value) => hat.0 = value,
+                SinkEvent::Abs(AbsoluteAxis::Hat0Y, value) => hat.1 = value,
+                _ => (),
+            }
+        }
+    }
+    hat
+}
+
+#[test]
+fn neutral_cancels_both_axes() {
+    assert_eq!(
+        hat_after(Some(SocdMode::Neutral), &[LEFT, LEFT | RIGHT]),
+        (0, 0)
+    );
+    assert_eq!(hat_after(Some(SocdMode::Neutral), &[UP, UP | DOWN]), (0, 0));
+}
+
+#[test]
+fn last_wins_takes_the_newer_direction() {
+    assert_eq!(
+        hat_after(Some(SocdMode::LastWins), &[LEFT, LEFT | RIGHT]),
+        (1, 0)
+    );
+    assert_eq!(
+        hat_after(Some(SocdMode::LastWins), &[RIGHT, LEFT | RIGHT]),
+        (-1, 0)
+    );
+    assert_eq!(
+        hat_after(Some(SocdMode::LastWins), &[UP, UP | DOWN]),
+        (0, 1)
+    );
+    assert_eq!(
+        hat_after(Some(SocdMode::LastWins), &[DOWN, UP | DOWN]),
+        (0, -1)
+    );
+    // Both held from the start, nothing to go by
+    assert_eq!(hat_after(Some(SocdMode::LastWins), &[LEFT | RIGHT]), (0, 0));
+}
+
+#[test]
+fn up_priority_keeps_up_and_cancels_left_right() {
+    assert_eq!(
+        hat_after(Some(SocdMode::UpPriority), &[LEFT, LEFT | RIGHT]),
+        (0, 0)
+    );
+    assert_eq!(
+        hat_after(Some(SocdMode::UpPriority), &[DOWN, UP | DOWN]),
+        (0, -1)
+    );
+}
+
+#[test]
+fn single_directions_pass_through_every_mode() {
+    for mode in [
+        None,
+        Some(SocdMode::Neutral),
+        Some(SocdMode::LastWins),
+        Some(SocdMode::UpPriority),
+    ] {
+        assert_eq!(hat_after(mode, &[UP | LEFT]), (-1, -1), "{:?}", mode);
+        assert_eq!(hat_after(mode, &[DOWN | RIGHT]), (1, 1), "{:?}", mode);
+    }
+}
+
+#[test]
+fn mad_catz_fightsticks_clean_socd_and_qanba_keeps_the_kernel_mapping() {
+    for ids in [
+        (0x0738, 0x4718),
+        (0x0738, 0x4a01),
+        (0x1bad, 0xf019),
+        (0x1bad, 0xf080),
+    ] {
+        let device = XPAD_DEVICES.get(&ids).unwrap();
+        assert!(
+            device.quirks.contains(QuirkFlags::SOCD_CLEANING),
+            "{}",
+            device.name
+        );
+    }
+    for ids in [(0x2c22, 0x2303), (0x2c22, 0x2500), (0x2c22, 0x2503)] {
+        let device = XPAD_DEVICES.get(&ids).unwrap();
+        assert!(
+            device.quirks.contains(QuirkFlags::SOCD_CLEANING),
+            "{}",
+            device.name
+        );
+        assert_eq!(device.mapping, MapFlags::empty(), "{}", device.name);
+    }
+}
+
+#[test]
+fn socd_quirk_starts_in_neutral() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::SOCD_CLEANING);
+    let xpad = pad.build();
+    assert_eq!(xpad.socd.lock().unwrap().mode, Some(SocdMode::Neutral));
+}
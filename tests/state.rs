@@ -0,0 +1,76 @@
+use super::*;
+
+#[test]
+fn state_reflects_every_input_of_a_frame() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert_eq!(xpad.current_state(), ControllerState::default());
+
+    let mut report = report_360();
+    report[2] = 0x01 | 0x04 | 0x10; // up, left, start
+    report[3] = 0x01 | 0x10; // TL, A
+    report[4] = 100;
+    report[5] = 200;
+    report[6..8].copy_from_slice(&1000i16.to_le_bytes());
+    report[8..10].copy_from_slice(&(-2000i16).to_le_bytes());
+    report[10..12].copy_from_slice(&(-3000i16).to_le_bytes());
+    report[12..14].copy_from_slice(&4000i16.to_le_bytes());
+    xpad.decode(&report);
+
+    assert_eq!(
+        xpad.current_state(),
+        ControllerState {
+            buttons: ButtonSet::A | ButtonSet::TL | ButtonSet::START,
+            left_stick: (1000, 1999),
+            right_stick: (-3000, -4001),
+            triggers: (100, 200),
+            dpad: (-1, -1),
+            paddles: [false; 4],
+        }
+    );
+
+    // The next frame replaces it, released controls included
+    xpad.decode(&report_360());
+    assert_eq!(
+        xpad.current_state(),
+        ControllerState {
+            left_stick: (0, -1),
+            right_stick: (0, -1),
+            ..ControllerState::default()
+        }
+    );
+}
+
+#[test]
+fn dpad_buttons_fill_in_the_dpad() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::DPAD_TO_BUTTONS,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[2] = 0x02 | 0x08; // down, right
+    xpad.decode(&report);
+
+    let state = xpad.current_state();
+    assert_eq!(state.dpad, (1, 1));
+    assert_eq!(state.buttons, ButtonSet::DPAD_DOWN | ButtonSet::DPAD_RIGHT);
+}
+
+#[test]
+fn elite_paddles_show_up_in_the_state() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::PADDLES, QuirkFlags::empty())
+        .ids(0x045e, 0x0b00);
+    let xpad = pad.build().with_serial_and_firmware(None, 0x050b);
+    handshake(&pad, &xpad);
+
+    let mut report = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    report[0] = GIP_CMD_FIRMWARE;
+    report[GIP_ELITE_PADDLES_OFFSET] = 0x01 | 0x04;
+    xpad.decode(&report);
+
+    let state = xpad.current_state();
+    assert_eq!(state.paddles, [true, false, true, false]);
+    assert_eq!(state.buttons, ButtonSet::PADDLE1 | ButtonSet::PADDLE3);
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+fn decode_stick(mapping: MapFlags, dpad: u8, (x, y): (i16, i16)) -> Vec<SinkEvent> {
+    let pad = XpadFixture::new(XType::Xbox360, mapping, QuirkFlags::empty());
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[2] = dpad;
+    report[6..8].copy_from_slice(&x.to_le_bytes());
+    report[8..10].copy_from_slice(&y.to_le_bytes());
+    xpad.decode(&report);
+    pad.sink.take()
+}
+
+fn abs(events: &[SinkEvent], axis: AbsoluteAxis) -> Option<i32> {
+    events.iter().rev().find_map(|event| match *event {
+        SinkEvent::Abs(a, value) if a == axis => Some(value),
+        _ => None,
+    })
+}
+
+// Hat for a raw left stick position, Y positive up as the pad sends it
+fn hat(stick: (i16, i16)) -> (i32, i32) {
+    let events = decode_stick(MapFlags::STICK_TO_DPAD, 0, stick);
+    (
+        abs(&events, AbsoluteAxis::Hat0X).unwrap(),
+        abs(&events, AbsoluteAxis::Hat0Y).unwrap(),
+    )
+}
+
+#[test]
+fn cardinal_directions() {
+    assert_eq!(hat((0, 30000)), (0, -1));
+    assert_eq!(hat((0, -30000)), (0, 1));
+    assert_eq!(hat((-30000, 0)), (-1, 0));
+    assert_eq!(hat((30000, 0)), (1, 0));
+}
+
+#[test]
+fn diagonals_need_a_diagonal_push() {
+    assert_eq!(hat((20000, 20000)), (1, -1));
+    assert_eq!(hat((-20000, -20000)), (-1, 1));
+    // A little drift on the other axis stays cardinal
+    assert_eq!(hat((30000, 5000)), (1, 0));
+}
+
+#[test]
+fn inside_the_threshold_is_centred() {
+    assert_eq!(hat((10000, 0)), (0, 0));
+
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::STICK_TO_DPAD, QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_stick_dpad_threshold(8000);
+    let mut report = report_360();
+    report[6..8].copy_from_slice(&10000i16.to_le_bytes());
+    xpad.decode(&report);
+    assert_eq!(abs(&pad.sink.take(), AbsoluteAxis::Hat0X), Some(1));
+}
+
+#[test]
+fn sticks_are_still_reported() {
+    let events = decode_stick(MapFlags::STICK_TO_DPAD, 0, (30000, 0));
+    assert_eq!(abs(&events, AbsoluteAxis::X), Some(30000));
+    assert_eq!(abs(&events, AbsoluteAxis::Hat0X), Some(1));
+}
+
+#[test]
+fn the_real_dpad_wins() {
+    // D-pad left while the stick points right
+    let events = decode_stick(MapFlags::STICK_TO_DPAD, 0x04, (30000, 0));
+    assert_eq!(abs(&events, AbsoluteAxis::Hat0X), Some(-1));
+}
+
+#[test]
+fn dpad_buttons_follow_the_stick_too() {
+    let events = decode_stick(
+        MapFlags::STICK_TO_DPAD | MapFlags::DPAD_TO_BUTTONS,
+        0,
+        (0, 30000),
+    );
+    assert!(events.contains(&SinkEvent::Key(Button::TriggerHappy3, true)));
+    for button in [
+        Button::TriggerHappy1,
+        Button::TriggerHappy2,
+        Button::TriggerHappy4,
+    ] {
+        assert!(
+            events.contains(&SinkEvent::Key(button, false)),
+            "{:?}",
+            button
+        );
+    }
+}
+
+#[test]
+fn without_the_flag_the_stick_leaves_the_hat_alone() {
+    let events = decode_stick(MapFlags::empty(), 0, (30000, 30000));
+    assert_eq!(abs(&events, AbsoluteAxis::Hat0X), Some(0));
+    assert_eq!(abs(&events, AbsoluteAxis::Hat0Y), Some(0));
+}
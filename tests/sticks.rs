@@ -0,0 +1,45 @@
+use super::*;
+
+fn abs(events: &[SinkEvent], axis: AbsoluteAxis) -> Option<i32> {
+    events.iter().rev().find_map(|event| match event {
+        SinkEvent::Abs(a, value) if *a == axis => Some(*value),
+        _ => None,
+    })
+}
+
+#[test]
+fn wired_360_reports_both_sticks() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+
+    let mut report = report_360();
+    report[6..8].copy_from_slice(&1000i16.to_le_bytes());
+    report[8..10].copy_from_slice(&(-2000i16).to_le_bytes());
+    report[10..12].copy_from_slice(&3000i16.to_le_bytes());
+    report[12..14].copy_from_slice(&(-4000i16).to_le_bytes());
+    xpad.decode(&report);
+
+    // Y is flipped the way Linux does it, ~raw
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::X), Some(1000));
+    assert_eq!(abs(&events, AbsoluteAxis::Y), Some(1999));
+    assert_eq!(abs(&events, AbsoluteAxis::Rx), Some(3000));
+    assert_eq!(abs(&events, AbsoluteAxis::Ry), Some(3999));
+}
+
+#[test]
+fn sticks_to_null_drops_both_sticks() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::STICKS_TO_NULL,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    let mut report = report_360();
+    report[10..12].copy_from_slice(&3000i16.to_le_bytes());
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    for axis in XPAD_ABS {
+        assert_eq!(abs(&events, axis), None, "{:?}", axis);
+    }
+}
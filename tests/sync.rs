@@ -0,0 +1,168 @@
+use super::*;
+
+fn ready(pad: XpadFixture) -> (XpadFixture, UsbXpad) {
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    (pad, xpad)
+}
+
+fn xboxone() -> (XpadFixture, UsbXpad) {
+    ready(XpadFixture::new(
+        XType::XboxOne,
+        MapFlags::empty(),
+        QuirkFlags::empty(),
+    ))
+}
+
+fn virtual_key(guide: bool) -> [u8; 6] {
+    [
+        GIP_CMD_VIRTUAL_KEY,
+        GIP_OPT_INTERNAL,
+        0x00,
+        0x02,
+        guide as u8,
+        0x00,
+    ]
+}
+
+// Events, then exactly one SYN_REPORT closing them
+#[track_caller]
+fn assert_one_frame(events: &[SinkEvent]) {
+    let syncs = events
+        .iter()
+        .filter(|&&event| event == SinkEvent::Sync)
+        .count();
+    assert!(events.len() > 1, "{:?}", events);
+    assert_eq!(syncs, 1, "{:?}", events);
+    assert_eq!(events.last(), Some(&SinkEvent::Sync), "{:?}", events);
+}
+
+#[test]
+fn xbox_one_input_is_one_frame() {
+    let (pad, xpad) = xboxone();
+    let mut report = report_one();
+    report[4] = 0x10; // A
+    xpad.decode(&report);
+    assert_one_frame(&pad.sink.take());
+
+    // Nothing changed, the frame is still reported and synced once
+    xpad.decode(&report);
+    assert_one_frame(&pad.sink.take());
+}
+
+#[test]
+fn guide_changes_are_one_frame_and_repeats_are_silent() {
+    let (pad, xpad) = xboxone();
+    xpad.decode(&virtual_key(true));
+    assert_eq!(
+        pad.sink.take(),
+        [SinkEvent::Key(Button::Mode, true), SinkEvent::Sync]
+    );
+
+    xpad.decode(&virtual_key(true));
+    assert!(pad.sink.take().is_empty());
+
+    xpad.decode(&virtual_key(false));
+    assert_one_frame(&pad.sink.take());
+}
+
+// A press held back for long-press detection emits nothing, so no SYN
+#[test]
+fn guide_held_for_long_press_is_silent() {
+    let (pad, xpad) = xboxone();
+    xpad.set_guide_longpress_handler(Duration::from_millis(800), Box::new(|| ()));
+    xpad.decode(&virtual_key(true));
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn reports_without_input_are_silent() {
+    let (pad, xpad) = xboxone();
+    // Only the Elite 2 on 5.11 firmware reports paddles in this one
+    let mut firmware = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    firmware[0] = GIP_CMD_FIRMWARE;
+    let reports = [
+        vec![GIP_CMD_STATUS, 0x00, 0x00, 0x01, 0x8a],
+        vec![GIP_CMD_ACK, GIP_OPT_INTERNAL, 0x00, 0x09, 0x00],
+        vec![
+            GIP_CMD_IDENTIFY,
+            GIP_OPT_INTERNAL,
+            0x03,
+            0x04,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+        ],
+        firmware,
+    ];
+    for report in reports {
+        xpad.decode(&report);
+        assert!(pad.sink.take().is_empty(), "{:02x?}", report);
+    }
+}
+
+#[test]
+fn short_and_early_input_is_silent() {
+    let (pad, xpad) = xboxone();
+    xpad.decode(&report_one()[..XPADONE_BUTTONS_LEN - 1]);
+    assert!(pad.sink.take().is_empty());
+
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.reinitialize().unwrap();
+    xpad.decode(&report_one());
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn elite_paddles_are_one_frame() {
+    let (pad, xpad) = {
+        let pad = XpadFixture::new(XType::XboxOne, MapFlags::PADDLES, QuirkFlags::empty())
+            .ids(0x045e, 0x0b00);
+        let xpad = pad.build().with_serial_and_firmware(None, 0x050b);
+        handshake(&pad, &xpad);
+        pad.sink.take();
+        (pad, xpad)
+    };
+    let mut report = vec![0u8; GIP_ELITE_MAPPING_OFFSET + 1];
+    report[0] = GIP_CMD_FIRMWARE;
+    report[GIP_ELITE_PADDLES_OFFSET] = 0x01;
+    xpad.decode(&report);
+    assert_one_frame(&pad.sink.take());
+}
+
+#[test]
+fn wheel_and_guitar_input_are_one_frame() {
+    let (pad, xpad) = ready(
+        XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+            .ids(0x0f0d, 0x0151),
+    );
+    xpad.decode(&report_one());
+    assert_one_frame(&pad.sink.take());
+
+    let (pad, xpad) = ready(
+        XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::GHL_XBOXONE)
+            .ids(0x1430, 0x079b),
+    );
+    let mut report = vec![0u8; 28];
+    report[0] = 0x21;
+    xpad.decode(&report);
+    assert_one_frame(&pad.sink.take());
+}
+
+#[test]
+fn other_protocols_sync_once_per_report() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(&report_360());
+    assert_one_frame(&pad.sink.take());
+    xpad.decode(&report_360()[..4]);
+    assert!(pad.sink.take().is_empty());
+
+    let pad = XpadFixture::new(XType::Xbox, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.decode(&[0u8; XPAD_REPORT_LEN]);
+    assert_one_frame(&pad.sink.take());
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+const VENDOR_AND_INTERFACE: u16 =
+    linux_usb::USB_DEVICE_ID_MATCH_VENDOR | linux_usb::USB_DEVICE_ID_MATCH_INT_INFO;
+
+fn triple(id: &UsbDeviceId) -> (u8, u8, u8) {
+    (
+        id.b_interface_class,
+        id.b_interface_subclass,
+        id.b_interface_protocol,
+    )
+}
+
+#[test]
+fn xbox360_vendor_matches_wired_pads_and_receivers() {
+    let [wired, wireless] = UsbDeviceId::xbox360_vendor(0x1bad);
+    for id in [&wired, &wireless] {
+        assert_eq!(id.match_flags, VENDOR_AND_INTERFACE);
+        assert_eq!(id.id_vendor, 0x1bad);
+    }
+    assert_eq!(triple(&wired), (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 1));
+    assert_eq!(
+        triple(&wireless),
+        (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 129)
+    );
+}
+
+#[test]
+fn xboxone_vendor_matches_gip_interfaces() {
+    let [id] = UsbDeviceId::xboxone_vendor(0x0e6f);
+    assert_eq!(id.match_flags, VENDOR_AND_INTERFACE);
+    assert_eq!(id.id_vendor, 0x0e6f);
+    assert_eq!(triple(&id), (linux_usb::USB_CLASS_VENDOR_SPEC, 71, 208));
+}
+
+#[test]
+fn every_table_entry_names_a_protocol() {
+    for id in XPAD_TABLE {
+        assert!(
+            id.protocol_xtype().is_some(),
+            "{:04x} {:?}",
+            id.id_vendor,
+            triple(id)
+        );
+    }
+}
+
+#[test]
+fn each_360_vendor_has_both_protocols() {
+    for id in XPAD_TABLE {
+        if triple(id) != (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 1) {
+            continue;
+        }
+        let wireless = XPAD_TABLE.iter().any(|other| {
+            other.id_vendor == id.id_vendor
+                && triple(other) == (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 129)
+        });
+        assert!(wireless, "{:04x} has no receiver entry", id.id_vendor);
+    }
+}
+
+#[test]
+fn no_entry_is_listed_twice() {
+    for (i, id) in XPAD_TABLE.iter().enumerate() {
+        let twice = XPAD_TABLE[i + 1..].iter().any(|other| {
+            other.match_flags == id.match_flags
+                && other.id_vendor == id.id_vendor
+                && triple(other) == triple(id)
+        });
+        assert!(!twice, "{:04x} {:?} listed twice", id.id_vendor, triple(id));
+    }
+}
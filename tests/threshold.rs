@@ -0,0 +1,66 @@
+use super::*;
+
+const T: u8 = DEFAULT_TRIGGER_THRESHOLD;
+
+// LT state after each 8-bit value, on a 360 pad with triggers as buttons
+fn lt_360(values: &[u8]) -> Vec<bool> {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::TRIGGERS_TO_BUTTONS,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    values
+        .iter()
+        .map(|&value| {
+            let mut report = report_360();
+            report[4] = value;
+            xpad.decode(&report);
+            pad.sink.take().contains(&SinkEvent::Key(Button::TL2, true))
+        })
+        .collect()
+}
+
+#[test]
+fn threshold_on_the_360() {
+    assert_eq!(lt_360(&[T - 1]), [false]);
+    assert_eq!(lt_360(&[T]), [false]);
+    assert_eq!(lt_360(&[T + 1]), [true]);
+}
+
+#[test]
+fn threshold_on_the_xboxone() {
+    let pad = XpadFixture::new(
+        XType::XboxOne,
+        MapFlags::TRIGGERS_TO_BUTTONS,
+        QuirkFlags::empty(),
+    )
+    .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+
+    // 10-bit triggers, compared on their top 8 bits
+    for (value, pressed) in [(T - 1, false), (T + 1, true)] {
+        let mut report = report_one();
+        report[6..8].copy_from_slice(&(u16::from(value) << 2).to_le_bytes());
+        xpad.decode(&report);
+        assert_eq!(
+            pad.sink.take().contains(&SinkEvent::Key(Button::TL2, true)),
+            pressed,
+            "{}",
+            value
+        );
+    }
+}
+
+#[test]
+fn hysteresis_keeps_a_hovering_trigger_from_chattering() {
+    let low = T - TRIGGER_HYSTERESIS;
+    // Pressed past the threshold, it stays pressed until it drops to
+    // `low`, and then stays released until it is past the threshold again
+    assert_eq!(
+        lt_360(&[T + 1, T - 1, low + 1, T - 1, low, low + 1, T, T + 1]),
+        [true, true, true, true, false, false, false, true]
+    );
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+// Decoded input lines this thread logged since the last call
+fn decoded_lines() -> Vec<String> {
+    logged_lines()
+        .into_iter()
+        .map(|(_, line)| line)
+        .filter(|line| line.starts_with("Decoded input: "))
+        .collect()
+}
+
+#[test]
+fn a_frame_is_logged_as_buttons_and_axes() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let _level = LOG_LEVEL_LOCK.lock().unwrap();
+    decoded_lines();
+
+    let mut report = report_360();
+    report[3] = 0x10 | 0x20; // A, B
+    report[4] = 200;
+    report[6..8].copy_from_slice(&1000i16.to_le_bytes());
+    xpad.decode(&report);
+
+    let lines = decoded_lines();
+    assert_eq!(lines.len(), 1);
+    let line = &lines[0];
+    assert!(
+        line.starts_with("Decoded input: pressed: A B | "),
+        "{}",
+        line
+    );
+    for axis in ["X=1000", "Y=-1", "Z=200", "Rz=0"] {
+        assert!(
+            line.split(' ').any(|word| word == axis),
+            "{} in {}",
+            axis,
+            line
+        );
+    }
+}
+
+#[test]
+fn nothing_held_logs_an_empty_pressed_list() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let _level = LOG_LEVEL_LOCK.lock().unwrap();
+    decoded_lines();
+
+    xpad.decode(&report_360());
+    let lines = decoded_lines();
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].starts_with("Decoded input: pressed:  | "),
+        "{}",
+        lines[0]
+    );
+}
+
+#[test]
+fn later_values_of_an_axis_replace_earlier_ones() {
+    let summary = summarize_frame(&[
+        SinkEvent::Key(Button::A, true),
+        SinkEvent::Abs(AbsoluteAxis::X, 5),
+        SinkEvent::Key(Button::B, false),
+        SinkEvent::Abs(AbsoluteAxis::X, 7),
+        SinkEvent::Abs(AbsoluteAxis::Z, 255),
+    ]);
+    assert_eq!(summary, "pressed: A | X=7 Z=255");
+}
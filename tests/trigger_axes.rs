@@ -0,0 +1,115 @@
+use super::*;
+
+fn abs(events: &[SinkEvent], axis: AbsoluteAxis) -> Option<i32> {
+    events.iter().rev().find_map(|event| match *event {
+        SinkEvent::Abs(a, value) if a == axis => Some(value),
+        _ => None,
+    })
+}
+
+// LT at 0x40 and RT at 0xc0 on a 360 pad
+fn triggers_360(xpad: &UsbXpad, pad: &XpadFixture) -> Vec<SinkEvent> {
+    let mut report = report_360();
+    report[4] = 0x40;
+    report[5] = 0xc0;
+    xpad.decode(&report);
+    pad.sink.take()
+}
+
+fn layout(left: AbsoluteAxis, right: AbsoluteAxis) -> TriggerAxisLayout {
+    TriggerAxisLayout { left, right }
+}
+
+#[test]
+fn default_is_z_and_rz() {
+    assert_eq!(
+        TriggerAxisLayout::default(),
+        layout(AbsoluteAxis::Z, AbsoluteAxis::Rz)
+    );
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    let events = triggers_360(&xpad, &pad);
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0x40));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0xc0));
+}
+
+#[test]
+fn swapped_layout_on_both_decoders() {
+    let swapped = layout(AbsoluteAxis::Rz, AbsoluteAxis::Z);
+
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_trigger_axis_layout(swapped).unwrap();
+    let events = triggers_360(&xpad, &pad);
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0x40));
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0xc0));
+
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    xpad.set_trigger_axis_layout(swapped).unwrap();
+    pad.sink.take();
+    let mut report = report_one();
+    report[6..8].copy_from_slice(&0x100u16.to_le_bytes());
+    report[8..10].copy_from_slice(&0x300u16.to_le_bytes());
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0x100));
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0x300));
+}
+
+#[test]
+fn freed_stick_axes_can_take_the_triggers() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::STICKS_TO_NULL,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    xpad.set_trigger_axis_layout(layout(AbsoluteAxis::Ry, AbsoluteAxis::Rx))
+        .unwrap();
+    let axes = xpad_capabilities(&xpad).axes;
+    assert!(axes.contains(&AbsoluteAxis::Ry) && axes.contains(&AbsoluteAxis::Rx));
+    assert!(!axes.contains(&AbsoluteAxis::Z) && !axes.contains(&AbsoluteAxis::Rz));
+
+    let events = triggers_360(&xpad, &pad);
+    assert_eq!(abs(&events, AbsoluteAxis::Ry), Some(0x40));
+    assert_eq!(abs(&events, AbsoluteAxis::Rx), Some(0xc0));
+    assert_eq!(abs(&events, AbsoluteAxis::Z), None);
+}
+
+#[test]
+fn stick_axes_in_use_are_refused() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    assert!(matches!(
+        xpad.set_trigger_axis_layout(layout(AbsoluteAxis::Ry, AbsoluteAxis::Rz)),
+        Err(XpadError::AxisConflict(axis)) if axis == AbsoluteAxis::Ry
+    ));
+    assert!(matches!(
+        xpad.set_trigger_axis_layout(layout(AbsoluteAxis::Z, AbsoluteAxis::Z)),
+        Err(XpadError::AxisConflict(axis)) if axis == AbsoluteAxis::Z
+    ));
+    assert_eq!(
+        *xpad.trigger_axes.lock().unwrap(),
+        TriggerAxisLayout::default()
+    );
+}
+
+#[test]
+fn sticks_coming_back_reset_the_layout() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::STICKS_TO_NULL,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    xpad.set_trigger_axis_layout(layout(AbsoluteAxis::Ry, AbsoluteAxis::Rx))
+        .unwrap();
+    xpad.set_mapping(MapFlags::empty());
+    assert_eq!(
+        *xpad.trigger_axes.lock().unwrap(),
+        TriggerAxisLayout::default()
+    );
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+const INVERTED: AxisConfig = AxisConfig {
+    invert_lt: true,
+    invert_rt: true,
+};
+
+fn abs(events: &[SinkEvent], axis: AbsoluteAxis) -> Option<i32> {
+    events.iter().rev().find_map(|event| match event {
+        SinkEvent::Abs(a, value) if *a == axis => Some(*value),
+        _ => None,
+    })
+}
+
+#[test]
+fn inverted_360_triggers_read_max_released_and_0_pressed() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_config(INVERTED);
+    let max = trigger_abs_max(XType::Xbox360);
+
+    xpad.decode(&report_360());
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(max));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(max));
+
+    let mut report = report_360();
+    report[4] = 0xff;
+    report[5] = 0xff;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0));
+}
+
+#[test]
+fn inverted_xboxone_triggers_read_max_released_and_0_pressed() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    xpad.set_axis_config(INVERTED);
+    let max = trigger_abs_max(XType::XboxOne);
+
+    xpad.decode(&report_one());
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(max));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(max));
+
+    // 10-bit triggers fully pressed
+    let mut report = report_one();
+    report[6..10].copy_from_slice(&[0xff, 0x03, 0xff, 0x03]);
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0));
+}
+
+#[test]
+fn trigger_buttons_ignore_the_inversion() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::TRIGGERS_BOTH, QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_axis_config(INVERTED);
+
+    let mut report = report_360();
+    report[4] = 0xff;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::TL2, true)));
+    assert!(events.contains(&SinkEvent::Key(Button::TR2, false)));
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0));
+}
+
+#[test]
+fn sticks_to_null_keeps_analog_triggers() {
+    let pad = XpadFixture::new(
+        XType::Xbox360,
+        MapFlags::STICKS_TO_NULL,
+        QuirkFlags::empty(),
+    );
+    let xpad = pad.build();
+    let caps = xpad_capabilities(&xpad);
+    assert!(caps.axes.contains(&AbsoluteAxis::Z) && caps.axes.contains(&AbsoluteAxis::Rz));
+    assert!(!caps.axes.contains(&AbsoluteAxis::X));
+
+    let mut report = report_360();
+    report[4] = 0xff;
+    report[5] = 0x80;
+    report[6..8].copy_from_slice(&0x4000i16.to_le_bytes());
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(255));
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(128));
+    assert_eq!(abs(&events, AbsoluteAxis::X), None);
+    assert!(!events.contains(&SinkEvent::Key(Button::TL2, true)));
+}
+
+#[test]
+fn triggers_fill_the_advertised_range() {
+    for (xtype, full) in [(XType::Xbox360, 0xffu16), (XType::XboxOne, 0x3ff)] {
+        let pad =
+            XpadFixture::new(xtype, MapFlags::empty(), QuirkFlags::empty()).ids(0x045e, 0x02ea);
+        let xpad = pad.build();
+        let report = match xtype {
+            XType::XboxOne => {
+                handshake(&pad, &xpad);
+                let mut report = report_one();
+                report[6..8].copy_from_slice(&full.to_le_bytes());
+                report
+            }
+            _ => {
+                let mut report = report_360().to_vec();
+                report[4] = full as u8;
+                report
+            }
+        };
+        pad.sink.take();
+        xpad.decode(&report);
+        let (min, max) = abs_range(&xpad, AbsoluteAxis::Z);
+        assert_eq!((min, max), (0, trigger_abs_max(xtype)));
+        assert_eq!(
+            abs(&pad.sink.take(), AbsoluteAxis::Z),
+            Some(max),
+            "{:?}",
+            xtype
+        );
+    }
+}
+
+#[test]
+fn triggers_both_reports_the_axis_and_the_button() {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::TRIGGERS_BOTH, QuirkFlags::empty());
+    let xpad = pad.build();
+    let caps = xpad_capabilities(&xpad);
+    assert!(caps.axes.contains(&AbsoluteAxis::Z) && caps.buttons.contains(&Button::TL2));
+
+    let mut report = report_360();
+    report[4] = 0xc0;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(0xc0));
+    assert!(events.contains(&SinkEvent::Key(Button::TL2, true)));
+
+    // Below the threshold only the axis moves
+    report[4] = 20;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Z), Some(20));
+    assert!(events.contains(&SinkEvent::Key(Button::TL2, false)));
+}
+
+#[test]
+fn triggers_both_on_10_bit_triggers() {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::TRIGGERS_BOTH, QuirkFlags::empty())
+        .ids(0x045e, 0x02ea);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+
+    let mut report = report_one();
+    report[8..10].copy_from_slice(&0x3ffu16.to_le_bytes());
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert_eq!(abs(&events, AbsoluteAxis::Rz), Some(0x3ff));
+    assert!(events.contains(&SinkEvent::Key(Button::TR2, true)));
+    assert!(events.contains(&SinkEvent::Key(Button::TL2, false)));
+}
+
+#[test]
+fn triggers_to_buttons_overrides_triggers_both() {
+    let both = MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::TRIGGERS_BOTH;
+    assert_eq!(normalize_mapping(both), MapFlags::TRIGGERS_TO_BUTTONS);
+    assert_eq!(
+        normalize_mapping(MapFlags::TRIGGERS_BOTH),
+        MapFlags::TRIGGERS_BOTH
+    );
+
+    let pad = XpadFixture::new(XType::Xbox360, both, QuirkFlags::empty());
+    let xpad = pad.build();
+    assert_eq!(xpad.mapping(), MapFlags::TRIGGERS_TO_BUTTONS);
+    let mut report = report_360();
+    report[4] = 0xff;
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::TL2, true)));
+    assert_eq!(abs(&events, AbsoluteAxis::Z), None);
+}
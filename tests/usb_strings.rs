@@ -0,0 +1,49 @@
+use super::*;
+
+fn generic() -> UsbXpad {
+    XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x0000, 0x0000)
+        .name("Generic X-Box pad")
+        .build()
+}
+
+fn name_line(xpad: &UsbXpad) -> String {
+    xpad.describe_config().lines().next().unwrap().to_string()
+}
+
+#[test]
+fn generic_pad_shows_its_product_string() {
+    let xpad = generic().with_usb_strings(Some("Acme"), Some("Turbo Pad"));
+    assert_eq!(xpad.display_name(), "Acme Turbo Pad");
+    assert_eq!(name_line(&xpad), "name: Acme Turbo Pad");
+    assert_eq!(xpad.diagnostics().product.as_deref(), Some("Turbo Pad"));
+
+    let xpad = generic().with_usb_strings(None, Some("Turbo Pad"));
+    assert_eq!(xpad.display_name(), "Turbo Pad");
+}
+
+#[test]
+fn blank_strings_count_as_missing() {
+    let xpad = generic().with_usb_strings(Some("  "), Some(""));
+    assert_eq!(xpad.display_name(), "Generic X-Box pad");
+    assert_eq!(xpad.diagnostics().product, None);
+
+    let xpad = generic().with_usb_strings(Some(" Acme "), Some(" Turbo Pad\t"));
+    assert_eq!(xpad.display_name(), "Acme Turbo Pad");
+
+    // Only a manufacturer doesn't name the pad
+    let xpad = generic().with_usb_strings(Some("Acme"), None);
+    assert_eq!(xpad.display_name(), "Generic X-Box pad");
+}
+
+#[test]
+fn table_name_wins_for_known_pads() {
+    let xpad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty())
+        .name("Microsoft X-Box 360 pad")
+        .build()
+        .with_usb_strings(Some("Microsoft"), Some("Controller"));
+    assert_eq!(xpad.display_name(), "Microsoft X-Box 360 pad");
+    assert_eq!(name_line(&xpad), "name: Microsoft X-Box 360 pad");
+    // The product string is still there to look at
+    assert_eq!(xpad.diagnostics().product.as_deref(), Some("Controller"));
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+fn watched() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360, MapFlags::empty(), QuirkFlags::empty());
+    let xpad = pad.build();
+    xpad.set_watchdog_timeout(Some(TIMEOUT));
+    (pad, xpad)
+}
+
+// A held, left stick pushed and LT pressed
+fn busy_report() -> [u8; 20] {
+    let mut report = report_360();
+    report[3] = 0x10;
+    report[4] = 0xff;
+    report[6..8].copy_from_slice(&20000i16.to_le_bytes());
+    report
+}
+
+#[test]
+fn silence_past_the_timeout_releases_everything() {
+    let (pad, xpad) = watched();
+    xpad.decode(&busy_report());
+    pad.sink.take();
+
+    pad.clock.advance(TIMEOUT - Duration::from_millis(1));
+    assert_eq!(xpad_watchdog_tick(&xpad), Some(Duration::from_millis(1)));
+    assert!(pad.sink.take().is_empty());
+
+    pad.clock.advance(Duration::from_millis(1));
+    assert_eq!(xpad_watchdog_tick(&xpad), Some(TIMEOUT));
+    let events = pad.sink.take();
+
+    let mut expected: Vec<SinkEvent> = xpad
+        .emitted_buttons()
+        .into_iter()
+        .map(|button| SinkEvent::Key(button, false))
+        .collect();
+    expected.extend(
+        xpad.emitted_axes()
+            .into_iter()
+            .map(|axis| SinkEvent::Abs(axis, 0)),
+    );
+    expected.push(SinkEvent::Sync);
+    assert_eq!(events, expected);
+    assert_eq!(xpad.current_state(), ControllerState::default());
+}
+
+#[test]
+fn the_neutral_frame_is_sent_once_per_silence() {
+    let (pad, xpad) = watched();
+    xpad.decode(&busy_report());
+    pad.clock.advance(TIMEOUT);
+    xpad_watchdog_tick(&xpad);
+    pad.sink.take();
+
+    pad.clock.advance(TIMEOUT * 3);
+    assert_eq!(xpad_watchdog_tick(&xpad), Some(TIMEOUT));
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn each_report_rearms_the_watchdog() {
+    let (pad, xpad) = watched();
+    xpad.decode(&busy_report());
+    pad.clock.advance(TIMEOUT / 2);
+    xpad.decode(&busy_report());
+    pad.sink.take();
+
+    // Counted from the second report
+    pad.clock.advance(TIMEOUT / 2);
+    assert_eq!(xpad_watchdog_tick(&xpad), Some(TIMEOUT / 2));
+    assert!(pad.sink.take().is_empty());
+
+    // And a report after a neutral frame allows the next one
+    pad.clock.advance(TIMEOUT / 2);
+    xpad_watchdog_tick(&xpad);
+    assert!(!pad.sink.take().is_empty());
+    xpad.decode(&busy_report());
+    pad.sink.take();
+    pad.clock.advance(TIMEOUT);
+    xpad_watchdog_tick(&xpad);
+    assert!(pad.sink.take().contains(&SinkEvent::Key(Button::A, false)));
+}
+
+#[test]
+fn nothing_runs_while_disabled_or_before_the_first_report() {
+    let (pad, xpad) = watched();
+    pad.clock.advance(TIMEOUT * 2);
+    assert_eq!(xpad_watchdog_tick(&xpad), None);
+
+    xpad.set_watchdog_timeout(None);
+    xpad.decode(&busy_report());
+    pad.sink.take();
+    pad.clock.advance(TIMEOUT * 2);
+    assert_eq!(xpad_watchdog_tick(&xpad), None);
+    assert!(pad.sink.take().is_empty());
+}
+
+#[test]
+fn inverted_triggers_rest_at_their_maximum() {
+    let (pad, xpad) = watched();
+    xpad.set_axis_config(AxisConfig {
+        invert_lt: true,
+        invert_rt: false,
+    });
+    xpad.decode(&busy_report());
+    pad.sink.take();
+    pad.clock.advance(TIMEOUT);
+    xpad_watchdog_tick(&xpad);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Z, 255)));
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Rz, 0)));
+}
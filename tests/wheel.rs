@@ -0,0 +1,41 @@
+use super::*;
+
+// Not a capture, see xpadone_process_wheel()
+fn wheel_report(steering: i16, brake: u16, throttle: u16) -> Vec<u8> {
+    let mut report = report_one();
+    report[6..8].copy_from_slice(&brake.to_le_bytes());
+    report[8..10].copy_from_slice(&throttle.to_le_bytes());
+    report[10..12].copy_from_slice(&steering.to_le_bytes());
+    report
+}
+
+fn hori_wheel() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::XboxOne, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x0f0d, 0x0151);
+    let xpad = pad.build();
+    handshake(&pad, &xpad);
+    pad.sink.take();
+    (pad, xpad)
+}
+
+#[test]
+fn pedals_and_steering_land_on_their_axes() {
+    let (pad, xpad) = hori_wheel();
+    xpad.decode(&wheel_report(-0x4000, 0x200, 0x3ff));
+
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::X, -0x4000)));
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Z, 0x200)));
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Rz, 0x3ff)));
+    // Steering isn't a stick, nothing is reported for the Y axes
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Abs(AbsoluteAxis::Y | AbsoluteAxis::Ry, _))));
+}
+
+#[test]
+fn short_wheel_report_is_dropped() {
+    let (pad, xpad) = hori_wheel();
+    xpad.decode(&wheel_report(0x1000, 0x100, 0x100)[..XPADONE_WHEEL_REPORT_LEN - 1]);
+    assert!(pad.sink.take().is_empty());
+}
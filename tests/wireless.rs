@@ -0,0 +1,69 @@
+use super::*;
+
+fn receiver() -> (XpadFixture, UsbXpad) {
+    let pad = XpadFixture::new(XType::Xbox360W, MapFlags::empty(), QuirkFlags::empty())
+        .ids(0x045e, 0x0719);
+    let xpad = pad.build();
+    (pad, xpad)
+}
+
+#[test]
+fn pad_data_is_decoded_as_a_360_report() {
+    let (pad, xpad) = receiver();
+    let mut report = report_360w();
+    report[4 + 3] = 0x04; // guide
+    xpad.decode(&report);
+    assert!(pad
+        .sink
+        .take()
+        .contains(&SinkEvent::Key(Button::Mode, true)));
+}
+
+#[test]
+fn only_pad_data_reports_carry_input() {
+    // Like Linux, anything but byte 1 == 0x01 is not pad data, however
+    // the bytes after it look
+    let (pad, xpad) = receiver();
+    for kind in [0x00, 0x02, 0x0f] {
+        let mut report = report_360w();
+        report[1] = kind;
+        report[4 + 3] = 0x04;
+        xpad.decode(&report);
+    }
+    assert!(!pad
+        .sink
+        .take()
+        .iter()
+        .any(|event| matches!(event, SinkEvent::Key(Button::Mode, _))));
+}
+
+#[test]
+fn byte_2_is_not_read_as_a_sequence_number() {
+    // Nothing documents a sequence number in receiver reports, gaps in
+    // byte 2 don't change how the report decodes
+    let (pad, xpad) = receiver();
+    for (seq, pressed) in [(1, true), (2, false), (4, true)] {
+        let mut report = report_360w();
+        report[2] = seq;
+        report[4 + 3] = if pressed { 0x10 } else { 0 };
+        xpad.decode(&report);
+        assert!(pad
+            .sink
+            .take()
+            .contains(&SinkEvent::Key(Button::A, pressed)));
+    }
+}
+
+#[test]
+fn presence_handler_sees_each_change_once() {
+    let (_pad, xpad) = receiver();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    xpad.set_presence_handler(Box::new(move |present| log.lock().unwrap().push(present)));
+
+    // Presence reports: byte 0 bit 3, pad there while byte 1 bit 7 is set
+    for status in [0x80, 0x80, 0x00, 0x00, 0x80] {
+        xpad.decode(&[0x08, status]);
+    }
+    assert_eq!(*seen.lock().unwrap(), [true, false, true]);
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+#[test]
+fn thrustmaster_wheel_is_decoded_like_a_pad() {
+    let pad =
+        XpadFixture::new(XType::Xbox, MapFlags::empty(), QuirkFlags::empty()).ids(0x044f, 0x0f00);
+    let xpad = pad.build();
+
+    // Nothing documents separate pedal bytes, so the wheel keeps the
+    // triggers and the black button of the common layout
+    let mut report = vec![0u8; XPAD_REPORT_LEN];
+    report[8] = 0xff; // black
+    report[10] = 0x80; // left trigger
+    report[11] = 0x40; // right trigger
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Key(Button::C, true)));
+    for axis in [AbsoluteAxis::Z, AbsoluteAxis::Rz] {
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SinkEvent::Abs(a, v) if *a == axis && *v > 0)));
+    }
+}
+
+// No pad in the table is known to send 8-bit sticks, so the 8-bit case
+// can only be checked on StickWidth itself
+#[test]
+fn eight_bit_sticks_scale_to_the_full_range() {
+    let scale = |raw: i8| StickWidth::Bits8.scale(raw as u8 as i16);
+    assert_eq!(scale(127), 32767);
+    assert_eq!(scale(-127), -32767);
+    assert_eq!(scale(-128), -32768);
+    assert_eq!(scale(0), 0);
+    assert_eq!(scale(64), 64 * 32767 / 127);
+    // Only the low byte carries the value
+    assert_eq!(StickWidth::Bits8.scale(0x7f7f), 32767);
+}
+
+#[test]
+fn sixteen_bit_sticks_pass_through() {
+    for raw in [i16::MIN, -1, 0, 1234, i16::MAX] {
+        assert_eq!(StickWidth::Bits16.scale(raw), raw);
+    }
+    assert_eq!(XBOX_LAYOUT_COMMON.stick_width, StickWidth::Bits16);
+
+    let pad =
+        XpadFixture::new(XType::Xbox, MapFlags::empty(), QuirkFlags::empty()).ids(0x045e, 0x0202);
+    let xpad = pad.build();
+    let mut report = vec![0u8; XPAD_REPORT_LEN];
+    report[12..14].copy_from_slice(&i16::MAX.to_le_bytes());
+    report[14..16].copy_from_slice(&100i16.to_le_bytes());
+    xpad.decode(&report);
+    let events = pad.sink.take();
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::X, i32::from(i16::MAX))));
+    assert!(events.contains(&SinkEvent::Abs(AbsoluteAxis::Y, i32::from(!100i16))));
+}
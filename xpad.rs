@@ -4,13 +4,14 @@ use input_linux::{
     LedKind, MiscKind, RelativeAxis, SoundKind, SwitchKind,
 };
 use std::io::{Result, Write};
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::{Arc, Mutex, Weak, atomic::{AtomicBool, AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering}};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use usb::{Urb, UsbDevice, UsbError};
 use input::{InputDevice, InputEvent, Button};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use bitflags::bitflags;
 use kernel::{prelude::*, usb, input};
-use linux::input::{ABS_X, ABS_Y, ABS_Z, ABS_RZ, ABS_HAT0X, ABS_HAT0Y};
 use linux::stat::{S_IRUGO, S_IWUSR};
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -36,18 +37,102 @@ mod linux {
 
 // Network protocol constants
 const XPAD_PKT_LEN: usize = 64;
+
+// Shortest input reports each protocol can be decoded from
+const XPAD_REPORT_LEN: usize = 20;
+const XPAD360_REPORT_LEN: usize = 14;
+const XPAD360_SHORT_REPORT_LEN: usize = 10; // SHORT_REPORTS clones, no right stick
+
+// Report id and length that open a standard 360 input report
+const XPAD360_INPUT_HEADER: [u8; 2] = [0x00, 0x14];
+const XPADONE_REPORT_LEN: usize = 18;
+
+// Xbox One input reports grow with the firmware: buttons and d-pad only,
+// then triggers, left stick, right stick, and the share byte at the end
+const XPADONE_BUTTONS_LEN: usize = 6;
+const XPADONE_TRIGGERS_LEN: usize = 10;
+const XPADONE_LEFT_STICK_LEN: usize = 14;
 const GHL_GUITAR_POKE_INTERVAL: u64 = 8; // Seconds
+const KEEPALIVE_INTERVAL: u64 = 5; // Seconds
+
+// GIP (Xbox One) command ids
+const GIP_CMD_ACK: u8 = 0x01;
+const GIP_CMD_ANNOUNCE: u8 = 0x02;
+const GIP_CMD_STATUS: u8 = 0x03;
+const GIP_CMD_IDENTIFY: u8 = 0x04;
+const GIP_CMD_POWER: u8 = 0x05;
+const GIP_CMD_AUTHENTICATE: u8 = 0x06;
+const GIP_CMD_VIRTUAL_KEY: u8 = 0x07;
+const GIP_CMD_RUMBLE: u8 = 0x09;
+const GIP_CMD_LED: u8 = 0x0a;
+const GIP_CMD_FIRMWARE: u8 = 0x0c;
+const GIP_CMD_INPUT: u8 = 0x20;
+
+// GIP header options
+const GIP_OPT_ACK: u8 = 0x10;
+const GIP_OPT_INTERNAL: u8 = 0x20;
+
+// Low nibble of byte 1 is the GIP client: 0 is the pad itself, anything
+// else an accessory plugged into it
+const GIP_CLIENT_MASK: u8 = 0x0f;
+
+// Vendor and product of the announcing client, little endian. The payload
+// after the 4-byte header is a 6-byte address, two unknown bytes, then the
+// ids (struct gip_pkt_announce in the xone driver, bus/protocol.c).
+const GIP_ANNOUNCE_VENDOR_OFFSET: usize = 12;
+const GIP_ANNOUNCE_PRODUCT_OFFSET: usize = 14;
+
+// Byte 4 bit 7 of a status report, clear once the client has gone
+const GIP_STATUS_CONNECTED: u8 = 0x80;
+
+// Byte 4 of the pad's own status report: battery level in bits 0-1
+// (critical, low, medium, full), battery type in bits 2-3 with 0 meaning
+// none (wired), and bit 4 set while it charges
+const GIP_BATTERY_LEVEL_MASK: u8 = 0x03;
+const GIP_BATTERY_TYPE_MASK: u8 = 0x0c;
+const GIP_BATTERY_CHARGING: u8 = 0x10;
+
+// Accessory products we can name
+const GIP_PRODUCT_HEADSET_ADAPTER: u16 = 0x02e4;
+
+// Share button, bit 0 of a byte counted from the end of the input report
+// as Linux does: 26 bytes back on pads flagged SHARE_OFFSET (the Series
+// S|X pad, byte 22 of its 48-byte report), 18 back on the others
+const GIP_SHARE_FROM_END: usize = 18;
+const GIP_SHARE_FROM_END_OFFSET: usize = 26;
+
+// Elite 2 (firmware 5.11+) paddle bits and custom mapping flag, firmware
+// report. The flag is non-zero while a custom button mapping is applied.
+const GIP_ELITE_PADDLES_OFFSET: usize = 18;
+const GIP_ELITE_MAPPING_OFFSET: usize = 19;
+// Active profile slot, input report of Elite 2 firmware before 5.0
+const GIP_ELITE_PROFILE_OFFSET: usize = 19;
+
+// Power mode byte of a GIP_CMD_POWER request that turns the pad off
+const GIP_POWER_OFF: u8 = 0x04;
 
     /// Configuration flags for controller mapping
 bitflags::bitflags! {
     #[derive(Clone, Copy, Debug)]
-    struct MapFlags: u8 {
+    struct MapFlags: u16 {
         const DPAD_TO_BUTTONS    = 1 << 0;
         const TRIGGERS_TO_BUTTONS = 1 << 1;
         const STICKS_TO_NULL     = 1 << 2;
         const SELECT_BUTTON      = 1 << 3;
         const PADDLES           = 1 << 4;
         const PROFILE_BUTTON     = 1 << 5;
+        const STICK_TO_DPAD      = 1 << 6;
+        const TRIGGERS_BOTH      = 1 << 7;
+        const MODE_AS_CHORD      = 1 << 8;
+        const SHARE_OFFSET       = 1 << 9;
+    }
+}
+
+impl MapFlags {
+    // Whether ABS_X/Y/RX/RY are advertised and reported. STICK_TO_DPAD
+    // doesn't change this, the left stick keeps its axes as well.
+    fn uses_analog_sticks(self) -> bool {
+        !self.contains(MapFlags::STICKS_TO_NULL)
     }
 }
 
@@ -86,6 +171,24 @@ pub const DANCEPAD_MAP_CONFIG: MapFlags = MapFlags::DPAD_TO_BUTTONS
     | MapFlags::TRIGGERS_TO_BUTTONS
     | MapFlags::STICKS_TO_NULL;
 
+/// Arcade sticks: digital triggers and no analog sticks, the lever stays the hat
+pub const FIGHTSTICK_MAP_CONFIG: MapFlags = MapFlags::TRIGGERS_TO_BUTTONS
+    | MapFlags::STICKS_TO_NULL;
+
+/// Wheels: pedals stay on the trigger axes, the d-pad becomes buttons
+pub const WHEEL_MAP_CONFIG: MapFlags = MapFlags::DPAD_TO_BUTTONS;
+
+/// Mapping preset by the name used in config files, None for unknown names
+pub fn preset(name: &str) -> Option<MapFlags> {
+    match name {
+        "default" => Some(MapFlags::empty()),
+        "dancepad" => Some(DANCEPAD_MAP_CONFIG),
+        "fightstick" => Some(FIGHTSTICK_MAP_CONFIG),
+        "wheel" => Some(WHEEL_MAP_CONFIG),
+        _ => None,
+    }
+}
+
 /// Xbox controller hardware variants
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -100,6 +203,9 @@ pub enum XType {
 // Power management constants
 const XPAD360W_POWEROFF_TIMEOUT: u64 = 5; // Seconds
 
+// Receiver status report with the pad's battery level (0-255) in byte 4
+const XPAD360W_BATTERY_REPORT: u8 = 0x13;
+
 /// Packet types for different controller protocols
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -114,11 +220,42 @@ pub enum PacketType {
 bitflags::bitflags! {
     /// Hardware-specific behavior flags
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-    pub struct QuirkFlags: u8 {
+    pub struct QuirkFlags: u16 {
         const START_PKT_1   = 1 << 0;
         const START_PKT_2   = 1 << 1;
         const START_PKT_3   = 1 << 2;
         const GHL_XBOXONE   = 1 << 3;
+        const NO_RUMBLE     = 1 << 4;
+        const SHORT_REPORTS = 1 << 5;
+        const DEBOUNCE_BUTTONS = 1 << 6;
+        const HANDHELD_EXTRA_REPORTS = 1 << 7;
+        const SOCD_CLEANING = 1 << 8;
+        const NEEDS_KEEPALIVE = 1 << 9;
+    }
+}
+
+bitflags::bitflags! {
+    /// Features an Xbox One pad announces in its capabilities descriptor
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct GipCapabilities: u8 {
+        const RUMBLE         = 1 << 0;
+        const TRIGGER_RUMBLE = 1 << 1;
+        const PADDLES        = 1 << 2;
+        const SHARE          = 1 << 3;
+    }
+}
+
+bitflags::bitflags! {
+    /// What a configured pad can do, for UIs to grey out the rest
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct FeatureSet: u16 {
+        const RUMBLE         = 1 << 0;
+        const TRIGGER_RUMBLE = 1 << 1;
+        const PLAYER_LEDS    = 1 << 2;
+        const GUIDE_LED      = 1 << 3;
+        const BATTERY        = 1 << 4;
+        const PADDLES        = 1 << 5;
+        const SHARE          = 1 << 6;
     }
 }
 
@@ -141,6 +278,79 @@ static TRIGGERS_TO_BUTTONS: AtomicBool = AtomicBool::new(false);
 static STICKS_TO_NULL: AtomicBool = AtomicBool::new(false);
 static AUTO_POWEROFF: AtomicBool = AtomicBool::new(false);
 
+// Module parameters on top of the table mapping, as applied at probe
+fn param_map_flags() -> MapFlags {
+    let mut flags = MapFlags::empty();
+    if DPAD_TO_BUTTONS.load(Ordering::Relaxed) {
+        flags |= MapFlags::DPAD_TO_BUTTONS;
+    }
+    if TRIGGERS_TO_BUTTONS.load(Ordering::Relaxed) {
+        flags |= MapFlags::TRIGGERS_TO_BUTTONS;
+    }
+    if STICKS_TO_NULL.load(Ordering::Relaxed) {
+        flags |= MapFlags::STICKS_TO_NULL;
+    }
+    flags
+}
+
+// Trigger travel (8-bit scale) needed before TRIGGERS_TO_BUTTONS reports a press
+const DEFAULT_TRIGGER_THRESHOLD: u8 = 30;
+// Margin below the threshold a pressed trigger must fall before it releases
+const TRIGGER_HYSTERESIS: u8 = 8;
+
+/// How much the driver itself logs, on top of the `log` crate's filtering
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum LogLevel {
+    /// Warnings only
+    Quiet = 0,
+    /// Debug messages at debug level, the default
+    Normal = 1,
+    /// Debug messages (packet dumps, decoded summaries, quirk actions)
+    /// raised to info so they show up without touching the log filter
+    Verbose = 2,
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Normal as u8);
+
+/// Change the driver's verbosity at runtime, e.g. to capture a bug report
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+fn log_level() -> LogLevel {
+    match LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Quiet,
+        1 => LogLevel::Normal,
+        _ => LogLevel::Verbose,
+    }
+}
+
+// The driver's debug output, gated by set_log_level()
+macro_rules! xpad_debug {
+    ($($arg:tt)*) => {
+        match log_level() {
+            LogLevel::Quiet => (),
+            LogLevel::Normal => log::debug!($($arg)*),
+            LogLevel::Verbose => log::info!($($arg)*),
+        }
+    };
+}
+
+// Button transitions closer together than this are dropped on DEBOUNCE_BUTTONS devices
+static DEBOUNCE_WINDOW_MS: AtomicU32 = AtomicU32::new(10);
+
+// Output packets are retried this many times on a stall/timeout, see
+// UsbXpad::send_output_packet()
+const DEFAULT_OUT_RETRIES: u8 = 3;
+const OUT_RETRY_BACKOFF_MS: u64 = 2; // Grows linearly per attempt
+// Rumble updates closer together than this are coalesced
+const DEFAULT_RUMBLE_INTERVAL_MS: u64 = 10;
+// How often an enveloped effect re-sends its motor magnitudes
+const ENVELOPE_TICK_MS: u64 = 20;
+// Longest the motors may run without a break before they're stopped
+const DEFAULT_MAX_RUMBLE_SECS: u64 = 30;
+
 /// Xbox controller device definition
 #[derive(Debug, Clone)]
 struct XpadDevice {
@@ -400,7 +610,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         id_vendor: 0x045e,
         id_product: 0x0b12,
         name: "Microsoft Xbox Series S|X Controller",
-        mapping: MapFlags::from_bits(MAP_SELECT_BUTTON).unwrap(),
+        mapping: MapFlags::from_bits(MAP_SELECT_BUTTON | MAP_SHARE_OFFSET).unwrap(),
         xtype: XType::XboxOne,
         quirks: QuirkFlags::empty(),
     },
@@ -674,7 +884,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz Street Fighter IV FightStick SE",
         mapping: MapFlags::empty(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x0738, 0x4726) => XpadDevice {
         id_vendor: 0x0738,
@@ -730,7 +940,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz Arcade Game Stick",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x0738, 0x4a01) => XpadDevice {
         id_vendor: 0x0738,
@@ -738,7 +948,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz FightStick TE 2",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::XboxOne,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x0738, 0x6040) => XpadDevice {
         id_vendor: 0x0738,
@@ -770,7 +980,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz MVC2TE Stick 2",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x0738, 0xbeef) => XpadDevice {
         id_vendor: 0x0738,
@@ -890,7 +1100,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "HAMA VibraX - *FAULTY HARDWARE*",
         mapping: MapFlags::empty(),
         xtype: XType::Xbox,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::NO_RUMBLE.union(QuirkFlags::DEBOUNCE_BUTTONS),
     },
     (0x0d2f, 0x0002) => XpadDevice {
         id_vendor: 0x0d2f,
@@ -1626,7 +1836,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Lenovo Legion Controller for Windows",
         mapping: MapFlags::empty(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::HANDHELD_EXTRA_REPORTS,
     },
     (0x1949, 0x041a) => XpadDevice {
         id_vendor: 0x1949,
@@ -1682,7 +1892,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz Street Fighter IV SE Fighting Stick",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf019) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1690,7 +1900,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz Brawlstick for Xbox 360",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf021) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1778,7 +1988,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz SFxT Fightstick Pro",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf03d) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1794,7 +2004,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz MLG FightStick TE",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf03f) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1802,7 +2012,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz FightStick SoulCaliber",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf042) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1810,7 +2020,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz FightStick TES+",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf080) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1818,7 +2028,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "Mad Catz FightStick TE2",
         mapping: MapFlags::from_bits(MAP_TRIGGERS_TO_BUTTONS).unwrap(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x1bad, 0xf501) => XpadDevice {
         id_vendor: 0x1bad,
@@ -1938,7 +2148,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "BDA Xbox Series X Wired Controller",
         mapping: MapFlags::empty(),
         xtype: XType::XboxOne,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::NEEDS_KEEPALIVE,
     },
     (0x20d6, 0x2009) => XpadDevice {
         id_vendor: 0x20d6,
@@ -1946,7 +2156,7 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "PowerA Enhanced Wired Controller for Xbox Series X|S",
         mapping: MapFlags::empty(),
         xtype: XType::XboxOne,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::NEEDS_KEEPALIVE,
     },
     (0x20d6, 0x281f) => XpadDevice {
         id_vendor: 0x20d6,
@@ -2170,7 +2380,31 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
         name: "OneXPlayer Gamepad",
         mapping: MapFlags::empty(),
         xtype: XType::Xbox360,
-        quirks: QuirkFlags::empty(),
+        quirks: QuirkFlags::HANDHELD_EXTRA_REPORTS,
+    },
+    (0x2c22, 0x2303) => XpadDevice {
+        id_vendor: 0x2c22,
+        id_product: 0x2303,
+        name: "Qanba Obsidian Arcade Joystick",
+        mapping: MapFlags::empty(),
+        xtype: XType::Xbox360,
+        quirks: QuirkFlags::SOCD_CLEANING,
+    },
+    (0x2c22, 0x2500) => XpadDevice {
+        id_vendor: 0x2c22,
+        id_product: 0x2500,
+        name: "Qanba Dragon",
+        mapping: MapFlags::empty(),
+        xtype: XType::Xbox360,
+        quirks: QuirkFlags::SOCD_CLEANING,
+    },
+    (0x2c22, 0x2503) => XpadDevice {
+        id_vendor: 0x2c22,
+        id_product: 0x2503,
+        name: "Qanba Dragon Arcade Joystick",
+        mapping: MapFlags::empty(),
+        xtype: XType::XboxOne,
+        quirks: QuirkFlags::SOCD_CLEANING,
     },
     (0x294b, 0x3303) => XpadDevice {
         id_vendor: 0x294b,
@@ -2374,64 +2608,329 @@ static XPAD_DEVICES: Map<(u16, u16), XpadDevice> = phf_map! {
     },
 };
 
+// Rumble needs a known protocol and a device that isn't flagged as broken.
+// The GHL guitar has no motor and rejects the generic rumble packet.
+fn rumble_supported(xtype: XType, quirks: QuirkFlags) -> bool {
+    xtype != XType::Unknown
+        && !quirks.intersects(QuirkFlags::NO_RUMBLE | QuirkFlags::GHL_XBOXONE)
+}
+
+// Only Microsoft's own Xbox One pads have impulse trigger motors,
+// the Adaptive Controller has none
+fn trigger_rumble_supported(xtype: XType, quirks: QuirkFlags, id_vendor: u16, id_product: u16) -> bool {
+    xtype == XType::XboxOne
+        && rumble_supported(xtype, quirks)
+        && id_vendor == 0x045e
+        && id_product != 0x0b0a
+}
+
+impl XpadDevice {
+    fn supports_rumble(&self) -> bool {
+        rumble_supported(self.xtype, self.quirks)
+    }
+
+    fn supports_trigger_rumble(&self) -> bool {
+        trigger_rumble_supported(self.xtype, self.quirks, self.id_vendor, self.id_product)
+    }
+}
+
+// Devices added at runtime, consulted before XPAD_DEVICES
+static RUNTIME_DEVICES: Mutex<Vec<XpadDevice>> = Mutex::new(Vec::new());
+
+// Register (or replace) a device definition at runtime
+fn register_device(device: XpadDevice) {
+    let mut devices = RUNTIME_DEVICES.lock().unwrap();
+    devices.retain(|d| (d.id_vendor, d.id_product) != (device.id_vendor, device.id_product));
+    devices.push(device);
+}
+
+/// What happens to a device that isn't in the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackPolicy {
+    /// Generic pad, protocol taken from the interface class
+    Infer,
+    /// Generic pad forced to one protocol, whatever the interface says
+    AllowAsXbox,
+    AllowAsXbox360,
+    AllowAsXboxOne,
+    /// Not bound at all
+    Reject,
+}
+
+impl FallbackPolicy {
+    // Protocol the generic pad is forced to, if any
+    fn xtype(self) -> Option<XType> {
+        match self {
+            FallbackPolicy::AllowAsXbox => Some(XType::Xbox),
+            FallbackPolicy::AllowAsXbox360 => Some(XType::Xbox360),
+            FallbackPolicy::AllowAsXboxOne => Some(XType::XboxOne),
+            FallbackPolicy::Infer | FallbackPolicy::Reject => None,
+        }
+    }
+}
+
+static FALLBACK_POLICY: Mutex<FallbackPolicy> = Mutex::new(FallbackPolicy::Infer);
+
+/// Choose how devices missing from the table are handled
+pub fn set_fallback_policy(policy: FallbackPolicy) {
+    *FALLBACK_POLICY.lock().unwrap() = policy;
+}
+
+fn fallback_policy() -> FallbackPolicy {
+    *FALLBACK_POLICY.lock().unwrap()
+}
+
+/// Turn off matching unknown devices to the generic pad entry
+pub fn set_allow_generic_fallback(allow: bool) {
+    set_fallback_policy(if allow { FallbackPolicy::Infer } else { FallbackPolicy::Reject });
+}
+
+// Protocol of a generic pad forced by the fallback policy
+fn generic_xtype(device: &XpadDevice) -> Option<XType> {
+    if (device.id_vendor, device.id_product) != (0x0000, 0x0000) {
+        return None;
+    }
+    fallback_policy().xtype()
+}
+
+// Unprogrammed clones that never got a real ID all show up as ffff:ffff,
+// whatever pad they actually are. Matched exactly, never as a fallback.
+const CLONE_CATCH_ALL_ID: (u16, u16) = (0xffff, 0xffff);
+
+// Definition for a device: runtime registrations first, then the static
+// table, then the generic entry unless that has been disabled
+fn lookup_device(id_vendor: u16, id_product: u16) -> Option<XpadDevice> {
+    let runtime = RUNTIME_DEVICES.lock().unwrap();
+    if let Some(device) = runtime.iter().find(|d| (d.id_vendor, d.id_product) == (id_vendor, id_product)) {
+        return Some(device.clone());
+    }
+    drop(runtime);
+
+    if (id_vendor, id_product) != (0x0000, 0x0000) {
+        if let Some(device) = XPAD_DEVICES.get(&(id_vendor, id_product)) {
+            if (id_vendor, id_product) == CLONE_CATCH_ALL_ID {
+                log::warn!("ffff:ffff has no real vendor or product, treating it as an original Xbox pad is a guess");
+            }
+            return Some(device.clone());
+        }
+    }
+
+    let policy = fallback_policy();
+    if policy == FallbackPolicy::Reject {
+        return None;
+    }
+    let mut device = XPAD_DEVICES.get(&(0x0000, 0x0000)).cloned()?;
+    if let Some(xtype) = policy.xtype() {
+        device.xtype = xtype;
+    }
+    Some(device)
+}
+
+/// Owned copy of a device table entry, for external tooling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceRecord {
+    pub vendor: u16,
+    pub product: u16,
+    pub name: String,
+    pub xtype: XType,
+    pub mapping: MapFlags,
+    pub quirks: QuirkFlags,
+}
+
+impl From<&XpadDevice> for DeviceRecord {
+    fn from(device: &XpadDevice) -> Self {
+        Self {
+            vendor: device.id_vendor,
+            product: device.id_product,
+            name: device.name.to_string(),
+            xtype: device.xtype,
+            mapping: device.mapping,
+            quirks: device.quirks,
+        }
+    }
+}
+
+// Flags are written out by name so the output doesn't depend on bit values
+#[cfg(feature = "serde")]
+impl serde::Serialize for DeviceRecord {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mapping: Vec<&str> = self.mapping.iter_names().map(|(name, _)| name).collect();
+        let quirks: Vec<&str> = self.quirks.iter_names().map(|(name, _)| name).collect();
+
+        let mut record = serializer.serialize_struct("DeviceRecord", 6)?;
+        record.serialize_field("vendor", &self.vendor)?;
+        record.serialize_field("product", &self.product)?;
+        record.serialize_field("name", &self.name)?;
+        record.serialize_field("xtype", &format!("{:?}", self.xtype))?;
+        record.serialize_field("mapping", &mapping)?;
+        record.serialize_field("quirks", &quirks)?;
+        record.end()
+    }
+}
+
+/// Every known device, static table plus runtime registrations, sorted by id.
+/// A runtime registration replaces the static entry with the same id.
+pub fn export_device_table() -> Vec<DeviceRecord> {
+    let runtime = RUNTIME_DEVICES.lock().unwrap();
+    let mut records: Vec<DeviceRecord> = XPAD_DEVICES
+        .values()
+        .filter(|d| !runtime.iter().any(|r| (r.id_vendor, r.id_product) == (d.id_vendor, d.id_product)))
+        .chain(runtime.iter())
+        .map(DeviceRecord::from)
+        .collect();
+    records.sort_by_key(|r| (r.vendor, r.product));
+    records
+}
+
+// SDL bus type for USB devices
+const SDL_BUS_USB: u16 = 0x0003;
+
+/// SDL-style controller GUID, the key used by community mapping databases.
+///
+/// Layout, all fields little endian:
+///  0-1   bus type (0x0003, USB)
+///  2-3   CRC16 of the device name, left 0 (mapping databases match either way)
+///  4-5   vendor id
+///  6-7   0
+///  8-9   product id
+/// 10-11  0
+/// 12-13  device version (bcdDevice)
+/// 14-15  driver signature and data, 0 for evdev devices
+///
+/// e.g. a wired 360 pad, 045e:028e version 0x0114, is
+/// 030000005e0400008e02000014010000.
+fn controller_guid(dev: &XpadDevice, version: u16) -> [u8; 16] {
+    let mut guid = [0u8; 16];
+    guid[0..2].copy_from_slice(&SDL_BUS_USB.to_le_bytes());
+    guid[4..6].copy_from_slice(&dev.id_vendor.to_le_bytes());
+    guid[8..10].copy_from_slice(&dev.id_product.to_le_bytes());
+    guid[12..14].copy_from_slice(&version.to_le_bytes());
+    guid
+}
+
 // buttons shared with xbox and xbox360
-const XPAD_COMMON_BTN: [i16; 9] = [
-    BTN_A, BTN_B, BTN_X, BTN_Y,            // "analog" buttons
-    BTN_START, BTN_SELECT, BTN_THUMBL, BTN_THUMBR,  // start/back/sticks
-    -1                                     // terminating entry
+const XPAD_COMMON_BTN: [Button; 8] = [
+    Button::A, Button::B, Button::X, Button::Y,                 // "analog" buttons
+    Button::Start, Button::Select, Button::ThumbL, Button::ThumbR,  // start/back/sticks
 ];
 
 // original xbox controllers only
-const XPAD_BTN: [i16; 3] = [
-    BTN_C, BTN_Z,        // "analog" buttons
-    -1                   // terminating entry
+const XPAD_BTN: [Button; 2] = [
+    Button::C, Button::Z,        // "analog" buttons
 ];
 
 // used when dpad is mapped to buttons
-const XPAD_BTN_PAD: [i16; 5] = [
-    BTN_TRIGGER_HAPPY1, BTN_TRIGGER_HAPPY2,     // d-pad left, right
-    BTN_TRIGGER_HAPPY3, BTN_TRIGGER_HAPPY4,     // d-pad up, down
-    -1                         // terminating entry
+const XPAD_BTN_PAD: [Button; 4] = [
+    Button::TriggerHappy1, Button::TriggerHappy2,     // d-pad left, right
+    Button::TriggerHappy3, Button::TriggerHappy4,     // d-pad up, down
 ];
 
 // used when triggers are mapped to buttons
-const XPAD_BTN_TRIGGERS: [i16; 3] = [
-    BTN_TL2, BTN_TR2,        // triggers left/right
-    -1
+const XPAD_BTN_TRIGGERS: [Button; 2] = [
+    Button::TL2, Button::TR2,        // triggers left/right
 ];
 
 // buttons for x360 controller
-const XPAD360_BTN: [i16; 4] = [
-    BTN_TL, BTN_TR,        // Button LB/RB
-    BTN_MODE,              // The big X button
-    -1
+const XPAD360_BTN: [Button; 3] = [
+    Button::TL, Button::TR,        // Button LB/RB
+    Button::Mode,                  // The big X button
 ];
 
-const XPAD_ABS: [i16; 5] = [
-    ABS_X, ABS_Y,        // left stick
-    ABS_RX, ABS_RY,      // right stick
-    -1                   // terminating entry
+const XPAD_ABS: [AbsoluteAxis; 4] = [
+    AbsoluteAxis::X, AbsoluteAxis::Y,        // left stick
+    AbsoluteAxis::Rx, AbsoluteAxis::Ry,      // right stick
 ];
 
 // used when dpad is mapped to axes
-const XPAD_ABS_PAD: [i16; 3] = [
-    ABS_HAT0X, ABS_HAT0Y,  // d-pad axes
-    -1                     // terminating entry
+const XPAD_ABS_PAD: [AbsoluteAxis; 2] = [
+    AbsoluteAxis::Hat0X, AbsoluteAxis::Hat0Y,  // d-pad axes
 ];
 
-// used when triggers are mapped to axes
-const XPAD_ABS_TRIGGERS: [i16; 3] = [
-    ABS_Z, ABS_RZ,        // triggers left/right
-    -1
+// used when triggers are mapped to axes, unless a TriggerAxisLayout moves them
+const XPAD_ABS_TRIGGERS: [AbsoluteAxis; 2] = [
+    AbsoluteAxis::Z, AbsoluteAxis::Rz,        // triggers left/right
 ];
 
+// Axes the triggers are reported on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TriggerAxisLayout {
+    left: AbsoluteAxis,
+    right: AbsoluteAxis,
+}
+
+impl Default for TriggerAxisLayout {
+    fn default() -> Self {
+        let [left, right] = XPAD_ABS_TRIGGERS;
+        Self { left, right }
+    }
+}
+
+impl TriggerAxisLayout {
+    fn contains(&self, axis: AbsoluteAxis) -> bool {
+        self.left == axis || self.right == axis
+    }
+}
+
 // used when the controller has extra paddle buttons
-const XPAD_BTN_PADDLES: [i16; 5] = [
-    BTN_TRIGGER_HAPPY5, BTN_TRIGGER_HAPPY6,  // paddle upper right, lower right
-    BTN_TRIGGER_HAPPY7, BTN_TRIGGER_HAPPY8,  // paddle upper left, lower left
-    -1                                      // terminating entry
+const XPAD_BTN_PADDLES: [Button; 4] = [
+    Button::TriggerHappy5, Button::TriggerHappy6,  // paddle upper right, lower right
+    Button::TriggerHappy7, Button::TriggerHappy8,  // paddle upper left, lower left
+];
+
+// Raw evdev codes for the typed events. Decoders only deal in Button and
+// AbsoluteAxis, codes only appear where events leave the driver.
+// Variants missing from the tables below have no code, settings that name
+// one are refused.
+trait EvdevCode: Sized {
+    fn code(&self) -> Option<u16>;
+    fn from_code(code: u16) -> Option<Self>;
+}
+
+// (variant, code) for every button a decoder can emit
+const BUTTON_CODES: [(Button, u16); 24] = [
+    (Button::A, 0x130), (Button::B, 0x131), (Button::C, 0x132),
+    (Button::X, 0x133), (Button::Y, 0x134), (Button::Z, 0x135),
+    (Button::TL, 0x136), (Button::TR, 0x137),
+    (Button::TL2, 0x138), (Button::TR2, 0x139),
+    (Button::Select, 0x13a), (Button::Start, 0x13b), (Button::Mode, 0x13c),
+    (Button::ThumbL, 0x13d), (Button::ThumbR, 0x13e),
+    (Button::Record, 0xa7),
+    (Button::TriggerHappy1, 0x2c0), (Button::TriggerHappy2, 0x2c1),
+    (Button::TriggerHappy3, 0x2c2), (Button::TriggerHappy4, 0x2c3),
+    (Button::TriggerHappy5, 0x2c4), (Button::TriggerHappy6, 0x2c5),
+    (Button::TriggerHappy7, 0x2c6), (Button::TriggerHappy8, 0x2c7),
+];
+
+// (variant, code) for every axis a decoder can emit
+const AXIS_CODES: [(AbsoluteAxis, u16); 9] = [
+    (AbsoluteAxis::X, 0x00), (AbsoluteAxis::Y, 0x01), (AbsoluteAxis::Z, 0x02),
+    (AbsoluteAxis::Rx, 0x03), (AbsoluteAxis::Ry, 0x04), (AbsoluteAxis::Rz, 0x05),
+    (AbsoluteAxis::Hat0X, 0x10), (AbsoluteAxis::Hat0Y, 0x11),
+    (AbsoluteAxis::Profile, 0x21),
 ];
 
+impl EvdevCode for Button {
+    fn code(&self) -> Option<u16> {
+        BUTTON_CODES.iter().find(|(b, _)| b == self).map(|&(_, code)| code)
+    }
+
+    fn from_code(code: u16) -> Option<Self> {
+        BUTTON_CODES.iter().find(|&&(_, c)| c == code).map(|&(b, _)| b)
+    }
+}
+
+impl EvdevCode for AbsoluteAxis {
+    fn code(&self) -> Option<u16> {
+        AXIS_CODES.iter().find(|(a, _)| a == self).map(|&(_, code)| code)
+    }
+
+    fn from_code(code: u16) -> Option<Self> {
+        AXIS_CODES.iter().find(|&&(_, c)| c == code).map(|&(a, _)| a)
+    }
+}
+
 // used for GHL dpad mapping
 const DPAD_MAPPING: [(i16, i16); 9] = [
     (0, -1), (1, -1), (1, 0), (1, 1),
@@ -2477,6 +2976,121 @@ impl UsbDeviceId {
             b_interface_protocol: pr,
         }
     }
+
+    // Wired pads use protocol 1, wireless receivers protocol 129
+    const fn xbox360_vendor(vend: u16) -> [Self; 2] {
+        [
+            Self::xbox360_vendor_proto(vend, 1),
+            Self::xbox360_vendor_proto(vend, 129),
+        ]
+    }
+
+    const fn xboxone_vendor(vend: u16) -> [Self; 1] {
+        [Self::xboxone_vendor_proto(vend, 208)]
+    }
+
+    // Protocol implied by the interface triple, None when it doesn't identify one
+    fn protocol_xtype(&self) -> Option<XType> {
+        if self.match_flags & linux_usb::USB_DEVICE_ID_MATCH_INT_INFO == 0 {
+            return None;
+        }
+
+        match (self.b_interface_class, self.b_interface_subclass, self.b_interface_protocol) {
+            (b'X', b'B', _) => Some(XType::Xbox),
+            (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 1) => Some(XType::Xbox360),
+            (linux_usb::USB_CLASS_VENDOR_SPEC, 93, 129) => Some(XType::Xbox360W),
+            (linux_usb::USB_CLASS_VENDOR_SPEC, 71, 208) => Some(XType::XboxOne),
+            _ => None,
+        }
+    }
+
+    // Whether this entry matches an interface of a device from `id_vendor`
+    fn matches(&self, id_vendor: u16, intf: &InterfaceInfo) -> bool {
+        if self.match_flags & linux_usb::USB_DEVICE_ID_MATCH_VENDOR != 0 && self.id_vendor != id_vendor {
+            return false;
+        }
+        if self.match_flags & linux_usb::USB_DEVICE_ID_MATCH_INT_INFO == 0 {
+            return true;
+        }
+        // The original Xbox entry matches its class pair with any protocol
+        let any_protocol = (self.b_interface_class, self.b_interface_subclass) == (b'X', b'B');
+        self.b_interface_class == intf.class
+            && self.b_interface_subclass == intf.subclass
+            && (any_protocol || self.b_interface_protocol == intf.protocol)
+    }
+}
+
+// One endpoint of an interface, as read from its descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EndpointInfo {
+    // bEndpointAddress, bit 7 set for IN
+    address: u8,
+    interrupt: bool,
+}
+
+impl EndpointInfo {
+    fn is_interrupt_out(&self) -> bool {
+        self.interrupt && self.address & 0x80 == 0
+    }
+}
+
+// Interrupt-out endpoint to send output on, the first one the interface lists
+fn find_out_endpoint(endpoints: &[EndpointInfo]) -> Option<u8> {
+    endpoints
+        .iter()
+        .find(|ep| ep.is_interrupt_out())
+        .map(|ep| ep.address)
+}
+
+// XPAD_TABLE entry for an interface. 360 vendors have one entry per
+// protocol, so this is what tells a wired pad (1) from a receiver (129).
+fn match_interface(id_vendor: u16, intf: &InterfaceInfo) -> Option<&'static UsbDeviceId> {
+    XPAD_TABLE.iter().find(|id| id.matches(id_vendor, intf))
+}
+
+// One interface of a possibly composite device, as read from its descriptors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InterfaceInfo {
+    number: u8,
+    class: u8,
+    subclass: u8,
+    protocol: u8,
+}
+
+impl InterfaceInfo {
+    // Interface match entry for this interface on a device from `id_vendor`
+    fn device_id(&self, id_vendor: u16) -> UsbDeviceId {
+        UsbDeviceId {
+            match_flags: linux_usb::USB_DEVICE_ID_MATCH_VENDOR
+                       | linux_usb::USB_DEVICE_ID_MATCH_INT_INFO,
+            id_vendor,
+            b_interface_class: self.class,
+            b_interface_subclass: self.subclass,
+            b_interface_protocol: self.protocol,
+        }
+    }
+
+    // Controller input lives on the vendor-specific 93 (360) or 71 (Xbox One)
+    // subclass, or the original Xbox's own class. Audio, chatpad and the like
+    // sit on other interfaces of the same device.
+    fn is_input(&self) -> bool {
+        match (self.class, self.subclass) {
+            (linux_usb::USB_CLASS_VENDOR_SPEC, 93 | 71) => true,
+            (b'X', b'B') => true,
+            _ => false,
+        }
+    }
+}
+
+// Pick the input interface of a composite device, the lowest numbered one
+// if there are several
+fn select_interface(interfaces: &[InterfaceInfo]) -> Result<InterfaceInfo, XpadError> {
+    interfaces
+        .iter()
+        .filter(|intf| intf.is_input())
+        .min_by_key(|intf| intf.number)
+        .copied()
+        .ok_or(XpadError::NoInputInterface)
 }
 
 const XPAD_TABLE: &[UsbDeviceId] = &[
@@ -2523,9 +3137,11 @@ const XPAD_TABLE: &[UsbDeviceId] = &[
 
     // Elecom JC-U3613M (expanded safely)
     UsbDeviceId::xbox360_vendor(0x056e)[0],
+    UsbDeviceId::xbox360_vendor(0x056e)[1],
 
     // Saitek P3600 (expanded safely)
     UsbDeviceId::xbox360_vendor(0x06a3)[0],
+    UsbDeviceId::xbox360_vendor(0x06a3)[1],
 
     // Mad Catz Xbox 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x0738)[0],
@@ -2538,76 +3154,95 @@ const XPAD_TABLE: &[UsbDeviceId] = &[
 
     // Mad Catz Gamepad (expanded safely)
     UsbDeviceId::xbox360_vendor(0x07ff)[0],
+    UsbDeviceId::xbox360_vendor(0x07ff)[1],
 
     // ASUS controllers (expanded safely)
     UsbDeviceId::xboxone_vendor(0x0b05)[0],
 
     // Zeroplus X-Box 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x0c12)[0],
+    UsbDeviceId::xbox360_vendor(0x0c12)[1],
 
     // Micro Star International X-Box 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x0db0)[0],
+    UsbDeviceId::xbox360_vendor(0x0db0)[1],
 
     // 0x0e6f Xbox 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x0e6f)[0],
+    UsbDeviceId::xbox360_vendor(0x0e6f)[1],
 
     // 0x0e6f Xbox One controllers (expanded safely)
     UsbDeviceId::xboxone_vendor(0x0e6f)[0],
 
     // Hori controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x0f0d)[0],
+    UsbDeviceId::xbox360_vendor(0x0f0d)[1],
     UsbDeviceId::xboxone_vendor(0x0f0d)[0],
 
     // SteelSeries controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1038)[0],
+    UsbDeviceId::xbox360_vendor(0x1038)[1],
 
     // Turtle Beach Controllers (expanded safely)
     UsbDeviceId::xboxone_vendor(0x10f5)[0],
 
     // Nacon GC100XF (expanded safely)
     UsbDeviceId::xbox360_vendor(0x11c9)[0],
+    UsbDeviceId::xbox360_vendor(0x11c9)[1],
 
     // PXN V900 (expanded safely)
     UsbDeviceId::xbox360_vendor(0x11ff)[0],
+    UsbDeviceId::xbox360_vendor(0x11ff)[1],
 
     // Ardwiino Controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1209)[0],
+    UsbDeviceId::xbox360_vendor(0x1209)[1],
 
     // Xbox 360 dance pads (expanded safely)
     UsbDeviceId::xbox360_vendor(0x12ab)[0],
+    UsbDeviceId::xbox360_vendor(0x12ab)[1],
 
     // RedOctane Xbox 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1430)[0],
+    UsbDeviceId::xbox360_vendor(0x1430)[1],
 
     // RedOctane X-Box One controllers (expanded safely)
     UsbDeviceId::xboxone_vendor(0x1430)[0],
 
     // Bigben Interactive controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x146b)[0],
+    UsbDeviceId::xbox360_vendor(0x146b)[1],
 
     // Razer Sabertooth (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1532)[0],
+    UsbDeviceId::xbox360_vendor(0x1532)[1],
 
     // Razer Wildcat (expanded safely)
     UsbDeviceId::xboxone_vendor(0x1532)[0],
 
     // Numark Xbox 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x15e4)[0],
+    UsbDeviceId::xbox360_vendor(0x15e4)[1],
 
     // Joytech Xbox 360 controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x162e)[0],
+    UsbDeviceId::xbox360_vendor(0x162e)[1],
 
     // Razer Onza (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1689)[0],
+    UsbDeviceId::xbox360_vendor(0x1689)[1],
 
     // Lenovo (expanded safely)
     UsbDeviceId::xbox360_vendor(0x17ef)[0],
+    UsbDeviceId::xbox360_vendor(0x17ef)[1],
 
     // Amazon controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1949)[0],
+    UsbDeviceId::xbox360_vendor(0x1949)[1],
 
     // QH Electronics (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1a86)[0],
+    UsbDeviceId::xbox360_vendor(0x1a86)[1],
 
     // Harmonix Rock Band guitar and drums (expanded safely)
     UsbDeviceId::xbox360_vendor(0x1bad)[0],
@@ -2615,29 +3250,36 @@ const XPAD_TABLE: &[UsbDeviceId] = &[
 
     // PowerA controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x20d6)[0],
+    UsbDeviceId::xbox360_vendor(0x20d6)[1],
     UsbDeviceId::xboxone_vendor(0x20d6)[0],
 
     // Machenike Controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x2345)[0],
+    UsbDeviceId::xbox360_vendor(0x2345)[1],
 
     // PowerA controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x24c6)[0],
+    UsbDeviceId::xbox360_vendor(0x24c6)[1],
     UsbDeviceId::xboxone_vendor(0x24c6)[0],
 
     // OneXPlayer Gamepad (expanded safely)
     UsbDeviceId::xbox360_vendor(0x2563)[0],
+    UsbDeviceId::xbox360_vendor(0x2563)[1],
 
     // Dareu H101 (expanded safely)
     UsbDeviceId::xbox360_vendor(0x260d)[0],
+    UsbDeviceId::xbox360_vendor(0x260d)[1],
 
     // Snakebyte (expanded safely)
     UsbDeviceId::xboxone_vendor(0x294b)[0],
 
     // Qanba Controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x2c22)[0],
+    UsbDeviceId::xbox360_vendor(0x2c22)[1],
 
     // 8BitDo Pro 2 Wired Controller (expanded safely)
     UsbDeviceId::xbox360_vendor(0x2dc8)[0],
+    UsbDeviceId::xbox360_vendor(0x2dc8)[1],
 
     // 8BitDo Pro 2 Wired Controller for Xbox (expanded safely)
     UsbDeviceId::xboxone_vendor(0x2dc8)[0],
@@ -2650,145 +3292,3175 @@ const XPAD_TABLE: &[UsbDeviceId] = &[
 
     // Wooting Keyboards (expanded safely)
     UsbDeviceId::xbox360_vendor(0x31e3)[0],
+    UsbDeviceId::xbox360_vendor(0x31e3)[1],
 
     // Nacon GC-100 (expanded safely)
     UsbDeviceId::xbox360_vendor(0x3285)[0],
+    UsbDeviceId::xbox360_vendor(0x3285)[1],
 
     // Nacon Evol-X (expanded safely)
     UsbDeviceId::xboxone_vendor(0x3285)[0],
 
     // GameSir Controllers (expanded safely)
     UsbDeviceId::xbox360_vendor(0x3537)[0],
+    UsbDeviceId::xbox360_vendor(0x3537)[1],
     UsbDeviceId::xboxone_vendor(0x3537)[0],
 
     // Black Shark Green Ghost Controller (expanded safely)
     UsbDeviceId::xbox360_vendor(0x413d)[0],
+    UsbDeviceId::xbox360_vendor(0x413d)[1],
 ];
 
 
-// Improved initialization with error handling
-fn init_devices() -> kernel::Result {
-    for device in XPAD_DEVICES.values() {
-        kernel::pr_info!(
-            "Initializing {:04x}:{:04x} - {}",
-            device.id_vendor,
-            device.id_product,
-            device.name
-        );
-        
-        // Safe hardware access in unsafe block
-        unsafe {
-            send_control_transfer(device, INIT_PACKETS)?;
-        }
-    }
-    Ok(())
+// Devices from one vendor, in the order they have to be brought up
+struct InitGroup<'a> {
+    id_vendor: u16,
+    devices: Vec<&'a XpadDevice>,
 }
 
-// Enhanced packet processing with proper error handling
-fn process_packet(dev: &mut InputDev, cmd: u16, data: &[u8]) -> Result<(), kernel::Error> {
-    if data.len() < XPAD_PKT_LEN {
-        return Err(kernel::Error::EINVAL);
+// Wired pads, and with them the receivers' control interfaces, come up
+// before the wireless pads that depend on them
+fn init_rank(device: &XpadDevice) -> u8 {
+    match device.xtype {
+        XType::Xbox360W => 1,
+        _ => 0,
     }
+}
 
-    // Validate and process packet data
-    let buttons = data[2];
-    let triggers = (data[10], data[11]);
-    
-    // Process analog sticks
-    if !STICKS_TO_NULL.load(Ordering::Relaxed) {
-        let x = i16::from_le_bytes([data[12], data[13]]);
-        let y = i16::from_le_bytes([data[14], data[15]]);
-        input_report_abs(dev, ABS_X, x.into());
-        input_report_abs(dev, ABS_Y, (!y).into());
+// Group devices by vendor and order each group by rank, then product id
+fn init_order<'a>(devices: impl IntoIterator<Item = &'a XpadDevice>) -> Vec<InitGroup<'a>> {
+    let mut groups: BTreeMap<u16, Vec<&'a XpadDevice>> = BTreeMap::new();
+    for device in devices {
+        groups.entry(device.id_vendor).or_default().push(device);
     }
 
-    // Process triggers
-    if TRIGGERS_TO_BUTTONS.load(Ordering::Relaxed) {
-        input_report_key(dev, BTN_TL2, triggers.0 > 0);
-        input_report_key(dev, BTN_TR2, triggers.1 > 0);
-    } else {
-        input_report_abs(dev, ABS_Z, triggers.0.into());
-        input_report_abs(dev, ABS_RZ, triggers.1.into());
-    }
+    groups
+        .into_iter()
+        .map(|(id_vendor, mut devices)| {
+            devices.sort_by_key(|d| (init_rank(d), d.id_product));
+            InitGroup { id_vendor, devices }
+        })
+        .collect()
+}
 
-    // Process D-pad
-    if DPAD_TO_BUTTONS.load(Ordering::Relaxed) {
-        input_report_key(dev, BTN_TRIGGER_HAPPY1, buttons & 0x04 != 0);
-        input_report_key(dev, BTN_TRIGGER_HAPPY2, buttons & 0x08 != 0);
-        input_report_key(dev, BTN_TRIGGER_HAPPY3, buttons & 0x01 != 0);
-        input_report_key(dev, BTN_TRIGGER_HAPPY4, buttons & 0x02 != 0);
-    } else {
-        let hat_x = (buttons & 0x04 != 0) as i32 - (buttons & 0x08 != 0) as i32;
-        let hat_y = (buttons & 0x01 != 0) as i32 - (buttons & 0x02 != 0) as i32;
-        input_report_abs(dev, ABS_HAT0X, hat_x);
-        input_report_abs(dev, ABS_HAT0Y, hat_y);
-    }
+// Which group, and which device in it, failed to initialize
+#[derive(Debug)]
+struct InitGroupError {
+    id_vendor: u16,
+    id_product: u16,
+    error: kernel::Error,
+}
 
-    input_sync(dev);
-    Ok(())
+// One control transfer of the init sequence
+type InitPacket = &'static [u8];
+
+// Where the init sequence's control transfers go
+trait InitTransport {
+    // # Safety
+    //
+    // Every packet has to be 1..=XPAD_PKT_LEN bytes long, see
+    // send_init_transfer_via()
+    unsafe fn send(&self, device: &XpadDevice, packets: &[InitPacket]) -> Result<(), kernel::Error>;
 }
 
-/*
- * xpad360w_process_packet
- *
- * Completes a request by converting the data into events for the
- * input subsystem. It is version for xbox 360 wireless controller.
- *
- * Byte.Bit
- * 00.1 - Status change: The controller or headset has connected/disconnected
- *                       Bits 01.7 and 01.6 are valid
- * 01.7 - Controller present
- * 01.6 - Headset present
- * 01.1 - Pad state (Bytes 4+) valid
- *
- */
+// The default control pipe
+struct ControlPipe;
 
-struct XpadDriver {
-    udev: usb::Device,
-    interface: usb::Interface,
-    input_dev: input::Device,
-    pad_present: AtomicBool,
+impl InitTransport for ControlPipe {
+    unsafe fn send(&self, device: &XpadDevice, packets: &[InitPacket]) -> Result<(), kernel::Error> {
+        send_control_transfer(device, packets)
+    }
 }
 
-impl usb::Driver for XpadDriver {
-    fn probe(
-        udev: &usb::Device,
-        interface: &usb::Interface,
-        id_info: &usb::IdInfo,
-    ) -> Result<Self> {
-        let input_dev = input::Device::new()?;
-        
-        // Setup input device capabilities based on controller type
-        input_dev.set_evbit(input::EventType::Key)?;
-        input_dev.set_evbit(input::EventType::Abs)?;
-        
-        // Register device
-        input_dev.register("xpad")?;
+// Init transport that keeps every packet instead of sending it
+#[derive(Default)]
+struct RecordingInit {
+    packets: Mutex<Vec<Vec<u8>>>,
+    failures: Mutex<VecDeque<kernel::Error>>,
+}
 
-        Ok(Self {
-            udev: udev.clone(),
-            interface: interface.clone(),
-            input_dev,
-            pad_present: AtomicBool::new(false),
-        })
+impl RecordingInit {
+    // Drain the packets sent so far
+    fn take(&self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut *self.packets.lock().unwrap())
     }
 
-    fn disconnected(&self) {
-        self.input_dev.unregister();
+    // Fail the next send with `err` instead of recording its packets
+    fn fail_next(&self, err: kernel::Error) {
+        self.failures.lock().unwrap().push_back(err);
     }
 }
 
-// Shared state structure
-struct UsbXpad {
-    xtype: XType,
-    dev: Arc<InputDevice>,
+impl InitTransport for RecordingInit {
+    unsafe fn send(&self, _device: &XpadDevice, packets: &[InitPacket]) -> Result<(), kernel::Error> {
+        if let Some(err) = self.failures.lock().unwrap().pop_front() {
+            return Err(err);
+        }
+        self.packets.lock().unwrap().extend(packets.iter().map(|packet| packet.to_vec()));
+        Ok(())
+    }
+}
+
+// Send the init sequence to `device`, one control transfer per packet
+fn send_init_transfer(device: &XpadDevice, packets: &[InitPacket]) -> Result<(), kernel::Error> {
+    send_init_transfer_via(&ControlPipe, device, packets)
+}
+
+// send_control_transfer() copies each buffer into a DMA-able transfer
+// buffer without checking its length and reads it after the call has
+// returned to us, so every packet has to be non-empty, fit in
+// XPAD_PKT_LEN and outlive the transfer. The 'static InitPacket covers
+// the last point, the lengths are checked here before anything is sent.
+fn send_init_transfer_via(
+    transport: &dyn InitTransport,
+    device: &XpadDevice,
+    packets: &[InitPacket],
+) -> Result<(), kernel::Error> {
+    if packets.iter().any(|packet| packet.is_empty() || packet.len() > XPAD_PKT_LEN) {
+        log::warn!("{:04x}:{:04x}: init packet with a bad length, not sent",
+                   device.id_vendor, device.id_product);
+        return Err(kernel::Error::EINVAL);
+    }
+
+    // SAFETY: every packet is 'static and 1..=XPAD_PKT_LEN bytes long,
+    // checked above, which is all send_control_transfer() relies on
+    unsafe { transport.send(device, packets) }
+}
+
+// Improved initialization with error handling
+fn init_devices() -> Result<(), InitGroupError> {
+    for group in init_order(XPAD_DEVICES.values()) {
+        for device in group.devices {
+            kernel::pr_info!(
+                "Initializing {:04x}:{:04x} - {}",
+                device.id_vendor,
+                device.id_product,
+                device.name
+            );
+
+            send_init_transfer(device, INIT_PACKETS).map_err(|error| InitGroupError {
+                id_vendor: group.id_vendor,
+                id_product: device.id_product,
+                error,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+// Minimum length of an input report, below it the report is rejected
+fn min_report_len(xtype: XType, quirks: QuirkFlags) -> usize {
+    match xtype {
+        XType::Xbox360 | XType::Xbox360W if quirks.contains(QuirkFlags::SHORT_REPORTS) => {
+            XPAD360_SHORT_REPORT_LEN
+        },
+        XType::Xbox360 | XType::Xbox360W => XPAD360_REPORT_LEN,
+        XType::XboxOne => XPADONE_REPORT_LEN,
+        XType::Xbox | XType::Unknown => XPAD_REPORT_LEN,
+    }
+}
+
+// Flip a stick axis so up is negative, as evdev expects. Bitwise not, as
+// Linux xpad does (~raw), so the full range maps onto itself and a centred
+// stick reads -1 rather than 0.
+fn invert_axis(v: i16) -> i16 {
+    !v
+}
+
+// Exponential moving average for one axis, alpha 1.0 passes values through
+#[derive(Debug, Clone, Copy)]
+struct AxisSmoothing {
+    alpha: f32,
+    state: Option<f32>,
+}
+
+impl AxisSmoothing {
+    fn apply(&mut self, value: i32) -> i32 {
+        let smoothed = match self.state {
+            Some(prev) => self.alpha * value as f32 + (1.0 - self.alpha) * prev,
+            None => value as f32,
+        };
+        self.state = Some(smoothed);
+        smoothed.round() as i32
+    }
+}
+
+// Every decoder reports axes through here so nothing outside the range
+// advertised by abs_range() reaches the input layer
+fn report_abs_clamped(xpad: &UsbXpad, dev: &dyn InputSink, axis: AbsoluteAxis, value: i32) {
+    let (min, max) = abs_range(xpad, axis);
+    let clamped = value.clamp(min, max);
+    if clamped != value {
+        xpad_debug!("{:?} value {} clamped to {}", axis, value, clamped);
+    }
+    dev.report_abs(axis, clamped);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    fn axes(self) -> (AbsoluteAxis, AbsoluteAxis) {
+        match self {
+            Stick::Left => (AbsoluteAxis::X, AbsoluteAxis::Y),
+            Stick::Right => (AbsoluteAxis::Rx, AbsoluteAxis::Ry),
+        }
+    }
+}
+
+// Response curve applied to a stick's deflection. expo 0 is linear, higher
+// values flatten the center for finer aim and catch up towards the edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct StickCurve {
+    expo: f32,
+}
+
+impl StickCurve {
+    // Radial: the distance m from center, as a fraction of full deflection,
+    // becomes (1 - expo) * m + expo * m^3, and both axes are scaled by the
+    // same factor so the direction is kept. The curve maps 1 to 1, so
+    // anything at or past full deflection (the corners of a square gate) is
+    // left alone, as is everything at expo 0. An expo outside 0..=1 is
+    // clamped, a NaN one counts as 0.
+    fn apply(self, (x, y): (i32, i32)) -> (i32, i32) {
+        let expo = if self.expo.is_nan() { 0.0 } else { self.expo.clamp(0.0, 1.0) };
+        let magnitude = (x as f32).hypot(y as f32) / 32767.0;
+        if expo == 0.0 || magnitude == 0.0 || magnitude >= 1.0 {
+            return (x, y);
+        }
+
+        let scale = (1.0 - expo) + expo * magnitude * magnitude;
+        ((x as f32 * scale).round() as i32, (y as f32 * scale).round() as i32)
+    }
+}
+
+// Every stick report goes through here, so stick processing is applied the
+// same way on all protocols: deadzone, response curve, then per-axis
+// smoothing. Y values are expected already inverted.
+fn report_stick(xpad: &UsbXpad, dev: &dyn InputSink, stick: Stick, (x, y): (i32, i32)) {
+    let deadzone = i32::from(xpad.stick_deadzone.load(Ordering::Relaxed));
+    let dead = |value: i32| if value.abs() < deadzone { 0 } else { value };
+    let curve = xpad.stick_curves.lock().unwrap()[stick as usize];
+    let (x, y) = curve.apply((dead(x), dead(y)));
+
+    let (x_axis, y_axis) = stick.axes();
+    report_stick_axis(xpad, dev, x_axis, x);
+    report_stick_axis(xpad, dev, y_axis, y);
+}
+
+fn report_stick_axis(xpad: &UsbXpad, dev: &dyn InputSink, axis: AbsoluteAxis, value: i32) {
+    let value = match xpad.smoothing.lock().unwrap().get_mut(&axis) {
+        Some(filter) => filter.apply(value),
+        None => value,
+    };
+    report_abs_clamped(xpad, &*dev, axis, value);
+}
+
+// Default deflection, of 32767, past which STICK_TO_DPAD presses a direction
+const DEFAULT_STICK_DPAD_THRESHOLD: u16 = 16384;
+
+// Direction of the left stick as a hat value, (0, 0) inside the threshold.
+// The circle is cut into eight 45 degree sectors so diagonals need a real
+// diagonal push, not just a little drift on the other axis.
+fn stick_to_hat(xpad: &UsbXpad, (x, y): (i16, i16)) -> (i32, i32) {
+    let threshold = f32::from(xpad.stick_dpad_threshold.load(Ordering::Relaxed));
+    // Raw stick Y is positive up, hat Y is positive down
+    let (x, y) = (f32::from(x), -f32::from(y));
+    if x.hypot(y) < threshold {
+        return (0, 0);
+    }
+
+    let sector = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+    match sector.rem_euclid(8) {
+        0 => (1, 0),
+        1 => (1, 1),
+        2 => (0, 1),
+        3 => (-1, 1),
+        4 => (-1, 0),
+        5 => (-1, -1),
+        6 => (0, -1),
+        _ => (1, -1),
+    }
+}
+
+/// How simultaneous opposite cardinal directions on the d-pad are resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SocdMode {
+    /// Left+right and up+down are both neutral
+    Neutral,
+    /// The direction pressed last wins
+    LastWins,
+    /// Up+down is up, left+right is neutral
+    UpPriority,
+}
+
+#[derive(Default)]
+struct SocdState {
+    mode: Option<SocdMode>,
+    // d-pad bits and resolved hat from the previous report, for LastWins
+    bits: u8,
+    hat: (i32, i32),
+}
+
+// Resolve one hat axis. `held` and `was_held` are the (negative, positive)
+// directions now and in the previous report, `last` the previous result.
+fn socd_axis(mode: SocdMode, held: (bool, bool), was_held: (bool, bool), last: i32, vertical: bool) -> i32 {
+    if !(held.0 && held.1) {
+        return held.1 as i32 - held.0 as i32;
+    }
+
+    match mode {
+        SocdMode::Neutral => 0,
+        // Up is the negative hat direction
+        SocdMode::UpPriority => if vertical { -1 } else { 0 },
+        SocdMode::LastWins => match was_held {
+            (true, false) => 1,
+            (false, true) => -1,
+            (true, true) => last,
+            (false, false) => 0,
+        },
+    }
+}
+
+// Report the d-pad from its button bits (0x01 up, 0x02 down, 0x04 left,
+// 0x08 right), same layout on every protocol. With STICK_TO_DPAD the left
+// stick also drives it whenever the real d-pad is released.
+fn report_dpad(xpad: &UsbXpad, dev: &dyn InputSink, bits: u8, left_stick: (i16, i16)) {
+    let axes = |bits: u8| (
+        (bits & 0x04 != 0, bits & 0x08 != 0),
+        (bits & 0x01 != 0, bits & 0x02 != 0),
+    );
+    let (x, y) = axes(bits);
+    let mut hat = (x.1 as i32 - x.0 as i32, y.1 as i32 - y.0 as i32);
+
+    let mut socd = xpad.socd.lock().unwrap();
+    if let Some(mode) = socd.mode {
+        let (was_x, was_y) = axes(socd.bits);
+        hat = (
+            socd_axis(mode, x, was_x, socd.hat.0, false),
+            socd_axis(mode, y, was_y, socd.hat.1, true),
+        );
+        socd.bits = bits;
+        socd.hat = hat;
+    }
+    drop(socd);
+
+    if hat == (0, 0) && xpad.mapping().contains(MapFlags::STICK_TO_DPAD) {
+        hat = stick_to_hat(xpad, left_stick);
+    }
+
+    if xpad.mapping().contains(MapFlags::DPAD_TO_BUTTONS) {
+        dev.report_key(Button::TriggerHappy1, hat.0 < 0);
+        dev.report_key(Button::TriggerHappy2, hat.0 > 0);
+        dev.report_key(Button::TriggerHappy3, hat.1 < 0);
+        dev.report_key(Button::TriggerHappy4, hat.1 > 0);
+    } else {
+        report_abs_clamped(xpad, &*dev, AbsoluteAxis::Hat0X, hat.0);
+        report_abs_clamped(xpad, &*dev, AbsoluteAxis::Hat0Y, hat.1);
+    }
+}
+
+// Enhanced packet processing with proper error handling.
+// Decodes the 360 report layout, shared by wired pads and wireless receivers.
+fn process_packet(xpad: &UsbXpad, data: &[u8]) -> Result<(), kernel::Error> {
+    if data.len() < min_report_len(xpad.protocol(), xpad.quirks) {
+        return Err(kernel::Error::EINVAL);
+    }
+
+    let dev = xpad.dev.clone();
+
+    // Validate and process packet data
+    let digital = data[2];
+    let buttons = data[3];
+    let triggers = (data[4], data[5]);
+
+    // Start/Back and stick clicks
+    dev.report_key(Button::Start, digital & 0x10 != 0);
+    dev.report_key(Button::Select, digital & 0x20 != 0);
+    dev.report_key(Button::ThumbL, digital & 0x40 != 0);
+    dev.report_key(Button::ThumbR, digital & 0x80 != 0);
+
+    // Bumpers, guide and face buttons
+    dev.report_key(Button::TL, buttons & 0x01 != 0);
+    dev.report_key(Button::TR, buttons & 0x02 != 0);
+    report_guide(xpad, &*dev, buttons & 0x04 != 0);
+    dev.report_key(Button::A, buttons & 0x10 != 0);
+    dev.report_key(Button::B, buttons & 0x20 != 0);
+    dev.report_key(Button::X, buttons & 0x40 != 0);
+    dev.report_key(Button::Y, buttons & 0x80 != 0);
+    
+    // Process analog sticks
+    if xpad.mapping().uses_analog_sticks() {
+        let x = i16::from_le_bytes([data[6], data[7]]);
+        let y = i16::from_le_bytes([data[8], data[9]]);
+        report_stick(xpad, &*dev, Stick::Left, (x.into(), invert_axis(y).into()));
+
+        // Short-report clones stop after the left stick
+        if data.len() >= XPAD360_REPORT_LEN {
+            let rx = i16::from_le_bytes([data[10], data[11]]);
+            let ry = i16::from_le_bytes([data[12], data[13]]);
+            report_stick(xpad, &*dev, Stick::Right, (rx.into(), invert_axis(ry).into()));
+        }
+    }
+
+    // Process triggers, independent of STICKS_TO_NULL
+    report_triggers(xpad, &*dev, (triggers.0.into(), triggers.1.into()), 8);
+
+    // Process D-pad
+    let left_stick = (i16::from_le_bytes([data[6], data[7]]), i16::from_le_bytes([data[8], data[9]]));
+    report_dpad(xpad, &*dev, digital, left_stick);
+
+    dev.synchronize();
+    Ok(())
+}
+
+// Byte positions in an original Xbox report. The face and black/white
+// buttons are analog, one byte each; the rest are bits in `digital`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct XboxLayout {
+    // D-pad, Start/Back and the stick clicks
+    digital: usize,
+    a: usize,
+    b: usize,
+    x: usize,
+    y: usize,
+    black: usize,
+    white: usize,
+    triggers: usize,
+    sticks: usize,
+    stick_width: StickWidth,
+}
+
+// Resolution of the stick fields. They are always 16 bits wide, but some
+// third-party pads only fill the low byte with a signed 8-bit value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StickWidth {
+    Bits8,
+    Bits16,
+}
+
+impl StickWidth {
+    // Stick value scaled to the full 16-bit range
+    fn scale(self, raw: i16) -> i16 {
+        match self {
+            StickWidth::Bits16 => raw,
+            StickWidth::Bits8 => (i32::from(raw as i8) * 32767 / 127).clamp(-32768, 32767) as i16,
+        }
+    }
+}
+
+// Every original Xbox pad. Microsoft's Duke and Controller S send the same
+// report as third-party pads, and no capture shows one that differs.
+const XBOX_LAYOUT_COMMON: XboxLayout = XboxLayout {
+    digital: 2,
+    a: 4, b: 5, x: 6, y: 7,
+    black: 8, white: 9,
+    triggers: 10,
+    sticks: 12,
+    stick_width: StickWidth::Bits16,
+};
+
+// Original Xbox packet processing
+fn xpad_process_packet(xpad: &UsbXpad, data: &[u8]) {
+    if data.len() < min_report_len(XType::Xbox, xpad.quirks) {
+        xpad_debug!("Dropped short report: {} bytes", data.len());
+        return;
+    }
+
+    let dev = xpad.dev.clone();
+    let layout = &XBOX_LAYOUT_COMMON;
+    let digital = data[layout.digital];
+
+    // Sticks
+    let stick = |offset: usize| {
+        let raw = i16::from_le_bytes([data[layout.sticks + offset], data[layout.sticks + offset + 1]]);
+        layout.stick_width.scale(raw)
+    };
+    if xpad.mapping().uses_analog_sticks() {
+        report_stick(xpad, &*dev, Stick::Left, (stick(0).into(), invert_axis(stick(2)).into()));
+        report_stick(xpad, &*dev, Stick::Right, (stick(4).into(), invert_axis(stick(6)).into()));
+    }
+
+    // Triggers
+    let triggers = (data[layout.triggers], data[layout.triggers + 1]);
+    report_triggers(xpad, &*dev, (triggers.0.into(), triggers.1.into()), 8);
+
+    // D-pad
+    report_dpad(xpad, &*dev, digital, (stick(0), stick(2)));
+
+    // Start/Back and stick clicks
+    dev.report_key(Button::Start, digital & 0x10 != 0);
+    dev.report_key(Button::Select, digital & 0x20 != 0);
+    dev.report_key(Button::ThumbL, digital & 0x40 != 0);
+    dev.report_key(Button::ThumbR, digital & 0x80 != 0);
+
+    // Analog face buttons count as pressed at any travel
+    dev.report_key(Button::A, data[layout.a] != 0);
+    dev.report_key(Button::B, data[layout.b] != 0);
+    dev.report_key(Button::X, data[layout.x] != 0);
+    dev.report_key(Button::Y, data[layout.y] != 0);
+
+    // Black and white
+    dev.report_key(Button::C, data[layout.black] != 0);
+    dev.report_key(Button::Z, data[layout.white] != 0);
+
+    dev.synchronize();
+}
+
+/*
+ * xpad360w_process_packet
+ *
+ * Completes a request by converting the data into events for the
+ * input subsystem. It is version for xbox 360 wireless controller.
+ *
+ * Byte.Bit
+ * 00.1 - Status change: The controller or headset has connected/disconnected
+ *                       Bits 01.7 and 01.6 are valid
+ * 01.7 - Controller present
+ * 01.6 - Headset present
+ * 01.1 - Pad state (Bytes 4+) valid
+ *
+ */
+
+struct XpadDriver {
+    udev: usb::Device,
+    interface: usb::Interface,
+    input_dev: input::Device,
     pad_present: AtomicBool,
-    irq_out_active: AtomicBool,
-    odata: Mutex<Vec<u8>>,
-    init_seq: Mutex<usize>,
-    mapping: MapFlags,
-    packet_type: PacketType,
-    quirks: QuirkFlags,
+}
+
+impl usb::Driver for XpadDriver {
+    fn probe(
+        udev: &usb::Device,
+        interface: &usb::Interface,
+        id_info: &usb::IdInfo,
+    ) -> Result<Self> {
+        let input_dev = input::Device::new()?;
+        
+        // Setup input device capabilities based on controller type
+        input_dev.set_evbit(input::EventType::Key)?;
+        input_dev.set_evbit(input::EventType::Abs)?;
+        
+        // Register device
+        input_dev.register("xpad")?;
+
+        Ok(Self {
+            udev: udev.clone(),
+            interface: interface.clone(),
+            input_dev,
+            pad_present: AtomicBool::new(false),
+        })
+    }
+
+    fn disconnected(&self) {
+        self.input_dev.unregister();
+    }
+}
+
+// Ends a decoded report with exactly one SYN_REPORT if anything was
+// reported since the last sync, and none if nothing was, so decoders
+// don't have to track which of their paths emitted events
+struct FrameSink {
+    inner: Arc<dyn InputSink>,
+    pending: AtomicBool,
+}
+
+impl FrameSink {
+    fn new(inner: Arc<dyn InputSink>) -> Self {
+        Self { inner, pending: AtomicBool::new(false) }
+    }
+
+    fn finish(&self) {
+        if self.pending.load(Ordering::Relaxed) {
+            self.synchronize();
+        }
+    }
+}
+
+impl InputSink for FrameSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.pending.store(true, Ordering::Relaxed);
+        self.inner.report_key(button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.pending.store(true, Ordering::Relaxed);
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        self.pending.store(false, Ordering::Relaxed);
+        self.inner.synchronize();
+    }
+
+    fn report_scan(&self, scancode: u32) {
+        self.inner.report_scan(scancode);
+    }
+}
+
+// Where decoders send their events, normally the registered input device
+trait InputSink: Send + Sync {
+    fn report_key(&self, button: Button, pressed: bool);
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32);
+    fn synchronize(&self);
+    // MSC_SCAN, only emitted by ScanSink so most sinks can ignore it
+    fn report_scan(&self, _scancode: u32) {}
+}
+
+impl InputSink for InputDevice {
+    fn report_key(&self, button: Button, pressed: bool) {
+        InputDevice::report_key(self, button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        InputDevice::report_abs(self, axis, value);
+    }
+
+    fn synchronize(&self) {
+        InputDevice::synchronize(self);
+    }
+
+    fn report_scan(&self, scancode: u32) {
+        InputDevice::report_misc(self, MiscKind::Scancode, scancode as i32);
+    }
+}
+
+// A single event as emitted by a decoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SinkEvent {
+    Key(Button, bool),
+    Abs(AbsoluteAxis, i32),
+    Scan(u32),
+    Sync,
+}
+
+// Sink that keeps every event in emission order instead of forwarding it,
+// so decoder output can be inspected without an input device
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<SinkEvent>>,
+}
+
+impl RecordingSink {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    // Drain the events recorded so far
+    fn take(&self) -> Vec<SinkEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl InputSink for RecordingSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.events.lock().unwrap().push(SinkEvent::Key(button, pressed));
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.events.lock().unwrap().push(SinkEvent::Abs(axis, value));
+    }
+
+    fn synchronize(&self) {
+        self.events.lock().unwrap().push(SinkEvent::Sync);
+    }
+
+    fn report_scan(&self, scancode: u32) {
+        self.events.lock().unwrap().push(SinkEvent::Scan(scancode));
+    }
+}
+
+// Time source for everything timer-driven, monotonic since an arbitrary origin
+trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+// Clock that only moves when told to
+#[derive(Default)]
+struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+// Drops button transitions that follow the previous accepted one within
+// DEBOUNCE_WINDOW_MS, for pads whose switches bounce. Axes pass through.
+struct DebounceSink {
+    inner: Arc<dyn InputSink>,
+    clock: Arc<dyn Clock>,
+    last: Mutex<HashMap<Button, (bool, Duration)>>,
+}
+
+impl DebounceSink {
+    fn new(inner: Arc<dyn InputSink>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner,
+            clock,
+            last: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl InputSink for DebounceSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        let now = self.clock.now();
+        let window = Duration::from_millis(DEBOUNCE_WINDOW_MS.load(Ordering::Relaxed).into());
+        let mut last = self.last.lock().unwrap();
+
+        match last.get(&button) {
+            Some(&(state, _)) if state == pressed => (),
+            Some(&(_, at)) if now.saturating_sub(at) < window => return,
+            _ => {
+                last.insert(button, (pressed, now));
+            },
+        }
+        drop(last);
+
+        self.inner.report_key(button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        self.inner.synchronize();
+    }
+}
+
+// Per-pad button substitutions, buttons not listed report as themselves
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ButtonRemap {
+    map: HashMap<Button, Button>,
+}
+
+impl ButtonRemap {
+    // Report `from` as `to`
+    fn set(&mut self, from: Button, to: Button) {
+        if from == to {
+            self.map.remove(&from);
+        } else {
+            self.map.insert(from, to);
+        }
+    }
+
+    fn swap(&mut self, a: Button, b: Button) {
+        self.set(a, b);
+        self.set(b, a);
+    }
+
+    fn get(&self, button: Button) -> Button {
+        self.map.get(&button).copied().unwrap_or(button)
+    }
+}
+
+// Applies the pad's ButtonRemap. When several physical buttons end up on
+// the same button it is held while any of them is, so releasing one of
+// them doesn't release a button another still holds. With MODE_AS_CHORD
+// the guide button goes to Start and Select both, merged the same way
+// with the real Start and Select.
+struct RemapSink {
+    inner: Arc<dyn InputSink>,
+    remap: Arc<Mutex<ButtonRemap>>,
+    mode_chord: Arc<AtomicBool>,
+    // Last state of each physical button
+    physical: Mutex<HashMap<Button, bool>>,
+}
+
+impl RemapSink {
+    fn new(inner: Arc<dyn InputSink>, remap: Arc<Mutex<ButtonRemap>>, mode_chord: Arc<AtomicBool>) -> Self {
+        Self {
+            inner,
+            remap,
+            mode_chord,
+            physical: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn targets(&self, remap: &ButtonRemap, button: Button) -> Vec<Button> {
+        if button == Button::Mode && self.mode_chord.load(Ordering::Relaxed) {
+            vec![Button::Start, Button::Select]
+        } else {
+            vec![remap.get(button)]
+        }
+    }
+}
+
+impl InputSink for RemapSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        let remap = self.remap.lock().unwrap();
+        let mut physical = self.physical.lock().unwrap();
+        physical.insert(button, pressed);
+
+        let mut reports = vec![];
+        for target in self.targets(&remap, button) {
+            let held = physical
+                .iter()
+                .any(|(&source, &down)| down && self.targets(&remap, source).contains(&target));
+            reports.push((target, held));
+        }
+        drop(physical);
+        drop(remap);
+
+        for (target, held) in reports {
+            self.inner.report_key(target, held);
+        }
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        self.inner.synchronize();
+    }
+}
+
+// Logs one human-readable line per frame with what the decoder produced.
+// Only built with the trace-input feature.
+#[cfg(feature = "trace-input")]
+struct TraceSink {
+    inner: Arc<dyn InputSink>,
+    frame: Mutex<Vec<SinkEvent>>,
+}
+
+#[cfg(feature = "trace-input")]
+impl TraceSink {
+    fn new(inner: Arc<dyn InputSink>) -> Self {
+        Self {
+            inner,
+            frame: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+// "pressed: A B TL | X=-1200 Y=300 Z=255", later reports of the same axis win
+#[cfg(feature = "trace-input")]
+fn summarize_frame(events: &[SinkEvent]) -> String {
+    let mut pressed = Vec::new();
+    let mut axes: Vec<(AbsoluteAxis, i32)> = Vec::new();
+
+    for event in events {
+        match *event {
+            SinkEvent::Key(button, true) => pressed.push(format!("{:?}", button)),
+            SinkEvent::Abs(axis, value) => match axes.iter_mut().find(|(a, _)| *a == axis) {
+                Some(entry) => entry.1 = value,
+                None => axes.push((axis, value)),
+            },
+            _ => (),
+        }
+    }
+
+    let axes: Vec<String> = axes.iter().map(|(axis, value)| format!("{:?}={}", axis, value)).collect();
+    format!("pressed: {} | {}", pressed.join(" "), axes.join(" "))
+}
+
+#[cfg(feature = "trace-input")]
+impl InputSink for TraceSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.frame.lock().unwrap().push(SinkEvent::Key(button, pressed));
+        self.inner.report_key(button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.frame.lock().unwrap().push(SinkEvent::Abs(axis, value));
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        let frame = std::mem::take(&mut *self.frame.lock().unwrap());
+        xpad_debug!("Decoded input: {}", summarize_frame(&frame));
+        self.inner.synchronize();
+    }
+}
+
+bitflags::bitflags! {
+    /// Digital controls, one bit each regardless of protocol
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ButtonSet: u32 {
+        const A          = 1 << 0;
+        const B          = 1 << 1;
+        const X          = 1 << 2;
+        const Y          = 1 << 3;
+        const C          = 1 << 4;
+        const Z          = 1 << 5;
+        const TL         = 1 << 6;
+        const TR         = 1 << 7;
+        const TL2        = 1 << 8;
+        const TR2        = 1 << 9;
+        const SELECT     = 1 << 10;
+        const START      = 1 << 11;
+        const MODE       = 1 << 12;
+        const THUMBL     = 1 << 13;
+        const THUMBR     = 1 << 14;
+        const DPAD_LEFT  = 1 << 15;
+        const DPAD_RIGHT = 1 << 16;
+        const DPAD_UP    = 1 << 17;
+        const DPAD_DOWN  = 1 << 18;
+        const PADDLE1    = 1 << 19;
+        const PADDLE2    = 1 << 20;
+        const PADDLE3    = 1 << 21;
+        const PADDLE4    = 1 << 22;
+        const SHARE      = 1 << 23;
+    }
+}
+
+impl ButtonSet {
+    // Bit for a reported button, empty for buttons outside the gamepad set
+    fn from_button(button: Button) -> Self {
+        match button {
+            Button::A => Self::A,
+            Button::B => Self::B,
+            Button::X => Self::X,
+            Button::Y => Self::Y,
+            Button::C => Self::C,
+            Button::Z => Self::Z,
+            Button::TL => Self::TL,
+            Button::TR => Self::TR,
+            Button::TL2 => Self::TL2,
+            Button::TR2 => Self::TR2,
+            Button::Select => Self::SELECT,
+            Button::Start => Self::START,
+            Button::Mode => Self::MODE,
+            Button::ThumbL => Self::THUMBL,
+            Button::ThumbR => Self::THUMBR,
+            Button::TriggerHappy1 => Self::DPAD_LEFT,
+            Button::TriggerHappy2 => Self::DPAD_RIGHT,
+            Button::TriggerHappy3 => Self::DPAD_UP,
+            Button::TriggerHappy4 => Self::DPAD_DOWN,
+            Button::TriggerHappy5 => Self::PADDLE1,
+            Button::TriggerHappy6 => Self::PADDLE2,
+            Button::TriggerHappy7 => Self::PADDLE3,
+            Button::TriggerHappy8 => Self::PADDLE4,
+            Button::Record => Self::SHARE,
+            _ => Self::empty(),
+        }
+    }
+}
+
+// Snapshot of every control, for consumers that poll instead of reading events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ControllerState {
+    buttons: ButtonSet,
+    left_stick: (i32, i32),
+    right_stick: (i32, i32),
+    triggers: (i32, i32),
+    // -1/0/1 per axis, from the hat or the d-pad buttons
+    dpad: (i32, i32),
+    paddles: [bool; 4],
+}
+
+impl ControllerState {
+    fn apply(&mut self, event: SinkEvent) {
+        match event {
+            SinkEvent::Key(button, pressed) => {
+                self.buttons.set(ButtonSet::from_button(button), pressed);
+
+                if matches!(button, Button::TriggerHappy1 | Button::TriggerHappy2
+                                    | Button::TriggerHappy3 | Button::TriggerHappy4) {
+                    let dpad = self.buttons;
+                    self.dpad = (
+                        dpad.contains(ButtonSet::DPAD_RIGHT) as i32 - dpad.contains(ButtonSet::DPAD_LEFT) as i32,
+                        dpad.contains(ButtonSet::DPAD_DOWN) as i32 - dpad.contains(ButtonSet::DPAD_UP) as i32,
+                    );
+                }
+
+                let paddle = match button {
+                    Button::TriggerHappy5 => Some(0),
+                    Button::TriggerHappy6 => Some(1),
+                    Button::TriggerHappy7 => Some(2),
+                    Button::TriggerHappy8 => Some(3),
+                    _ => None,
+                };
+                if let Some(idx) = paddle {
+                    self.paddles[idx] = pressed;
+                }
+            },
+            SinkEvent::Abs(axis, value) => match axis {
+                AbsoluteAxis::X => self.left_stick.0 = value,
+                AbsoluteAxis::Y => self.left_stick.1 = value,
+                AbsoluteAxis::Rx => self.right_stick.0 = value,
+                AbsoluteAxis::Ry => self.right_stick.1 = value,
+                AbsoluteAxis::Z => self.triggers.0 = value,
+                AbsoluteAxis::Rz => self.triggers.1 = value,
+                AbsoluteAxis::Hat0X => self.dpad.0 = value,
+                AbsoluteAxis::Hat0Y => self.dpad.1 = value,
+                _ => (),
+            },
+            SinkEvent::Scan(_) | SinkEvent::Sync => (),
+        }
+    }
+}
+
+// Keeps a ControllerState up to date with everything flowing to the device
+struct StateSink {
+    inner: Arc<dyn InputSink>,
+    state: Arc<Mutex<ControllerState>>,
+}
+
+impl StateSink {
+    fn new(inner: Arc<dyn InputSink>, state: Arc<Mutex<ControllerState>>) -> Self {
+        Self { inner, state }
+    }
+}
+
+impl InputSink for StateSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.state.lock().unwrap().apply(SinkEvent::Key(button, pressed));
+        self.inner.report_key(button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.state.lock().unwrap().apply(SinkEvent::Abs(axis, value));
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        self.inner.synchronize();
+    }
+}
+
+// Precedes every button event with MSC_SCAN for remappers that key off
+// scancodes. The scancode is the button's evdev code, so it stays stable
+// whatever the protocol or the mapping.
+struct ScanSink {
+    inner: Arc<dyn InputSink>,
+    enabled: Arc<AtomicBool>,
+}
+
+impl ScanSink {
+    fn new(inner: Arc<dyn InputSink>, enabled: Arc<AtomicBool>) -> Self {
+        Self { inner, enabled }
+    }
+}
+
+impl InputSink for ScanSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        if let Some(code) = button.code().filter(|_| self.enabled.load(Ordering::Relaxed)) {
+            self.inner.report_scan(u32::from(code));
+        }
+        self.inner.report_key(button, pressed);
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.inner.report_abs(axis, value);
+    }
+
+    fn synchronize(&self) {
+        self.inner.synchronize();
+    }
+}
+
+// Innermost link of the chain: passes everything on to the device and
+// copies it to the tap, if one is set. Whatever the tap sees is exactly
+// what the device got, after remapping, chords, scancodes and debouncing.
+struct TapSink {
+    inner: Arc<dyn InputSink>,
+    tap: Arc<Mutex<Option<Arc<dyn InputSink>>>>,
+}
+
+impl TapSink {
+    fn new(inner: Arc<dyn InputSink>, tap: Arc<Mutex<Option<Arc<dyn InputSink>>>>) -> Self {
+        Self { inner, tap }
+    }
+
+    fn tap(&self) -> Option<Arc<dyn InputSink>> {
+        self.tap.lock().unwrap().clone()
+    }
+}
+
+impl InputSink for TapSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.inner.report_key(button, pressed);
+        if let Some(tap) = self.tap() {
+            tap.report_key(button, pressed);
+        }
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.inner.report_abs(axis, value);
+        if let Some(tap) = self.tap() {
+            tap.report_abs(axis, value);
+        }
+    }
+
+    fn synchronize(&self) {
+        self.inner.synchronize();
+        if let Some(tap) = self.tap() {
+            tap.synchronize();
+        }
+    }
+
+    fn report_scan(&self, scancode: u32) {
+        self.inner.report_scan(scancode);
+        if let Some(tap) = self.tap() {
+            tap.report_scan(scancode);
+        }
+    }
+}
+
+// Input side of the pad, normally the interrupt-in URB. Only needed to
+// hold it back for a poll interval, see xpad_poll_tick().
+trait InTransport: Send + Sync {
+    fn resubmit(&self) -> Result<(), UsbError>;
+}
+
+impl InTransport for Urb {
+    fn resubmit(&self) -> Result<(), UsbError> {
+        Urb::submit(self)
+    }
+}
+
+// Output side of the pad, normally the interrupt-out URB
+trait OutTransport: Send + Sync {
+    fn submit(&self, data: &[u8]) -> Result<(), UsbError>;
+    // Vendor control request on the default pipe, the reply (if any) is discarded
+    fn control(&self, setup: &ControlSetup) -> Result<(), UsbError>;
+    // Abort whatever was submitted and hasn't completed yet
+    fn cancel(&self) {}
+}
+
+// Setup stage of a control request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ControlSetup {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+}
+
+impl OutTransport for Urb {
+    fn submit(&self, data: &[u8]) -> Result<(), UsbError> {
+        self.transfer(data)?;
+        Urb::submit(self)
+    }
+
+    fn control(&self, setup: &ControlSetup) -> Result<(), UsbError> {
+        let mut dummy = vec![0u8; setup.length as usize];
+        self.device().control_msg_recv(
+            setup.request,
+            setup.request_type,
+            setup.value,
+            setup.index,
+            &mut dummy,
+        )
+    }
+
+    fn cancel(&self) {
+        self.kill();
+    }
+}
+
+// Transport that keeps every submitted packet and control request instead of
+// sending it, and counts input resubmits and cancels
+#[derive(Default)]
+struct RecordingTransport {
+    packets: Mutex<Vec<Vec<u8>>>,
+    controls: Mutex<Vec<ControlSetup>>,
+    resubmits: AtomicUsize,
+    cancels: AtomicUsize,
+    failures: Mutex<VecDeque<UsbError>>,
+}
+
+impl RecordingTransport {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    // Drain the packets submitted so far
+    fn take(&self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut *self.packets.lock().unwrap())
+    }
+
+    // Drain the control requests issued so far
+    fn take_controls(&self) -> Vec<ControlSetup> {
+        std::mem::take(&mut *self.controls.lock().unwrap())
+    }
+
+    fn resubmits(&self) -> usize {
+        self.resubmits.load(Ordering::SeqCst)
+    }
+
+    fn cancels(&self) -> usize {
+        self.cancels.load(Ordering::SeqCst)
+    }
+
+    // Fail the next submit with `err` instead of recording the packet.
+    // Queued failures are used up in order.
+    fn fail_next(&self, err: UsbError) {
+        self.failures.lock().unwrap().push_back(err);
+    }
+}
+
+impl InTransport for RecordingTransport {
+    fn resubmit(&self) -> Result<(), UsbError> {
+        self.resubmits.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl OutTransport for RecordingTransport {
+    fn submit(&self, data: &[u8]) -> Result<(), UsbError> {
+        if let Some(err) = self.failures.lock().unwrap().pop_front() {
+            return Err(err);
+        }
+        self.packets.lock().unwrap().push(data.to_vec());
+        Ok(())
+    }
+
+    fn control(&self, setup: &ControlSetup) -> Result<(), UsbError> {
+        self.controls.lock().unwrap().push(*setup);
+        Ok(())
+    }
+
+    fn cancel(&self) {
+        self.cancels.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Some 360 clones only start reporting after these dummy reads,
+// selected by the START_PKT_* quirks
+const XPAD360_START_PKT_1: ControlSetup = ControlSetup {
+    request_type: 0xc1, request: 0x01, value: 0x0100, index: 0x0000, length: 20,
+};
+const XPAD360_START_PKT_2: ControlSetup = ControlSetup {
+    request_type: 0xc1, request: 0x01, value: 0x0000, index: 0x0000, length: 8,
+};
+const XPAD360_START_PKT_3: ControlSetup = ControlSetup {
+    request_type: 0xc0, request: 0x01, value: 0x0000, index: 0x0000, length: 4,
+};
+
+// Issue the start requests the pad's quirks ask for
+fn xpad360_start(xpad: &UsbXpad) -> Result<(), UsbError> {
+    for action in xpad.quirk_actions() {
+        if let QuirkAction::StartPacket(setup) = action {
+            xpad.irq_out.control(setup)?;
+        }
+    }
+    Ok(())
+}
+
+// Keepalive the GHL guitar needs, or it stops reporting after a few seconds
+const GHL_XBOXONE_POKE: [u8; 12] = [
+    0x22, 0x00, 0x00, 0x08, 0x07, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+// Output packet that keeps an idle NEEDS_KEEPALIVE pad connected. The
+// PowerA and BDA Series X pads take the power-on request they get at init.
+fn keepalive_packet(id_vendor: u16) -> Option<&'static [u8]> {
+    match id_vendor {
+        0x20d6 | 0x24c6 => Some(&XBOXONE_POWER_ON),
+        _ => None,
+    }
+}
+
+// What a quirk bit does to a pad, decided once by apply_quirks()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuirkAction {
+    // Control request issued when the 360 pad is started
+    StartPacket(&'static ControlSetup),
+    // Periodic GHL_XBOXONE_POKE, sent by PadTimer::GhlPoke
+    GhlPoke { interval: Duration },
+    // Periodic keepalive packet sent by PadTimer::Keepalive, the interval
+    // can be changed at runtime
+    Keepalive { packet: &'static [u8] },
+    // Rumble requests are rejected
+    DisableRumble,
+    // Reports may stop after the left stick
+    ShortReports,
+    // Button input goes through a DebounceSink
+    DebounceButtons,
+    // Only standard input reports are decoded, vendor extras are dropped
+    StandardReportsOnly,
+    // Opposite d-pad directions are resolved with this mode by default
+    SocdCleaning(SocdMode),
+}
+
+// The one place quirk bits are turned into behavior. Runs once from
+// UsbXpad::new; anything quirk-dependent should look at quirk_actions()
+// rather than testing the bits itself.
+fn apply_quirks(xpad: &mut UsbXpad) {
+    let start_packets = [
+        (QuirkFlags::START_PKT_1, &XPAD360_START_PKT_1),
+        (QuirkFlags::START_PKT_2, &XPAD360_START_PKT_2),
+        (QuirkFlags::START_PKT_3, &XPAD360_START_PKT_3),
+    ];
+    let mut actions = Vec::new();
+
+    for (quirk, setup) in start_packets {
+        if xpad.quirks.contains(quirk) {
+            actions.push(QuirkAction::StartPacket(setup));
+        }
+    }
+    if xpad.quirks.contains(QuirkFlags::GHL_XBOXONE) {
+        actions.push(QuirkAction::GhlPoke { interval: Duration::from_secs(GHL_GUITAR_POKE_INTERVAL) });
+    }
+    if xpad.quirks.contains(QuirkFlags::NEEDS_KEEPALIVE) {
+        match keepalive_packet(xpad.id_vendor) {
+            Some(packet) => actions.push(QuirkAction::Keepalive { packet }),
+            None => log::warn!("{:04x}:{:04x} needs a keepalive, none known for the vendor",
+                               xpad.id_vendor, xpad.id_product),
+        }
+    }
+    if xpad.quirks.contains(QuirkFlags::NO_RUMBLE) {
+        actions.push(QuirkAction::DisableRumble);
+    }
+    if xpad.quirks.contains(QuirkFlags::SHORT_REPORTS) {
+        actions.push(QuirkAction::ShortReports);
+    }
+    if xpad.quirks.contains(QuirkFlags::DEBOUNCE_BUTTONS) {
+        actions.push(QuirkAction::DebounceButtons);
+        xpad.dev = Arc::new(DebounceSink::new(xpad.dev.clone(), xpad.clock.clone()));
+    }
+    if xpad.quirks.contains(QuirkFlags::HANDHELD_EXTRA_REPORTS) {
+        actions.push(QuirkAction::StandardReportsOnly);
+    }
+    if xpad.quirks.contains(QuirkFlags::SOCD_CLEANING) {
+        actions.push(QuirkAction::SocdCleaning(SocdMode::Neutral));
+        xpad.socd.get_mut().unwrap().mode = Some(SocdMode::Neutral);
+    }
+
+    if !actions.is_empty() {
+        xpad_debug!("{:04x}:{:04x} quirk actions: {:?}", xpad.id_vendor, xpad.id_product, actions);
+    }
+    xpad.quirk_actions = actions;
+}
+
+// Send the GHL keepalive if the pad needs one. Returns when to poke next.
+fn xpad_ghl_poke_tick(xpad: &UsbXpad) -> Option<Duration> {
+    if xpad.is_disconnected() {
+        return None;
+    }
+    let interval = xpad.quirk_actions().iter().find_map(|action| match action {
+        QuirkAction::GhlPoke { interval } => Some(*interval),
+        _ => None,
+    })?;
+
+    let mut packet = GHL_XBOXONE_POKE;
+    packet[2] = xpad.odata_serial.fetch_add(1, Ordering::SeqCst);
+    if let Err(err) = xpad.send_output_packet(&packet) {
+        log::warn!("GHL poke failed: {:?}", err);
+    }
+    Some(interval)
+}
+
+// Periodic work a pad runs, each step done by an xpad_*_tick() function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PadTimer {
+    GhlPoke,
+    Keepalive,
+    Watchdog,
+    Envelope,
+    Rumble,
+    RumbleCap,
+    Poll,
+    Guide,
+    OutRetry,
+}
+
+impl PadTimer {
+    const ALL: [PadTimer; 9] = [
+        PadTimer::GhlPoke, PadTimer::Keepalive, PadTimer::Watchdog,
+        PadTimer::Envelope, PadTimer::Rumble, PadTimer::RumbleCap,
+        PadTimer::Poll, PadTimer::Guide, PadTimer::OutRetry,
+    ];
+    // Run from attach on, the rest are kicked when there is work for them
+    const ON_ATTACH: [PadTimer; 2] = [PadTimer::GhlPoke, PadTimer::Keepalive];
+
+    fn tick(self) -> fn(&UsbXpad) -> Option<Duration> {
+        match self {
+            PadTimer::GhlPoke => xpad_ghl_poke_tick,
+            PadTimer::Keepalive => xpad_keepalive_tick,
+            PadTimer::Watchdog => xpad_watchdog_tick,
+            PadTimer::Envelope => xpad_envelope_tick,
+            PadTimer::Rumble => xpad_rumble_tick,
+            PadTimer::RumbleCap => xpad_rumble_cap_tick,
+            PadTimer::Poll => xpad_poll_tick,
+            PadTimer::Guide => xpad_guide_tick,
+            PadTimer::OutRetry => xpad_out_retry_tick,
+        }
+    }
+}
+
+// Work item running one PadTimer, requeues itself for as long as the tick
+// asks for another run. The pad owns its timers, so they only hold it weakly.
+struct XpadTimer {
+    kind: PadTimer,
+    xpad: Weak<UsbXpad>,
+}
+
+impl Work for XpadTimer {
+    fn run(&self) {
+        let Some(xpad) = self.xpad.upgrade() else {
+            return;
+        };
+        if let Some(delay) = (self.kind.tick())(&xpad) {
+            self.schedule_delayed(delay);
+        }
+    }
+}
+
+// Send the keepalive if the pad's quirks ask for one. Returns when to
+// send the next.
+fn xpad_keepalive_tick(xpad: &UsbXpad) -> Option<Duration> {
+    if xpad.is_disconnected() {
+        return None;
+    }
+    let packet = xpad.quirk_actions().iter().find_map(|action| match action {
+        QuirkAction::Keepalive { packet } => Some(*packet),
+        _ => None,
+    })?;
+
+    // A GIP packet like any other, byte 2 carries the output sequence number
+    let mut packet = packet.to_vec();
+    packet[2] = xpad.odata_serial.fetch_add(1, Ordering::SeqCst);
+    if let Err(err) = xpad.send_output_packet(&packet) {
+        log::warn!("Keepalive failed: {:?}", err);
+    }
+    Some(*xpad.keepalive_interval.lock().unwrap())
+}
+
+// Shared state structure
+struct UsbXpad {
+    name: &'static str,
+    xtype: XType,
+    id_vendor: u16,
+    id_product: u16,
+    dev: Arc<dyn InputSink>,
+    irq_out: Arc<dyn OutTransport>,
+    pad_present: AtomicBool,
+    irq_out_active: AtomicBool,
+    odata: Mutex<Vec<u8>>,
+    odata_serial: AtomicU8,
+    out_retries: u8,
+    out_inflight: Mutex<Option<(Vec<u8>, u8)>>,
+    out_retry: Mutex<Vec<OutRetry>>,
+    init_seq: Mutex<usize>,
+    mapping: AtomicU16,
+    packet_type: PacketType,
+    quirks: QuirkFlags,
+    intf_id: Option<UsbDeviceId>,
+    trigger_threshold: u8,
+    trigger_pressed: [AtomicBool; 2],
+    guide_pressed: AtomicBool,
+    clock: Arc<dyn Clock>,
+    presence_handler: Mutex<Option<Box<dyn Fn(bool) + Send + Sync>>>,
+    accessories: Mutex<Vec<Accessory>>,
+    accessory_handler: Mutex<Option<Box<dyn Fn(&[Accessory]) + Send + Sync>>>,
+    last_led: Mutex<Option<Vec<u8>>>,
+    led_animation: Mutex<Option<LedAnimation>>,
+    rumble_interval: Mutex<Duration>,
+    rumble_limiter: Mutex<RumbleLimiter>,
+    max_rumble_duration: Mutex<Option<Duration>>,
+    rumble_envelope: Mutex<Option<ActiveEnvelope>>,
+    player_index: AtomicU8,
+    battery: Mutex<Option<BatteryStatus>>,
+    events_dropped: Arc<AtomicU64>,
+    guide_longpress: Mutex<Option<GuideLongPress>>,
+    guide_hold: Mutex<Option<GuideHold>>,
+    injected: Mutex<ControllerState>,
+    poll_interval: Mutex<Option<Duration>>,
+    active_profile: AtomicU8,
+    // Custom mapping flag of the latest Elite firmware report
+    elite_mapping: AtomicU8,
+    keepalive_interval: Mutex<Duration>,
+    axis_config: Mutex<AxisConfig>,
+    ghl_strum_buttons: AtomicBool,
+    disconnected: AtomicBool,
+    resubmit_at: Mutex<Option<Duration>>,
+    irq_in: Mutex<Option<Arc<dyn InTransport>>>,
+    gip_caps: Mutex<Option<GipCapabilities>>,
+    smoothing: Mutex<HashMap<AbsoluteAxis, AxisSmoothing>>,
+    share_action: Mutex<ShareAction>,
+    share_pressed: AtomicBool,
+    state: Arc<Mutex<ControllerState>>,
+    init_state: Mutex<InitState>,
+    early_input_dropped: AtomicU64,
+    ready_handler: Mutex<Option<Box<dyn Fn() + Send>>>,
+    quirk_actions: Vec<QuirkAction>,
+    stick_dpad_threshold: AtomicU16,
+    socd: Mutex<SocdState>,
+    stick_deadzone: AtomicU16,
+    stick_curves: Mutex<[StickCurve; 2]>,
+    trigger_axes: Mutex<TriggerAxisLayout>,
+    rumble_gain: AtomicU8,
+    default_led: Mutex<Option<LedState>>,
+    profile: Arc<dyn DeviceProfile>,
+    interface_number: Option<u8>,
+    watchdog_timeout: Mutex<Option<Duration>>,
+    remap: Arc<Mutex<ButtonRemap>>,
+    mode_chord: Arc<AtomicBool>,
+    scancodes: Arc<AtomicBool>,
+    // Gets a copy of what reaches the device, see TapSink
+    tap: Arc<Mutex<Option<Arc<dyn InputSink>>>>,
+    generic: bool,
+    out_endpoint: Option<u8>,
+    manufacturer: Option<String>,
+    product: Option<String>,
+    serial: Option<String>,
+    // Bus and port chain the pad is plugged into, e.g. "1-4.2"
+    port_path: Option<String>,
+    // bcdDevice, what the firmware version shows up as over USB
+    firmware: Option<u16>,
+    last_packet_at: Mutex<Option<Duration>>,
+    neutralized: AtomicBool,
+    // One per PadTimer once start_timers() has run, empty after a disconnect
+    timers: Mutex<Vec<Arc<XpadTimer>>>,
+}
+
+impl UsbXpad {
+    fn new(
+        device: &XpadDevice,
+        dev: Arc<dyn InputSink>,
+        irq_out: Arc<dyn OutTransport>,
+        intf_id: Option<UsbDeviceId>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        // Decoders write to the outermost sink: debounce, trace, remap, state, scan, tap, device
+        let tap = Arc::new(Mutex::new(None));
+        let dev: Arc<dyn InputSink> = Arc::new(TapSink::new(dev, tap.clone()));
+        let scancodes = Arc::new(AtomicBool::new(false));
+        let dev: Arc<dyn InputSink> = Arc::new(ScanSink::new(dev, scancodes.clone()));
+        let state = Arc::new(Mutex::new(ControllerState::default()));
+        let dev: Arc<dyn InputSink> = Arc::new(StateSink::new(dev, state.clone()));
+        let mapping = normalize_mapping(device.mapping | param_map_flags());
+        let remap = Arc::new(Mutex::new(ButtonRemap::default()));
+        let mode_chord = Arc::new(AtomicBool::new(mapping.contains(MapFlags::MODE_AS_CHORD)));
+        let dev: Arc<dyn InputSink> = Arc::new(RemapSink::new(dev, remap.clone(), mode_chord.clone()));
+        #[cfg(feature = "trace-input")]
+        let dev: Arc<dyn InputSink> = Arc::new(TraceSink::new(dev));
+
+        let mut xpad = Self {
+            name: device.name,
+            // The interface protocol wins over the table, e.g. 129 is a
+            // receiver, unless the fallback policy forced the generic pad's
+            xtype: match generic_xtype(device) {
+                Some(xtype) => xtype,
+                None => intf_id.and_then(|id| id.protocol_xtype()).unwrap_or(device.xtype),
+            },
+            id_vendor: device.id_vendor,
+            id_product: device.id_product,
+            dev,
+            irq_out,
+            pad_present: AtomicBool::new(false),
+            irq_out_active: AtomicBool::new(false),
+            odata: Mutex::new(Vec::with_capacity(XPAD_PKT_LEN)),
+            odata_serial: AtomicU8::new(0),
+            out_retries: DEFAULT_OUT_RETRIES,
+            out_inflight: Mutex::new(None),
+            out_retry: Mutex::new(Vec::new()),
+            init_seq: Mutex::new(0),
+            mapping: AtomicU16::new(mapping.bits()),
+            packet_type: PacketType::Xb,
+            quirks: device.quirks,
+            intf_id,
+            trigger_threshold: DEFAULT_TRIGGER_THRESHOLD,
+            trigger_pressed: [AtomicBool::new(false), AtomicBool::new(false)],
+            guide_pressed: AtomicBool::new(false),
+            clock,
+            presence_handler: Mutex::new(None),
+            accessories: Mutex::new(Vec::new()),
+            accessory_handler: Mutex::new(None),
+            last_led: Mutex::new(None),
+            led_animation: Mutex::new(None),
+            rumble_interval: Mutex::new(Duration::from_millis(DEFAULT_RUMBLE_INTERVAL_MS)),
+            rumble_limiter: Mutex::new(RumbleLimiter::default()),
+            max_rumble_duration: Mutex::new(Some(Duration::from_secs(DEFAULT_MAX_RUMBLE_SECS))),
+            rumble_envelope: Mutex::new(None),
+            player_index: AtomicU8::new(0),
+            battery: Mutex::new(None),
+            events_dropped: Arc::new(AtomicU64::new(0)),
+            guide_longpress: Mutex::new(None),
+            guide_hold: Mutex::new(None),
+            injected: Mutex::new(ControllerState::default()),
+            poll_interval: Mutex::new(None),
+            active_profile: AtomicU8::new(0),
+            elite_mapping: AtomicU8::new(0),
+            keepalive_interval: Mutex::new(Duration::from_secs(KEEPALIVE_INTERVAL)),
+            axis_config: Mutex::new(AxisConfig::default()),
+            ghl_strum_buttons: AtomicBool::new(false),
+            disconnected: AtomicBool::new(false),
+            resubmit_at: Mutex::new(None),
+            irq_in: Mutex::new(None),
+            gip_caps: Mutex::new(None),
+            smoothing: Mutex::new(HashMap::new()),
+            share_action: Mutex::new(ShareAction::Button(Button::Select)),
+            share_pressed: AtomicBool::new(false),
+            state,
+            init_state: Mutex::new(InitState::Enumerated),
+            early_input_dropped: AtomicU64::new(0),
+            ready_handler: Mutex::new(None),
+            quirk_actions: Vec::new(),
+            stick_dpad_threshold: AtomicU16::new(DEFAULT_STICK_DPAD_THRESHOLD),
+            socd: Mutex::new(SocdState::default()),
+            stick_deadzone: AtomicU16::new(0),
+            stick_curves: Mutex::new([StickCurve::default(); 2]),
+            trigger_axes: Mutex::new(TriggerAxisLayout::default()),
+            rumble_gain: AtomicU8::new(100),
+            default_led: Mutex::new(table_default_led(device.id_vendor, device.id_product)),
+            profile: Arc::new(XboxProfile),
+            interface_number: None,
+            watchdog_timeout: Mutex::new(None),
+            remap,
+            mode_chord,
+            scancodes,
+            tap,
+            generic: (device.id_vendor, device.id_product) == (0x0000, 0x0000),
+            out_endpoint: None,
+            manufacturer: None,
+            product: None,
+            serial: None,
+            port_path: None,
+            firmware: None,
+            last_packet_at: Mutex::new(None),
+            neutralized: AtomicBool::new(false),
+            timers: Mutex::new(Vec::new()),
+        };
+        apply_quirks(&mut xpad);
+        xpad.profile = resolve_profile(&xpad);
+        xpad
+    }
+
+    // Bind to the interface chosen by select_interface(). This decides the
+    // protocol the same way a matched interface id passed to new() does.
+    fn with_interface(mut self, interface: InterfaceInfo) -> Self {
+        let intf_id = match_interface(self.id_vendor, &interface)
+            .copied()
+            .unwrap_or_else(|| interface.device_id(self.id_vendor));
+        self.intf_id = Some(intf_id);
+        if let Some(xtype) = intf_id.protocol_xtype() {
+            if !(self.generic && fallback_policy().xtype().is_some()) {
+                self.xtype = xtype;
+            }
+        }
+        self.interface_number = Some(interface.number);
+        self.profile = resolve_profile(&self);
+        self
+    }
+
+    fn mapping(&self) -> MapFlags {
+        MapFlags::from_bits_truncate(self.mapping.load(Ordering::Relaxed))
+    }
+
+    // Change this pad's mapping, reports decoded from now on use it. Returns
+    // whether the buttons or axes it reports changed, in which case the
+    // caller has to register capabilities again.
+    fn set_mapping(&self, mapping: MapFlags) -> bool {
+        let before = xpad_capabilities(self);
+        let mapping = normalize_mapping(mapping);
+        self.mapping.store(mapping.bits(), Ordering::Relaxed);
+        self.mode_chord.store(mapping.contains(MapFlags::MODE_AS_CHORD), Ordering::Relaxed);
+
+        // Sticks coming back take their axes back from the triggers
+        let mut trigger_axes = self.trigger_axes.lock().unwrap();
+        if mapping.uses_analog_sticks() && XPAD_ABS.iter().any(|&axis| trigger_axes.contains(axis)) {
+            log::warn!("Trigger axes {:?} clash with the sticks, moved back to Z/RZ", *trigger_axes);
+            *trigger_axes = TriggerAxisLayout::default();
+        }
+        drop(trigger_axes);
+
+        let after = xpad_capabilities(self);
+
+        let changed = before.buttons != after.buttons || before.axes != after.axes;
+        if changed {
+            log::info!("{:04x}:{:04x} mapping changed to {:?}, consumers may need a reconnect to see the new controls",
+                       self.id_vendor, self.id_product, mapping);
+        }
+        changed
+    }
+
+    // Look the pad up again in the device tables, e.g. after a corrected
+    // entry was registered, and apply what can change on a live pad. Only the
+    // mapping can; quirks and protocol decide the sink chain and decoder
+    // picked in new(), so a change there needs the device probed again.
+    fn refresh_from_registry(&self) -> Refresh {
+        let Some(device) = lookup_device(self.id_vendor, self.id_product) else {
+            return Refresh::Unchanged;
+        };
+
+        let xtype = match generic_xtype(&device) {
+            Some(xtype) => xtype,
+            None => self.intf_id.and_then(|id| id.protocol_xtype()).unwrap_or(device.xtype),
+        };
+        if device.quirks != self.quirks || xtype != self.xtype {
+            log::info!("{:04x}:{:04x} quirks or protocol changed in the registry, reprobe to apply",
+                       self.id_vendor, self.id_product);
+            return Refresh::ReprobeNeeded;
+        }
+
+        let mapping = normalize_mapping(device.mapping | param_map_flags());
+        if mapping.bits() == self.mapping().bits() {
+            return Refresh::Unchanged;
+        }
+        Refresh::MappingApplied { capabilities_changed: self.set_mapping(mapping) }
+    }
+
+    // Pick the out endpoint from the bound interface's endpoints. The
+    // transport should be opened on out_endpoint(); output is unavailable
+    // when there is none.
+    fn with_endpoints(mut self, endpoints: &[EndpointInfo]) -> Self {
+        self.out_endpoint = find_out_endpoint(endpoints);
+        if self.out_endpoint.is_none() {
+            log::warn!("{:04x}:{:04x} has no interrupt-out endpoint", self.id_vendor, self.id_product);
+        }
+        self
+    }
+
+    fn out_endpoint(&self) -> Option<u8> {
+        self.out_endpoint
+    }
+
+    // Send an arbitrary output packet, for reverse engineering. Nothing is
+    // checked, a malformed packet can wedge or even brick a pad's firmware,
+    // so this only exists with the experimental feature. With `stamp_serial`
+    // byte 2 gets the next GIP sequence number, as the driver's own do.
+    #[cfg(feature = "experimental")]
+    fn send_raw_output(&self, packet: &[u8], stamp_serial: bool) -> Result<(), XpadError> {
+        let mut packet = packet.to_vec();
+        if stamp_serial {
+            match packet.get_mut(2) {
+                Some(serial) => *serial = self.odata_serial.fetch_add(1, Ordering::SeqCst),
+                None => return Err(XpadError::NotSupported),
+            }
+        }
+        self.send_output_packet(&packet)?;
+        Ok(())
+    }
+
+    // Strings the device reports about itself. Missing or blank ones are
+    // treated as absent.
+    fn with_usb_strings(mut self, manufacturer: Option<&str>, product: Option<&str>) -> Self {
+        let clean = |s: Option<&str>| s.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+        self.manufacturer = clean(manufacturer);
+        self.product = clean(product);
+        self
+    }
+
+    // Serial number string and bcdDevice from the device descriptor. Blank
+    // serials, which some clones report, count as none. On Elites the
+    // firmware also decides the paddle report format.
+    fn with_serial_and_firmware(mut self, serial: Option<&str>, firmware: u16) -> Self {
+        self.serial = serial.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+        self.firmware = Some(firmware);
+        self.packet_type = match (self.id_vendor, self.id_product) {
+            (0x045e, 0x02e3) => PacketType::Xbe1,
+            (0x045e, 0x0b00) if firmware < 0x0500 => PacketType::Xbe2FwOld,
+            (0x045e, 0x0b00) if firmware < 0x050b => PacketType::Xbe2Fw5Early,
+            (0x045e, 0x0b00) => PacketType::Xbe2Fw5_11,
+            _ => PacketType::Xb,
+        };
+        self
+    }
+
+    // Firmware version, if with_serial_and_firmware() was given bcdDevice
+    fn firmware_version(&self) -> Option<FirmwareVersion> {
+        self.firmware.map(FirmwareVersion::from_bcd)
+    }
+
+    // Where the pad is plugged in, as the USB core names it ("1-4.2")
+    fn with_port_path(mut self, path: &str) -> Self {
+        self.port_path = Some(path.to_string());
+        self
+    }
+
+    // Key for per-controller settings that tells identical pads apart.
+    // Vendor and product, then the first of: the serial number, which
+    // follows the pad to any port; the port path, which stays put across
+    // replugs into the same port; the interface number, which only tells
+    // apart the pads of one receiver. Same inputs give the same string.
+    fn fingerprint(&self) -> String {
+        let id = format!("{:04x}:{:04x}", self.id_vendor, self.id_product);
+        match (&self.serial, &self.port_path, self.interface_number) {
+            (Some(serial), _, _) => format!("{}/serial={}", id, serial),
+            (None, Some(path), _) => format!("{}/port={}", id, path),
+            (None, None, Some(interface)) => format!("{}/interface={}", id, interface),
+            (None, None, None) => id,
+        }
+    }
+
+    // Table name, unless the pad only matched the generic entry and has a
+    // product string of its own
+    fn display_name(&self) -> String {
+        match (&self.manufacturer, &self.product) {
+            (Some(manufacturer), Some(product)) if self.generic => format!("{} {}", manufacturer, product),
+            (None, Some(product)) if self.generic => product.clone(),
+            _ => self.name.to_string(),
+        }
+    }
+
+    // What the device's quirks turned into at setup
+    fn quirk_actions(&self) -> &[QuirkAction] {
+        &self.quirk_actions
+    }
+
+    // Decided when the pad was bound, see new() and with_interface(): the
+    // interface we matched on wins over the table xtype, unless the
+    // fallback policy forced the generic pad's
+    fn protocol(&self) -> XType {
+        self.xtype
+    }
+
+    // The device is gone. Cancel the output URB, drop every queued or
+    // pending output, stop the periodic work and refuse new output. Input
+    // URBs that still complete are neither decoded nor resubmitted.
+    fn handle_disconnect(&self) {
+        if self.disconnected.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        log::info!("{:04x}:{:04x} disconnected", self.id_vendor, self.id_product);
+
+        self.irq_out.cancel();
+        self.irq_out_active.store(false, Ordering::SeqCst);
+        self.odata.lock().unwrap().clear();
+        *self.out_inflight.lock().unwrap() = None;
+        self.out_retry.lock().unwrap().clear();
+        *self.rumble_limiter.lock().unwrap() = RumbleLimiter::default();
+        *self.rumble_envelope.lock().unwrap() = None;
+        *self.resubmit_at.lock().unwrap() = None;
+        *self.irq_in.lock().unwrap() = None;
+        *self.guide_hold.lock().unwrap() = None;
+        *self.led_animation.lock().unwrap() = None;
+        for timer in self.timers.lock().unwrap().drain(..) {
+            timer.cancel();
+        }
+
+        self.pad_present.store(false, Ordering::SeqCst);
+        *self.init_state.lock().unwrap() = InitState::Enumerated;
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.disconnected.load(Ordering::SeqCst)
+    }
+
+    // Create the pad's timers and start the ones that run from the
+    // beginning. The timers need the pad shared, so whoever puts it in an
+    // Arc calls this.
+    fn start_timers(self: &Arc<Self>) {
+        *self.timers.lock().unwrap() = PadTimer::ALL
+            .iter()
+            .map(|&kind| Arc::new(XpadTimer { kind, xpad: Arc::downgrade(self) }))
+            .collect();
+        for kind in PadTimer::ON_ATTACH {
+            self.kick_timer(kind);
+        }
+    }
+
+    // Run a timer's tick as soon as possible, moving it up if it is already
+    // queued. The tick schedules the next run itself. Does nothing before
+    // start_timers() or once the pad is gone.
+    fn kick_timer(&self, kind: PadTimer) {
+        if self.is_disconnected() {
+            return;
+        }
+        if let Some(timer) = self.timers.lock().unwrap().iter().find(|timer| timer.kind == kind) {
+            timer.schedule_delayed(Duration::ZERO);
+        }
+    }
+
+    // Drop a queued run of a timer, for when the work it was waiting on
+    // went away
+    fn cancel_timer(&self, kind: PadTimer) {
+        if let Some(timer) = self.timers.lock().unwrap().iter().find(|timer| timer.kind == kind) {
+            timer.cancel();
+        }
+    }
+
+    // Send one output packet. If the endpoint stalls or times out, on submit
+    // or in the completion, it is tried again from the OutRetry timer, up to
+    // out_retries times; a queued retry counts as sent here. Anything else
+    // (disconnect included) is returned right away.
+    fn send_output_packet(&self, data: &[u8]) -> Result<(), UsbError> {
+        self.submit_out(data, 0)
+    }
+
+    // Submit an output packet for the `attempt`-th time, 0 being the first
+    fn submit_out(&self, data: &[u8], attempt: u8) -> Result<(), UsbError> {
+        if self.is_disconnected() {
+            return Err(UsbStatus::Disconnected.into());
+        }
+        match self.irq_out.submit(data) {
+            Ok(()) => {
+                *self.out_inflight.lock().unwrap() = Some((data.to_vec(), attempt));
+                Ok(())
+            },
+            Err(err) if usb_error_is_transient(&err) && attempt < self.out_retries => {
+                xpad_debug!("output packet failed ({:?}), retry {}", err, attempt + 1);
+                self.queue_out_retry(data.to_vec(), attempt + 1);
+                Ok(())
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    // Try `packet` again once the backoff for its attempt has passed. We
+    // are in completion or timer context here, nothing may sleep.
+    fn queue_out_retry(&self, packet: Vec<u8>, attempt: u8) {
+        let due = self.clock.now() + Duration::from_millis(OUT_RETRY_BACKOFF_MS * u64::from(attempt));
+        self.out_retry.lock().unwrap().push(OutRetry { packet, attempt, due });
+        self.kick_timer(PadTimer::OutRetry);
+    }
+
+    // The output URB completed. A stall or timeout puts the packet that was
+    // in flight up for a retry, as a failed submit does.
+    fn out_completed(&self, result: Result<(), UsbError>) {
+        let inflight = self.out_inflight.lock().unwrap().take();
+        let Err(err) = result else {
+            return;
+        };
+        match inflight {
+            Some((packet, attempt)) if usb_error_is_transient(&err) && attempt < self.out_retries => {
+                xpad_debug!("output packet failed ({:?}), retry {}", err, attempt + 1);
+                self.queue_out_retry(packet, attempt + 1);
+            },
+            _ => log::warn!("Output packet failed: {:?}", err),
+        }
+    }
+
+    // The input URB completed with `status` and `data`. Decodes the report
+    // and resubmits the URB, unless the pad is gone or polling holds it.
+    fn in_completed(&self, status: UsbStatus, data: &[u8], urb: &dyn InTransport) -> Result<(), UsbError> {
+        match status {
+            UsbStatus::Success if self.is_disconnected() => return Ok(()),
+            UsbStatus::Success => (),
+            UsbStatus::Disconnected => {
+                self.handle_disconnect();
+                return Ok(());
+            },
+            UsbStatus::Cancelled => return Ok(()),
+            err => {
+                log::warn!("URB error: {:?}", err);
+                return Err(err.into());
+            }
+        }
+
+        xpad_debug!("Received packet: {:02X?}", data);
+        self.decode(data);
+
+        if self.hold_input() {
+            return Ok(());
+        }
+        urb.resubmit()
+    }
+}
+
+// An output packet waiting out its backoff before the next try
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OutRetry {
+    packet: Vec<u8>,
+    // Which try this will be, 1 for the first retry
+    attempt: u8,
+    due: Duration,
+}
+
+// Send the output packets whose backoff is over, in the order they failed.
+// Returns how long until the next one is due, None when none are left.
+fn xpad_out_retry_tick(xpad: &UsbXpad) -> Option<Duration> {
+    if xpad.is_disconnected() {
+        return None;
+    }
+    let now = xpad.clock.now();
+    let due: Vec<OutRetry> = {
+        let mut queue = xpad.out_retry.lock().unwrap();
+        let (due, later) = std::mem::take(&mut *queue).into_iter().partition(|retry| retry.due <= now);
+        *queue = later;
+        due
+    };
+    for retry in due {
+        if let Err(err) = xpad.submit_out(&retry.packet, retry.attempt) {
+            log::warn!("Output packet retry {} failed: {:?}", retry.attempt, err);
+        }
+    }
+
+    // Failed retries above may have queued the next attempt
+    let next = xpad.out_retry.lock().unwrap().iter().map(|retry| retry.due).min()?;
+    Some(next.saturating_sub(now))
+}
+
+// Output URB completion handler, the counterpart of xpad_irq_in()
+fn xpad_irq_out(urb: &Urb, xpad: &UsbXpad) {
+    match urb.status() {
+        UsbStatus::Success => xpad.out_completed(Ok(())),
+        UsbStatus::Disconnected => xpad.handle_disconnect(),
+        UsbStatus::Cancelled => (),
+        err => xpad.out_completed(Err(err.into())),
+    }
+}
+
+// Counters for troubleshooting, see UsbXpad::diagnostics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct XpadDiagnostics {
+    // Xbox One input reports dropped because init hadn't completed
+    early_input_dropped: u64,
+    // Interface the pad was bound to, if it was selected from several
+    interface: Option<u8>,
+    // USB product string, if the device has one
+    product: Option<String>,
+    // Events the manager's queue dropped because it was full
+    events_dropped: u64,
+}
+
+// Xbox One pads don't send usable input until the whole handshake is done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum InitState {
+    // Bound, nothing heard from the pad yet
+    Enumerated,
+    // The pad sent its announce packet
+    Announced,
+    // All init packets have gone out
+    Configured,
+    // The last init packet was acked, input is live
+    Ready,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessoryKind {
+    HeadsetAdapter,
+    Other,
+}
+
+impl AccessoryKind {
+    fn from_ids(vendor: u16, product: u16) -> Self {
+        match (vendor, product) {
+            (0x045e, GIP_PRODUCT_HEADSET_ADAPTER) => Self::HeadsetAdapter,
+            _ => Self::Other,
+        }
+    }
+}
+
+// Something plugged into the expansion port of an Xbox One pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Accessory {
+    // GIP client id it talks on
+    client: u8,
+    vendor: u16,
+    product: u16,
+    kind: AccessoryKind,
+}
+
+// What pressing the Share/Capture button does
+enum ShareAction {
+    // Report it as this button
+    Button(Button),
+    // Drop it
+    Ignore,
+    // Call back with the new state on every change, nothing is reported
+    Callback(Box<dyn Fn(bool) + Send + Sync>),
+}
+
+impl UsbXpad {
+    // Send an LED packet and remember it so it can be restored after a re-init
+    fn send_led_packet(&self, packet: &[u8]) -> Result<(), UsbError> {
+        *self.last_led.lock().unwrap() = Some(packet.to_vec());
+        self.send_output_packet(packet)
+    }
+
+    // Run the init sequence again on a pad that is already up, e.g. after a
+    // firmware hiccup, then put the LED back the way it was
+    fn reinitialize(&self) -> Result<(), XpadError> {
+        self.reset_sequences();
+        *self.init_state.lock().unwrap() = InitState::Enumerated;
+        self.reset_filters();
+
+        for packet in self.profile.init_packets(self) {
+            self.send_output_packet(&packet)?;
+        }
+        if self.protocol() == XType::Xbox360 {
+            xpad360_start(self)?;
+        }
+        // Taken before reaching Ready, which may send the default LED
+        let last_led = self.last_led.lock().unwrap().clone();
+        // Xbox One pads got there asking for the ack, the rest are Ready now
+        self.advance_init(InitState::Configured);
+
+        if let Some(packet) = last_led {
+            self.send_output_packet(&packet)?;
+        }
+        Ok(())
+    }
+
+    // Called once the pad is actually usable: init sequence sent and, on
+    // Xbox One, acknowledged. Fires again after a reinitialize().
+    fn set_ready_handler(&self, handler: Box<dyn Fn() + Send>) {
+        *self.ready_handler.lock().unwrap() = Some(handler);
+    }
+
+    // Called when the guide is held for `threshold`, that press is then
+    // not reported. Shorter presses still come through as a tap.
+    fn set_guide_longpress_handler(&self, threshold: Duration, handler: Box<dyn Fn() + Send>) {
+        *self.guide_longpress.lock().unwrap() = Some(GuideLongPress { threshold, handler });
+        *self.guide_hold.lock().unwrap() = None;
+    }
+
+    fn init_state(&self) -> InitState {
+        *self.init_state.lock().unwrap()
+    }
+
+    // Move the init state forward, never back; only reinitialize() rewinds it.
+    // Only Xbox One pads wait for an ack, the others are ready once configured.
+    // Reaching Ready fires the ready handler.
+    fn advance_init(&self, next: InitState) {
+        let mut state = self.init_state.lock().unwrap();
+        let mut next = next;
+        if next == InitState::Configured && self.protocol() != XType::XboxOne {
+            next = InitState::Ready;
+        }
+        if next <= *state {
+            return;
+        }
+        *state = next;
+        drop(state);
+
+        if next == InitState::Ready {
+            self.apply_default_led();
+
+            let deferred = self.rumble_limiter.lock().unwrap().deferred.take();
+            if let Some(rumble) = deferred.filter(|rumble| !rumble.is_stop()) {
+                if let Err(err) = xpad_play_effect(self, &rumble) {
+                    log::warn!("Deferred rumble failed: {:?}", err);
+                }
+            }
+            if let Some(handler) = self.ready_handler.lock().unwrap().as_ref() {
+                handler();
+            }
+        }
+    }
+
+    // LED to show once init completes, None leaves the controller's own choice
+    fn set_default_led(&self, state: Option<LedState>) {
+        *self.default_led.lock().unwrap() = state;
+    }
+
+    // Only the first step of a rotating default is shown, stepping it
+    // needs the LED device's work item. A pad with a player index keeps
+    // showing its player instead, and one that was already shown an LED
+    // gets that back from reinitialize().
+    fn apply_default_led(&self) {
+        if self.player_index() != 0 || self.last_led.lock().unwrap().is_some() {
+            return;
+        }
+        let state = *self.default_led.lock().unwrap();
+        if let Some(state) = state {
+            if let Err(err) = xpad_set_led(self, state) {
+                log::warn!("Failed to set default LED: {:?}", err);
+            }
+        }
+    }
+
+    // Smooth a stick axis with an EMA, alpha in (0, 1], 1.0 disables it
+    fn set_axis_smoothing(&self, axis: AbsoluteAxis, alpha: f32) {
+        let mut smoothing = self.smoothing.lock().unwrap();
+        if alpha >= 1.0 {
+            smoothing.remove(&axis);
+        } else {
+            smoothing.insert(axis, AxisSmoothing { alpha: alpha.max(f32::EPSILON), state: None });
+        }
+    }
+
+    // Only Microsoft's own Xbox One pads can be switched off from the host,
+    // third-party ones are wired-only and ignore the request
+    fn supports_power_off(&self) -> bool {
+        self.protocol() == XType::XboxOne
+            && self.id_vendor == 0x045e
+            && !self.quirks.contains(QuirkFlags::GHL_XBOXONE)
+    }
+
+    // Turn the pad off. It has to go through the whole init handshake again
+    // when it comes back, so input stays gated until then.
+    fn power_off(&self) -> Result<(), XpadError> {
+        if !self.supports_power_off() {
+            return Err(XpadError::NotSupported);
+        }
+
+        let packet = [
+            GIP_CMD_POWER, GIP_OPT_INTERNAL,
+            self.odata_serial.fetch_add(1, Ordering::SeqCst),
+            0x01, GIP_POWER_OFF,
+        ];
+        self.send_output_packet(&packet)?;
+
+        self.pad_present.store(false, Ordering::SeqCst);
+        *self.init_state.lock().unwrap() = InitState::Enumerated;
+        Ok(())
+    }
+
+    // Emit MSC_SCAN before each button event, off by default. Like
+    // set_mapping(), returns whether capabilities have to be registered
+    // again, which they do whenever MSC_SCAN comes or goes.
+    fn set_emit_scancodes(&self, enable: bool) -> bool {
+        self.scancodes.swap(enable, Ordering::Relaxed) != enable
+    }
+
+    // Replace the pad's button remapping, takes effect from the next report
+    // Refused if it names a button without an evdev code, see EvdevCode
+    fn set_button_remap(&self, remap: ButtonRemap) -> Result<(), XpadError> {
+        let unknown = remap.map.iter().flat_map(|(&from, &to)| [from, to]).find(|button| button.code().is_none());
+        if let Some(button) = unknown {
+            return Err(XpadError::UnknownButton(button));
+        }
+        *self.remap.lock().unwrap() = remap;
+        Ok(())
+    }
+
+    // Stick values closer to center than this are reported as 0
+    fn set_stick_deadzone(&self, deadzone: u16) {
+        self.stick_deadzone.store(deadzone, Ordering::Relaxed);
+    }
+
+    // Move the trigger axes. Any axis the pad doesn't otherwise report will
+    // do, so RY is fine once STICKS_TO_NULL has freed it. Like a mapping
+    // change, the capabilities have to be registered again.
+    fn set_trigger_axis_layout(&self, layout: TriggerAxisLayout) -> Result<(), XpadError> {
+        if layout.left == layout.right {
+            return Err(XpadError::AxisConflict(layout.left));
+        }
+
+        let current = *self.trigger_axes.lock().unwrap();
+        let caps = xpad_capabilities(self);
+        let taken = caps.axes.iter().find(|&&axis| !current.contains(axis) && layout.contains(axis));
+        if let Some(&axis) = taken {
+            return Err(XpadError::AxisConflict(axis));
+        }
+
+        *self.trigger_axes.lock().unwrap() = layout;
+        Ok(())
+    }
+
+    // Response curve for one stick, applied after the deadzone
+    fn set_stick_curve(&self, stick: Stick, curve: StickCurve) {
+        self.stick_curves.lock().unwrap()[stick as usize] = curve;
+    }
+
+    // Scale every rumble update, in percent
+    fn set_rumble_gain(&self, percent: u8) {
+        self.rumble_gain.store(percent.min(100), Ordering::Relaxed);
+    }
+
+    // Everything a user can change on this pad, to be restored later with
+    // apply_settings()
+    fn export_settings(&self) -> XpadSettings {
+        let remap = self.remap.lock().unwrap();
+        let mut remap: Vec<(u16, u16)> = remap
+            .map
+            .iter()
+            .filter_map(|(from, to)| Some((from.code()?, to.code()?)))
+            .collect();
+        remap.sort_unstable();
+        let axis_config = *self.axis_config.lock().unwrap();
+
+        XpadSettings {
+            mapping: self.mapping().bits(),
+            stick_deadzone: self.stick_deadzone.load(Ordering::Relaxed),
+            stick_dpad_threshold: self.stick_dpad_threshold.load(Ordering::Relaxed),
+            socd: self.socd.lock().unwrap().mode,
+            remap,
+            rumble_gain: self.rumble_gain.load(Ordering::Relaxed),
+            default_led: *self.default_led.lock().unwrap(),
+            invert_lt: axis_config.invert_lt,
+            invert_rt: axis_config.invert_rt,
+        }
+    }
+
+    // Restore exported settings. Only goes through the regular setters, so it
+    // is fine on a pad that was just set up. Returns whether the reported
+    // controls changed, as set_mapping() does.
+    fn apply_settings(&self, settings: &XpadSettings) -> bool {
+        let mut remap = ButtonRemap::default();
+        for &(from, to) in &settings.remap {
+            match (Button::from_code(from), Button::from_code(to)) {
+                (Some(from), Some(to)) => remap.set(from, to),
+                _ => log::warn!("Ignoring remap of unknown button code {:#x} -> {:#x}", from, to),
+            }
+        }
+
+        self.set_stick_deadzone(settings.stick_deadzone);
+        self.set_stick_dpad_threshold(settings.stick_dpad_threshold);
+        self.set_socd_mode(settings.socd);
+        if let Err(err) = self.set_button_remap(remap) {
+            log::warn!("Ignoring remap: {:?}", err);
+        }
+        self.set_rumble_gain(settings.rumble_gain);
+        self.set_default_led(settings.default_led);
+        self.set_axis_config(AxisConfig { invert_lt: settings.invert_lt, invert_rt: settings.invert_rt });
+        self.set_mapping(MapFlags::from_bits_truncate(settings.mapping))
+    }
+
+    // Resolved settings as a plain text report, one "key: value" per line,
+    // for pasting into bug reports. Keys don't change between versions.
+    fn describe_config(&self) -> String {
+        let names = |names: Vec<&str>| if names.is_empty() { "none".to_string() } else { names.join(" | ") };
+        let mapping = names(self.mapping().iter_names().map(|(name, _)| name).collect());
+        let quirks = names(self.quirks.iter_names().map(|(name, _)| name).collect());
+
+        let triggers = if self.mapping().contains(MapFlags::TRIGGERS_TO_BUTTONS) {
+            format!("buttons (threshold {})", self.trigger_threshold)
+        } else if self.mapping().contains(MapFlags::TRIGGERS_BOTH) {
+            format!("axes (0..{}) and buttons (threshold {})", trigger_abs_max(self.protocol()), self.trigger_threshold)
+        } else {
+            format!("axes (0..{})", trigger_abs_max(self.protocol()))
+        };
+
+        // The decoders always flip Y and RY, the triggers are up to AxisConfig
+        let axis_config = *self.axis_config.lock().unwrap();
+        let mut inverted = vec!["Y", "RY"];
+        if axis_config.invert_lt {
+            inverted.push("LT");
+        }
+        if axis_config.invert_rt {
+            inverted.push("RT");
+        }
+
+        let led = if let Some(animation) = self.led_animation.lock().unwrap().as_ref() {
+            format!("rotating every {:?}", animation.interval)
+        } else {
+            match self.last_led.lock().unwrap().as_ref() {
+                Some(packet) => format!("{:02x?}", packet),
+                None => "default".to_string(),
+            }
+        };
+
+        let mut report = String::new();
+        report.push_str(&format!("name: {}\n", self.display_name()));
+        report.push_str(&format!("id: {:04x}:{:04x}\n", self.id_vendor, self.id_product));
+        report.push_str(&format!("xtype: {:?}\n", self.protocol()));
+        report.push_str(&format!("packet_type: {:?}\n", self.packet_type));
+        report.push_str(&format!("mapping: {}\n", mapping));
+        report.push_str(&format!("quirks: {}\n", quirks));
+        report.push_str(&format!("deadzone: {}\n", self.stick_deadzone.load(Ordering::Relaxed)));
+        report.push_str(&format!("inversion: {}\n", inverted.join(", ")));
+        report.push_str(&format!("triggers: {}\n", triggers));
+        report.push_str(&format!("socd: {:?}\n", self.socd.lock().unwrap().mode));
+        report.push_str(&format!("rumble_gain: {}%\n", self.rumble_gain.load(Ordering::Relaxed)));
+        report.push_str(&format!("led: {}\n", led));
+        report
+    }
+
+    // Stick deflection needed for STICK_TO_DPAD to press a direction
+    fn set_stick_dpad_threshold(&self, threshold: u16) {
+        self.stick_dpad_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    // How opposite d-pad directions held together are resolved, None
+    // passes them through as the pad reports them
+    fn set_socd_mode(&self, mode: Option<SocdMode>) {
+        *self.socd.lock().unwrap() = SocdState { mode, ..SocdState::default() };
+    }
+
+    // Minimum time between rumble packets, zero sends every update
+    fn set_rumble_interval(&self, interval: Duration) {
+        *self.rumble_interval.lock().unwrap() = interval;
+    }
+
+    // Longest the motors may run before they're stopped regardless of the
+    // effect, raise it for legitimately long effects. None removes the cap.
+    fn set_max_rumble_duration(&self, cap: Option<Duration>) {
+        *self.max_rumble_duration.lock().unwrap() = cap;
+    }
+
+    // Latest decoded state of every control
+    fn current_state(&self) -> ControllerState {
+        *self.state.lock().unwrap()
+    }
+
+    // Elite profile slot last reported, 0 is the default profile
+    fn active_profile(&self) -> u8 {
+        self.active_profile.load(Ordering::Relaxed)
+    }
+
+    // Whether Elite paddle presses are dropped. Once the pad remaps the
+    // paddles to other buttons itself, reporting them too would press two
+    // buttons at once. Either signal is enough: a profile slot other than
+    // the default (0), or the custom mapping flag of the firmware report,
+    // which is set even in the default slot when its mapping was edited.
+    // Neither overrides the other. The flag of each report is stored
+    // before its paddles are looked at, so a change applies from the
+    // report announcing it; a report too short to carry it keeps the last.
+    fn paddles_suppressed(&self) -> bool {
+        self.active_profile() != 0 || self.elite_mapping.load(Ordering::Relaxed) != 0
+    }
+
+    // Buttons held in the latest decoded state. The d-pad bits are set
+    // from the hat as well, so they don't depend on DPAD_TO_BUTTONS.
+    fn pressed_buttons(&self) -> ButtonSet {
+        let state = self.current_state();
+        let mut buttons = state.buttons;
+        buttons.set(ButtonSet::DPAD_LEFT, state.dpad.0 < 0);
+        buttons.set(ButtonSet::DPAD_RIGHT, state.dpad.0 > 0);
+        buttons.set(ButtonSet::DPAD_UP, state.dpad.1 < 0);
+        buttons.set(ButtonSet::DPAD_DOWN, state.dpad.1 > 0);
+        buttons
+    }
+
+    // Report `state` as if the pad had sent it, for macros and tools. Values
+    // are final, nothing goes through the deadzone, curve or threshold
+    // handling. Only controls that differ from the last injected state are
+    // reported. Paddles are taken from the button set.
+    fn inject_state(&self, state: &ControllerState) {
+        let mut last = self.injected.lock().unwrap();
+        let mapping = self.mapping();
+        let mut changed = false;
+
+        let mut skip = ButtonSet::empty();
+        if !mapping.contains(MapFlags::DPAD_TO_BUTTONS) {
+            skip |= ButtonSet::DPAD_LEFT | ButtonSet::DPAD_RIGHT | ButtonSet::DPAD_UP | ButtonSet::DPAD_DOWN;
+        }
+        if !mapping.intersects(MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::TRIGGERS_BOTH) {
+            skip |= ButtonSet::TL2 | ButtonSet::TR2;
+        }
+        let diff = (state.buttons ^ last.buttons) - skip;
+        for &(button, _) in BUTTON_CODES.iter() {
+            let bit = ButtonSet::from_button(button);
+            if diff.contains(bit) {
+                self.dev.report_key(button, state.buttons.contains(bit));
+                changed = true;
+            }
+        }
+
+        let mut axes = vec![];
+        if mapping.uses_analog_sticks() {
+            axes.push((AbsoluteAxis::X, state.left_stick.0, last.left_stick.0));
+            axes.push((AbsoluteAxis::Y, state.left_stick.1, last.left_stick.1));
+            axes.push((AbsoluteAxis::Rx, state.right_stick.0, last.right_stick.0));
+            axes.push((AbsoluteAxis::Ry, state.right_stick.1, last.right_stick.1));
+        }
+        if !mapping.contains(MapFlags::TRIGGERS_TO_BUTTONS) {
+            let layout = *self.trigger_axes.lock().unwrap();
+            axes.push((layout.left, state.triggers.0, last.triggers.0));
+            axes.push((layout.right, state.triggers.1, last.triggers.1));
+        }
+        if !mapping.contains(MapFlags::DPAD_TO_BUTTONS) {
+            axes.push((AbsoluteAxis::Hat0X, state.dpad.0, last.dpad.0));
+            axes.push((AbsoluteAxis::Hat0Y, state.dpad.1, last.dpad.1));
+        }
+        for (axis, value, was) in axes {
+            if value != was {
+                self.dev.report_abs(axis, value);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.dev.synchronize();
+        }
+        *last = *state;
+    }
+
+    fn diagnostics(&self) -> XpadDiagnostics {
+        XpadDiagnostics {
+            early_input_dropped: self.early_input_dropped.load(Ordering::Relaxed),
+            interface: self.interface_number,
+            product: self.product.clone(),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    // Refused for a button without an evdev code, see EvdevCode
+    fn set_share_action(&self, action: ShareAction) -> Result<(), XpadError> {
+        if let ShareAction::Button(button) = action {
+            if button.code().is_none() {
+                return Err(XpadError::UnknownButton(button));
+            }
+        }
+        *self.share_action.lock().unwrap() = action;
+        Ok(())
+    }
+
+    // A reconnected pad starts its own counters from scratch, so the output
+    // serial and the init sequence start over too
+    fn reset_sequences(&self) {
+        self.odata_serial.store(0, Ordering::SeqCst);
+        *self.init_seq.lock().unwrap() = 0;
+    }
+
+    // Forget filter history, a reconnected pad must not start from stale values
+    fn reset_filters(&self) {
+        for filter in self.smoothing.lock().unwrap().values_mut() {
+            filter.state = None;
+        }
+    }
+
+    // Buttons this pad will report, same list build_capabilities registers
+    fn emitted_buttons(&self) -> Vec<Button> {
+        xpad_capabilities(self).buttons
+    }
+
+    // Axes this pad will report, same list build_capabilities registers
+    fn emitted_axes(&self) -> Vec<AbsoluteAxis> {
+        xpad_capabilities(self).axes
+    }
+
+    // Called with true when a 360 wireless pad connects to the receiver and
+    // false when it leaves. The receiver doesn't say why a pad left, a pad
+    // turned off and one out of range look the same.
+    fn set_presence_handler(&self, handler: Box<dyn Fn(bool) + Send + Sync>) {
+        *self.presence_handler.lock().unwrap() = Some(handler);
+    }
+
+    // Last battery report, None until the pad sends one
+    fn battery(&self) -> Option<BatteryStatus> {
+        *self.battery.lock().unwrap()
+    }
+
+    fn update_battery(&self, status: BatteryStatus) {
+        if self.battery.lock().unwrap().replace(status) != Some(status) {
+            xpad_debug!("{:04x}:{:04x} battery {:?}", self.id_vendor, self.id_product, status);
+        }
+    }
+
+    // Accessories currently plugged into an Xbox One pad
+    fn accessories(&self) -> Vec<Accessory> {
+        self.accessories.lock().unwrap().clone()
+    }
+
+    // Called with the full list whenever an accessory is attached or removed
+    fn set_accessory_handler(&self, handler: Box<dyn Fn(&[Accessory]) + Send + Sync>) {
+        *self.accessory_handler.lock().unwrap() = Some(handler);
+    }
+
+    // Record an accessory attach (Some) or detach (None) on a GIP client
+    fn update_accessory(&self, client: u8, attached: Option<(u16, u16)>) {
+        let mut accessories = self.accessories.lock().unwrap();
+        let before = accessories.len();
+        accessories.retain(|a| a.client != client);
+        let removed = accessories.len() != before;
+
+        if let Some((vendor, product)) = attached {
+            accessories.push(Accessory { client, vendor, product, kind: AccessoryKind::from_ids(vendor, product) });
+            accessories.sort_by_key(|a| a.client);
+        } else if !removed {
+            return;
+        }
+
+        let snapshot = accessories.clone();
+        drop(accessories);
+        if let Some(handler) = self.accessory_handler.lock().unwrap().as_ref() {
+            handler(&snapshot);
+        }
+    }
+
+    // Capabilities from the pad's descriptor. The identify report isn't
+    // decoded yet, so this is None and the table decides.
+    fn gip_capabilities(&self) -> Option<GipCapabilities> {
+        *self.gip_caps.lock().unwrap()
+    }
+
+    // The descriptor wins over the table when the pad sent one
+    fn supports_rumble(&self) -> bool {
+        match self.gip_capabilities() {
+            Some(caps) => {
+                caps.contains(GipCapabilities::RUMBLE)
+                    && !self.quirks.intersects(QuirkFlags::NO_RUMBLE | QuirkFlags::GHL_XBOXONE)
+            },
+            None => rumble_supported(self.protocol(), self.quirks),
+        }
+    }
+
+    fn supports_trigger_rumble(&self) -> bool {
+        match self.gip_capabilities() {
+            Some(caps) => caps.contains(GipCapabilities::TRIGGER_RUMBLE) && self.supports_rumble(),
+            None => trigger_rumble_supported(self.protocol(), self.quirks, self.id_vendor, self.id_product),
+        }
+    }
+
+    fn has_trigger_rumble(&self) -> bool {
+        self.supports_trigger_rumble()
+    }
+
+    fn has_share(&self) -> bool {
+        match self.gip_capabilities() {
+            Some(caps) => caps.contains(GipCapabilities::SHARE),
+            None => self.mapping().contains(MapFlags::SELECT_BUTTON),
+        }
+    }
+
+    fn has_paddles(&self) -> bool {
+        match self.gip_capabilities() {
+            Some(caps) => caps.contains(GipCapabilities::PADDLES),
+            None => self.mapping().contains(MapFlags::PADDLES),
+        }
+    }
+
+    // Everything above in one place, from the xtype, quirks and table entry,
+    // with the capabilities descriptor taking over once the pad sent one
+    fn features(&self) -> FeatureSet {
+        let mut features = FeatureSet::empty();
+        features.set(FeatureSet::RUMBLE, self.supports_rumble());
+        features.set(FeatureSet::TRIGGER_RUMBLE, self.supports_trigger_rumble());
+        features.set(FeatureSet::PADDLES, self.has_paddles());
+        features.set(FeatureSet::SHARE, self.has_share());
+
+        match self.protocol() {
+            XType::Xbox360 => features |= FeatureSet::PLAYER_LEDS,
+            // Only the wireless pads report their battery
+            XType::Xbox360W => features |= FeatureSet::PLAYER_LEDS | FeatureSet::BATTERY,
+            XType::XboxOne if !self.quirks.contains(QuirkFlags::GHL_XBOXONE) => {
+                features |= FeatureSet::GUIDE_LED;
+                // Wired-only pads report no battery type
+                if self.battery().is_some_and(|battery| battery.charge != ChargeState::Wired) {
+                    features |= FeatureSet::BATTERY;
+                }
+            },
+            _ => (),
+        }
+        features
+    }
+}
+
+/// A pad's user settings, as exported by UsbXpad::export_settings().
+/// Buttons are stored as evdev codes and the mapping as its bits, so saved
+/// settings don't depend on this crate's type layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XpadSettings {
+    pub mapping: u16,
+    pub stick_deadzone: u16,
+    pub stick_dpad_threshold: u16,
+    pub socd: Option<SocdMode>,
+    pub remap: Vec<(u16, u16)>,
+    pub rumble_gain: u8,
+    pub default_led: Option<LedState>,
+    // Absent from settings saved before trigger inversion existed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub invert_lt: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub invert_rt: bool,
+}
+
+// Outcome of UsbXpad::refresh_from_registry()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Refresh {
+    // The tables say nothing new about this pad
+    Unchanged,
+    // The new mapping is in effect, re-register capabilities if they changed
+    MappingApplied { capabilities_changed: bool },
+    // Quirks or protocol changed, the pad has to be set up again
+    ReprobeNeeded,
+}
+
+/// Controller firmware version, from bcdDevice: major in the high byte,
+/// minor in the low one, so 0x050b is 5.11
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl FirmwareVersion {
+    fn from_bcd(bcd: u16) -> Self {
+        Self { major: (bcd >> 8) as u8, minor: bcd as u8 }
+    }
+}
+
+/// Documented problems of a firmware version that a firmware update fixes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownIssue {
+    /// Elite 2 before 5.11 sends the paddles in an older report layout,
+    /// which isn't decoded, so the paddles don't work
+    Elite2LegacyPaddleLayout,
+}
+
+// What is known to be wrong with `version` on the (vendor, product) pad,
+// for apps that want to suggest an update
+fn known_firmware_issues(version: &FirmwareVersion, product: (u16, u16)) -> Vec<KnownIssue> {
+    let mut issues = Vec::new();
+    if product == (0x045e, 0x0b00) && *version < (FirmwareVersion { major: 5, minor: 11 }) {
+        issues.push(KnownIssue::Elite2LegacyPaddleLayout);
+    }
+    issues
+}
+
+// Errors surfaced by UsbXpad operations
+#[derive(Debug)]
+enum XpadError {
+    // The device can't do what was asked
+    NotSupported,
+    // None of the device's interfaces carries controller input
+    NoInputInterface,
+    // The axis is already reported for another control
+    AxisConflict(AbsoluteAxis),
+    // The button has no evdev code the driver knows
+    UnknownButton(Button),
+    Usb(UsbError),
+}
+
+impl From<UsbError> for XpadError {
+    fn from(err: UsbError) -> Self {
+        XpadError::Usb(err)
+    }
+}
+
+// Errors worth retrying an output packet for
+fn usb_error_is_transient(err: &UsbError) -> bool {
+    matches!(err, UsbError::Stall | UsbError::Timeout)
+}
+
+// Builds a UsbXpad without any hardware behind it: events land in a
+// RecordingSink, output packets in a RecordingTransport and time comes
+// from a ManualClock, all kept on the fixture so they can be driven and
+// inspected afterwards.
+#[cfg(any(test, feature = "testing"))]
+struct XpadFixture {
+    device: XpadDevice,
+    intf_id: Option<UsbDeviceId>,
+    sink: Arc<RecordingSink>,
+    transport: Arc<RecordingTransport>,
+    clock: Arc<ManualClock>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl XpadFixture {
+    fn new(xtype: XType, mapping: MapFlags, quirks: QuirkFlags) -> Self {
+        Self {
+            device: XpadDevice {
+                id_vendor: 0x045e,
+                id_product: 0x028e,
+                name: "Test X-Box pad",
+                mapping,
+                xtype,
+                quirks,
+            },
+            intf_id: None,
+            sink: Arc::new(RecordingSink::new()),
+            transport: Arc::new(RecordingTransport::new()),
+            clock: Arc::new(ManualClock::new()),
+        }
+    }
+
+    fn ids(mut self, id_vendor: u16, id_product: u16) -> Self {
+        self.device.id_vendor = id_vendor;
+        self.device.id_product = id_product;
+        self
+    }
+
+    fn name(mut self, name: &'static str) -> Self {
+        self.device.name = name;
+        self
+    }
+
+    fn interface(mut self, intf_id: UsbDeviceId) -> Self {
+        self.intf_id = Some(intf_id);
+        self
+    }
+
+    fn build(&self) -> UsbXpad {
+        UsbXpad::new(
+            &self.device,
+            self.sink.clone(),
+            self.transport.clone(),
+            self.intf_id,
+            self.clock.clone(),
+        )
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl UsbXpad {
+    fn test_fixture(xtype: XType, mapping: MapFlags, quirks: QuirkFlags) -> UsbXpad {
+        XpadFixture::new(xtype, mapping, quirks).build()
+    }
+}
+
+// The ack a pad sends back for `packet`, an output packet that asked for
+// one with GIP_OPT_ACK. Same layout as XPADONE_MODE_REPORT_ACK: our
+// sequence number echoed, then the command and options being acked.
+#[cfg(any(test, feature = "testing"))]
+fn gip_ack_for(packet: &[u8]) -> Vec<u8> {
+    let mut ack = XPADONE_MODE_REPORT_ACK.to_vec();
+    ack[2] = packet[2];
+    ack[5] = packet[0];
+    ack[6] = packet[1] & !GIP_OPT_ACK;
+    ack
+}
+
+// Where a replay corpus and our decoders part ways
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug)]
+enum CorpusError {
+    Io(std::io::Error),
+    Parse { line: usize, reason: String },
+    // First event, in (type, code) order, that differs in the frame fed at
+    // `line`. None on one side means the event is missing there.
+    Mismatch { line: usize, expected: Option<SinkEvent>, got: Option<SinkEvent> },
+}
+
+// Frame events the way evdev delivers them: one value per (type, code),
+// the last one reported, and only if it changed since the previous frame
+#[cfg(any(test, feature = "testing"))]
+fn corpus_frame(events: &[SinkEvent], last: &mut BTreeMap<(u8, u16), i32>) -> BTreeMap<(u8, u16), SinkEvent> {
+    let mut frame = BTreeMap::new();
+    for &event in events {
+        let key = match event {
+            SinkEvent::Key(button, _) => (1, button.code().expect("decoders only emit buttons with codes")),
+            SinkEvent::Abs(axis, _) => (3, axis.code().expect("decoders only emit axes with codes")),
+            SinkEvent::Scan(_) | SinkEvent::Sync => continue,
+        };
+        frame.insert(key, event);
+    }
+    frame.retain(|key, event| {
+        let value = match *event {
+            SinkEvent::Key(_, pressed) => pressed as i32,
+            SinkEvent::Abs(_, value) => value,
+            _ => unreachable!(),
+        };
+        last.insert(*key, value).unwrap_or(0) != value
+    });
+    frame
+}
+
+// Replay a corpus of input reports and check our decoders produce the
+// events the Linux xpad driver does for them. Returns the number of
+// frames checked.
+//
+// The corpus is plain text, one directive per line, '#' starts a comment:
+//
+//   pad 360|one|xbox    start over with a fresh pad of that protocol,
+//     [preset]          optionally with a mapping preset() by name,
+//     [vvvv:pppp]       other ids than the protocol's Microsoft pad
+//     [fw=bcd]          and a bcdDevice, which picks the Elite report format
+//   in 00 14 00 10 ...  feed one report, bytes in hex
+//   key 0x130 1         expect EV_KEY code/value from the last report
+//   abs 0x01 -1         expect EV_ABS code/value from the last report
+//
+// Expected events are what evtest shows for the report under Linux, so
+// only controls that changed are listed and SYN_REPORT is implied. Order within a frame
+// doesn't matter. The golden-*.txt files use the same format to pin what
+// each mapping preset makes of a report with every input held.
+#[cfg(any(test, feature = "testing"))]
+fn run_corpus(path: &std::path::Path) -> std::result::Result<usize, CorpusError> {
+    let text = std::fs::read_to_string(path).map_err(CorpusError::Io)?;
+
+    let mut fixture = None;
+    let mut last = BTreeMap::new();
+    let mut frame: Option<(usize, BTreeMap<(u8, u16), SinkEvent>)> = None;
+    let mut expected = BTreeMap::new();
+    let mut frames = 0;
+
+    // Compare the pending frame against what the corpus listed for it
+    let check = |frame: &Option<(usize, BTreeMap<(u8, u16), SinkEvent>)>,
+                 expected: &BTreeMap<(u8, u16), SinkEvent>| {
+        let Some((line, got)) = frame else { return Ok(()) };
+        let keys: std::collections::BTreeSet<_> = got.keys().chain(expected.keys()).collect();
+        for key in keys {
+            let (expected, got) = (expected.get(key).copied(), got.get(key).copied());
+            if expected != got {
+                return Err(CorpusError::Mismatch { line: *line, expected, got });
+            }
+        }
+        Ok(())
+    };
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let parse = |reason: &str| CorpusError::Parse { line, reason: reason.to_string() };
+        let mut words = raw.split('#').next().unwrap().split_whitespace();
+        let Some(directive) = words.next() else { continue };
+
+        match directive {
+            "pad" | "in" => {
+                check(&frame, &expected)?;
+                frames += frame.take().is_some() as usize;
+                expected.clear();
+            },
+            _ => (),
+        }
+
+        match directive {
+            "pad" => {
+                let (xtype, mut ids) = match words.next() {
+                    Some("360") => (XType::Xbox360, (0x045e, 0x028e)),
+                    Some("one") => (XType::XboxOne, (0x045e, 0x02ea)),
+                    Some("xbox") => (XType::Xbox, (0x045e, 0x0202)),
+                    _ => return Err(parse("expected pad 360, one or xbox")),
+                };
+                let mut mapping = MapFlags::empty();
+                let mut firmware = None;
+                for word in words {
+                    let hex = |s: &str| u16::from_str_radix(s, 16).ok();
+                    if let Some(flags) = preset(word) {
+                        mapping = flags;
+                    } else if let Some((vendor, product)) = word.split_once(':') {
+                        ids = hex(vendor).zip(hex(product)).ok_or_else(|| parse("bad vendor:product"))?;
+                    } else if let Some(bcd) = word.strip_prefix("fw=") {
+                        firmware = Some(hex(bcd).ok_or_else(|| parse("bad firmware version"))?);
+                    } else {
+                        return Err(parse("expected a preset, vendor:product or fw=bcd"));
+                    }
+                }
+                let pad = XpadFixture::new(xtype, mapping, QuirkFlags::empty()).ids(ids.0, ids.1);
+                let mut xpad = pad.build();
+                if let Some(firmware) = firmware {
+                    xpad = xpad.with_serial_and_firmware(None, firmware);
+                }
+                // The corpus holds input reports only. Go through the
+                // handshake the way a pad would, acking the last init packet.
+                xpad.reinitialize().map_err(|_| parse("init sequence failed"))?;
+                if let Some(last) = pad.transport.take().last() {
+                    if last[1] & GIP_OPT_ACK != 0 {
+                        xpad.decode(&gip_ack_for(last));
+                    }
+                }
+                pad.sink.take();
+                fixture = Some((pad, xpad));
+                last.clear();
+            },
+            "in" => {
+                let (pad, xpad) = fixture.as_ref().ok_or_else(|| parse("report before any pad"))?;
+                let data = words
+                    .map(|byte| u8::from_str_radix(byte, 16))
+                    .collect::<std::result::Result<Vec<u8>, _>>()
+                    .map_err(|_| parse("bad hex byte"))?;
+                xpad.decode(&data);
+                frame = Some((line, corpus_frame(&pad.sink.take(), &mut last)));
+            },
+            "key" | "abs" => {
+                if frame.is_none() {
+                    return Err(parse("expected event before any report"));
+                }
+                let code = words.next().and_then(|code| u16::from_str_radix(code.trim_start_matches("0x"), 16).ok());
+                let value = words.next().and_then(|value| value.parse::<i32>().ok());
+                let (Some(code), Some(value)) = (code, value) else {
+                    return Err(parse("expected a hex code and a value"));
+                };
+                let event = match directive {
+                    "key" => Button::from_code(code).map(|button| ((1, code), SinkEvent::Key(button, value != 0))),
+                    _ => AbsoluteAxis::from_code(code).map(|axis| ((3, code), SinkEvent::Abs(axis, value))),
+                };
+                let (key, event) = event.ok_or_else(|| parse("code the driver doesn't use"))?;
+                expected.insert(key, event);
+            },
+            _ => return Err(parse("unknown directive")),
+        }
+    }
+    check(&frame, &expected)?;
+    Ok(frames + frame.is_some() as usize)
+}
+
+#[cfg(test)]
+mod tests;
+
+// Advertised ABS_Z/ABS_RZ maximum, triggers are 8-bit on 360 and 10-bit on Xbox One
+fn trigger_abs_max(xtype: XType) -> i32 {
+    match xtype {
+        XType::XboxOne => 1023,
+        _ => 255,
+    }
+}
+
+// Buttons and axes a configured pad reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Capabilities {
+    buttons: Vec<Button>,
+    axes: Vec<AbsoluteAxis>,
+    // MSC_SCAN has to be advertised
+    scancodes: bool,
+}
+
+// Everything the decoders may emit for this pad, from its resolved xtype,
+// mapping and quirks. Mirrors the XPAD_* code lists.
+fn xpad_capabilities(xpad: &UsbXpad) -> Capabilities {
+    let xtype = xpad.protocol();
+    let mapping = xpad.mapping();
+
+    let mut buttons = XPAD_COMMON_BTN.to_vec();
+    let mut axes = Vec::new();
+
+    match xtype {
+        XType::Xbox360 | XType::Xbox360W | XType::XboxOne => buttons.extend(XPAD360_BTN),
+        _ => buttons.extend(XPAD_BTN),
+    }
+
+    if xpad.has_share() {
+        if let ShareAction::Button(button) = *xpad.share_action.lock().unwrap() {
+            if !buttons.contains(&button) {
+                buttons.push(button);
+            }
+        }
+    }
+    if mapping.contains(MapFlags::MODE_AS_CHORD) {
+        buttons.retain(|&button| button != Button::Mode);
+        for button in [Button::Start, Button::Select] {
+            if !buttons.contains(&button) {
+                buttons.push(button);
+            }
+        }
+    }
+    if mapping.contains(MapFlags::PADDLES) {
+        buttons.extend(XPAD_BTN_PADDLES);
+    }
+    if mapping.contains(MapFlags::PROFILE_BUTTON) {
+        axes.push(AbsoluteAxis::Profile);
+    }
+
+    if mapping.uses_analog_sticks() {
+        axes.extend(XPAD_ABS);
+    }
+
+    if mapping.contains(MapFlags::DPAD_TO_BUTTONS) {
+        buttons.extend(XPAD_BTN_PAD);
+    } else {
+        axes.extend(XPAD_ABS_PAD);
+    }
+    if xpad.quirks.contains(QuirkFlags::GHL_XBOXONE) && xpad.ghl_strum_buttons.load(Ordering::Relaxed) {
+        axes.retain(|&axis| axis != AbsoluteAxis::Hat0Y);
+        for button in [Button::TriggerHappy1, Button::TriggerHappy2] {
+            if !buttons.contains(&button) {
+                buttons.push(button);
+            }
+        }
+    }
+
+    if mapping.intersects(MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::TRIGGERS_BOTH) {
+        buttons.extend(XPAD_BTN_TRIGGERS);
+    }
+    if !mapping.contains(MapFlags::TRIGGERS_TO_BUTTONS) {
+        let layout = *xpad.trigger_axes.lock().unwrap();
+        axes.extend([layout.left, layout.right]);
+    }
+
+    // Linux keeps these lists as -1 terminated arrays, where a missing or
+    // stray terminator silently truncates them. The typed arrays here carry
+    // their length in the type, so there is nothing to parse; what can
+    // still go wrong is two lists overlapping and advertising a code twice.
+    debug_assert!(buttons.iter().enumerate().all(|(i, b)| !buttons[i + 1..].contains(b)),
+                  "duplicate button in capabilities: {:?}", buttons);
+    debug_assert!(axes.iter().enumerate().all(|(i, a)| !axes[i + 1..].contains(a)),
+                  "duplicate axis in capabilities: {:?}", axes);
+
+    let scancodes = xpad.scancodes.load(Ordering::Relaxed);
+    Capabilities { buttons, axes, scancodes }
+}
+
+// Advertised (min, max) of an axis on this pad
+fn abs_range(xpad: &UsbXpad, axis: AbsoluteAxis) -> (i32, i32) {
+    if xpad.trigger_axes.lock().unwrap().contains(axis) {
+        return (0, trigger_abs_max(xpad.protocol()));
+    }
+    match axis {
+        AbsoluteAxis::X | AbsoluteAxis::Y | AbsoluteAxis::Rx | AbsoluteAxis::Ry => (-32768, 32767),
+        AbsoluteAxis::Z | AbsoluteAxis::Rz => (0, trigger_abs_max(xpad.protocol())),
+        AbsoluteAxis::Hat0X | AbsoluteAxis::Hat0Y => (-1, 1),
+        AbsoluteAxis::Profile => (0, 4),
+        _ => (0, 255),
+    }
+}
+
+// Register the pad's buttons and axes on its input device
+fn build_capabilities(xpad: &UsbXpad, input: &InputDevice) -> Result<(), kernel::Error> {
+    let caps = xpad_capabilities(xpad);
+
+    for button in caps.buttons {
+        input.set_keybit(button)?;
+    }
+    for axis in caps.axes {
+        let (min, max) = abs_range(xpad, axis);
+        let (fuzz, flat) = match axis {
+            AbsoluteAxis::X | AbsoluteAxis::Y | AbsoluteAxis::Rx | AbsoluteAxis::Ry => (16, 128),
+            _ => (0, 0),
+        };
+        input.set_abs_params(axis, min, max, fuzz, flat)?;
+    }
+    if caps.scancodes {
+        input.set_evbit(input::EventType::Misc)?;
+        input.set_mscbit(MiscKind::Scancode)?;
+    }
+    Ok(())
+}
+
+// Per-axis options applied before an axis is reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AxisConfig {
+    // Report the trigger axis upside down, max released and 0 fully pressed.
+    // Triggers reported as buttons aren't affected.
+    invert_lt: bool,
+    invert_rt: bool,
+}
+
+// Scale a `bits` wide raw trigger reading to the advertised axis range
+fn trigger_abs_value(xpad: &UsbXpad, raw: u16, bits: u32) -> i32 {
+    let raw_max = (1u32 << bits) - 1;
+    let abs_max = trigger_abs_max(xpad.protocol()) as u32;
+    ((raw as u32).min(raw_max) * abs_max / raw_max) as i32
+}
+
+// TRIGGERS_BOTH only makes sense with the axes, TRIGGERS_TO_BUTTONS wins
+fn normalize_mapping(mapping: MapFlags) -> MapFlags {
+    if mapping.contains(MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::TRIGGERS_BOTH) {
+        log::warn!("TRIGGERS_BOTH ignored, TRIGGERS_TO_BUTTONS is set");
+        return mapping - MapFlags::TRIGGERS_BOTH;
+    }
+    mapping
+}
+
+// Report both triggers from `bits` wide raw values: as axes, as buttons
+// (threshold compared on the top 8 bits), or both with TRIGGERS_BOTH
+fn report_triggers(xpad: &UsbXpad, dev: &dyn InputSink, (lt, rt): (u16, u16), bits: u32) {
+    let mapping = xpad.mapping();
+
+    if mapping.intersects(MapFlags::TRIGGERS_TO_BUTTONS | MapFlags::TRIGGERS_BOTH) {
+        let shift = bits.saturating_sub(8);
+        dev.report_key(Button::TL2, trigger_button(xpad, 0, (lt >> shift) as u8));
+        dev.report_key(Button::TR2, trigger_button(xpad, 1, (rt >> shift) as u8));
+    }
+    if !mapping.contains(MapFlags::TRIGGERS_TO_BUTTONS) {
+        let layout = *xpad.trigger_axes.lock().unwrap();
+        let config = *xpad.axis_config.lock().unwrap();
+        let max = trigger_abs_max(xpad.protocol());
+        let value = |raw, invert| match trigger_abs_value(xpad, raw, bits) {
+            value if invert => max - value,
+            value => value,
+        };
+        report_abs_clamped(xpad, dev, layout.left, value(lt, config.invert_lt));
+        report_abs_clamped(xpad, dev, layout.right, value(rt, config.invert_rt));
+    }
+}
+
+// Digital state of trigger `idx` (0 = left, 1 = right) for an 8-bit trigger value.
+// Once pressed, the trigger has to drop TRIGGER_HYSTERESIS below the threshold
+// to release, so values hovering around the edge don't chatter.
+fn trigger_button(xpad: &UsbXpad, idx: usize, value: u8) -> bool {
+    let was_pressed = xpad.trigger_pressed[idx].load(Ordering::Relaxed);
+    let pressed = if was_pressed {
+        value > xpad.trigger_threshold.saturating_sub(TRIGGER_HYSTERESIS)
+    } else {
+        value > xpad.trigger_threshold
+    };
+    xpad.trigger_pressed[idx].store(pressed, Ordering::Relaxed);
+    pressed
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -2805,42 +6477,301 @@ fn xpad360w_process_packet(xpad: &UsbXpad, data: &[u8]) {
     if data[0] & 0x08 != 0 {
         let present = data[1] & 0x80 != 0;
         if xpad.pad_present.swap(present, Ordering::SeqCst) != present {
-            // Schedule work for presence change
-            // (Would typically use a channel or async task here)
+            xpad.reset_filters();
+            if present {
+                xpad.reset_sequences();
+            }
+
+            if let Some(handler) = xpad.presence_handler.lock().unwrap().as_ref() {
+                handler(present);
+            }
         }
     }
 
+    if data[1] == 0x00 && data.len() > 4 && data[3] == XPAD360W_BATTERY_REPORT {
+        xpad.update_battery(BatteryStatus::from_360w(data[4]));
+        return;
+    }
+
     // Process valid pad data
     if data[1] == 0x01 && data.len() >= 4 {
-        let dev = xpad.dev.clone();
-        xpad360_process_packet(&dev, &data[4..]);
+        if let Err(err) = process_packet(xpad, &data[4..]) {
+            xpad_debug!("Dropped wireless report: {:?}", err);
+        }
+    }
+}
+
+/// Whether the battery is being charged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeState {
+    Discharging,
+    Charging,
+    /// Running off USB without a battery
+    Wired,
+    /// The protocol doesn't say
+    Unknown,
+}
+
+/// Battery of a wireless pad, the same whichever protocol reported it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    pub percent: u8,
+    pub charge: ChargeState,
+}
+
+impl BatteryStatus {
+    // 360 receivers give the level as 0-255 and nothing on charging
+    fn from_360w(level: u8) -> Self {
+        Self {
+            percent: (u16::from(level) * 100 / 255) as u8,
+            charge: ChargeState::Unknown,
+        }
+    }
+
+    // Xbox One pads only report four steps
+    fn from_gip(status: u8) -> Self {
+        const LEVELS: [u8; 4] = [5, 25, 60, 100];
+
+        let charge = if status & GIP_BATTERY_TYPE_MASK == 0 {
+            ChargeState::Wired
+        } else if status & GIP_BATTERY_CHARGING != 0 {
+            ChargeState::Charging
+        } else {
+            ChargeState::Discharging
+        };
+        Self {
+            percent: LEVELS[usize::from(status & GIP_BATTERY_LEVEL_MASK)],
+            charge,
+        }
+    }
+}
+
+// The pad repeats a virtual key report until it is acked, only forward
+// actual changes so a repeat isn't reported as another press.
+fn xpadone_report_guide(xpad: &UsbXpad, dev: &dyn InputSink, pressed: bool) {
+    if xpad.guide_pressed.swap(pressed, Ordering::SeqCst) != pressed {
+        report_guide(xpad, dev, pressed);
+    }
+}
+
+// Guide long-press handler and how long the guide has to be held for it
+struct GuideLongPress {
+    threshold: Duration,
+    handler: Box<dyn Fn() + Send>,
+}
+
+// Guide held since `since`; `fired` once the long-press handler ran for it
+#[derive(Debug, Clone, Copy)]
+struct GuideHold {
+    since: Duration,
+    fired: bool,
+}
+
+// Every guide report goes through here. Without a long-press handler the
+// button is passed straight on. With one, the press is held back: released
+// before the threshold it is reported as a tap, held past it the handler
+// runs instead and the guide isn't reported at all.
+fn report_guide(xpad: &UsbXpad, dev: &dyn InputSink, pressed: bool) {
+    if xpad.guide_longpress.lock().unwrap().is_none() {
+        dev.report_key(Button::Mode, pressed);
+        return;
+    }
+
+    // The Guide timer fires the handler while the guide is held without
+    // new reports; repeated reports of the press check it as well
+    let mut hold = xpad.guide_hold.lock().unwrap();
+    match (*hold, pressed) {
+        (None, true) => {
+            *hold = Some(GuideHold { since: xpad.clock.now(), fired: false });
+            drop(hold);
+            xpad.kick_timer(PadTimer::Guide);
+        },
+        (Some(_), true) => {
+            drop(hold);
+            xpad_guide_tick(xpad);
+        },
+        (Some(GuideHold { fired, .. }), false) => {
+            *hold = None;
+            drop(hold);
+            xpad.cancel_timer(PadTimer::Guide);
+            if !fired {
+                dev.report_key(Button::Mode, true);
+                dev.synchronize();
+                dev.report_key(Button::Mode, false);
+            }
+        },
+        (None, false) => (),
+    }
+}
+
+// Run the long-press handler once the guide has been held long enough.
+// Returns how long until it is due, None when no press is pending.
+fn xpad_guide_tick(xpad: &UsbXpad) -> Option<Duration> {
+    let longpress = xpad.guide_longpress.lock().unwrap();
+    let longpress = longpress.as_ref()?;
+    let mut hold = xpad.guide_hold.lock().unwrap();
+    let pending = hold.as_mut().filter(|hold| !hold.fired)?;
+
+    let held = xpad.clock.now().saturating_sub(pending.since);
+    if held < longpress.threshold {
+        return Some(longpress.threshold - held);
+    }
+    pending.fired = true;
+    drop(hold);
+    (longpress.handler)();
+    None
+}
+
+// Ack for a virtual key (mode) report, the pad stops sending input if the
+// host doesn't acknowledge these. `seq` is echoed from the report.
+const XPADONE_MODE_REPORT_ACK: [u8; 13] = [
+    GIP_CMD_ACK, GIP_OPT_INTERNAL, 0x00, 0x09,
+    0x00, GIP_CMD_VIRTUAL_KEY, GIP_OPT_INTERNAL, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+fn xpadone_ack_mode_report(xpad: &UsbXpad, seq: u8) {
+    let mut packet = XPADONE_MODE_REPORT_ACK;
+    packet[2] = seq;
+
+    if let Err(err) = xpad.send_output_packet(&packet) {
+        log::warn!("Failed to ack mode report {}: {:?}", seq, err);
+    }
+}
+
+// Apply the configured ShareAction to a decoded share state
+fn xpad_report_share(xpad: &UsbXpad, dev: &dyn InputSink, pressed: bool) {
+    let changed = xpad.share_pressed.swap(pressed, Ordering::SeqCst) != pressed;
+
+    match &*xpad.share_action.lock().unwrap() {
+        ShareAction::Button(button) => dev.report_key(*button, pressed),
+        ShareAction::Ignore => (),
+        ShareAction::Callback(callback) => {
+            if changed {
+                callback(pressed);
+            }
+        },
+    }
+}
+
+// Racing wheels speaking GIP, decoded by xpadone_process_wheel()
+const XBOXONE_WHEELS: [(u16, u16); 1] = [
+    (0x0f0d, 0x0151), // Hori Racing Wheel Overdrive
+];
+
+fn is_xboxone_wheel(id_vendor: u16, id_product: u16) -> bool {
+    XBOXONE_WHEELS.contains(&(id_vendor, id_product))
+}
+
+// Wheel input report. The wheel keeps the gamepad report layout that Linux
+// xpadone_process_packet() decodes: buttons and d-pad in bytes 4-5, brake
+// and throttle (10-bit) at 6 and 8 where a pad has LT and RT, steering at
+// 10 where a pad has the left stick X. We have no capture from the wheel
+// itself, so this rests on the pedals being sent as triggers.
+const XPADONE_WHEEL_REPORT_LEN: usize = 12;
+
+fn xpadone_process_wheel(xpad: &UsbXpad, dev: &dyn InputSink, data: &[u8]) {
+    if data.len() < XPADONE_WHEEL_REPORT_LEN {
+        xpad_debug!("Dropped short wheel report: {} bytes", data.len());
+        return;
     }
+
+    dev.report_key(Button::Start, data[4] & 0x04 != 0);
+    dev.report_key(Button::Select, data[4] & 0x08 != 0);
+    dev.report_key(Button::A, data[4] & 0x10 != 0);
+    dev.report_key(Button::B, data[4] & 0x20 != 0);
+    dev.report_key(Button::X, data[4] & 0x40 != 0);
+    dev.report_key(Button::Y, data[4] & 0x80 != 0);
+    dev.report_key(Button::TL, data[5] & 0x10 != 0);
+    dev.report_key(Button::TR, data[5] & 0x20 != 0);
+    report_dpad(xpad, dev, data[5], (0, 0));
+
+    let steering = i16::from_le_bytes([data[10], data[11]]);
+    let brake = u16::from_le_bytes([data[6], data[7]]);
+    let throttle = u16::from_le_bytes([data[8], data[9]]);
+    report_abs_clamped(xpad, dev, AbsoluteAxis::X, steering.into());
+    report_abs_clamped(xpad, dev, AbsoluteAxis::Z, trigger_abs_value(xpad, brake, 10));
+    report_abs_clamped(xpad, dev, AbsoluteAxis::Rz, trigger_abs_value(xpad, throttle, 10));
 }
 
 // Xbox One packet processing
 fn xpadone_process_packet(xpad: &UsbXpad, data: &[u8]) {
-    let dev = xpad.dev.clone();
-    let mut do_sync = false;
+    let frame = FrameSink::new(xpad.dev.clone());
+    let dev = &frame;
+
+    // Reports that arrive mid-handshake are garbage or stale, drop them
+    if matches!(data[0], GIP_CMD_INPUT | GIP_CMD_FIRMWARE | 0x21)
+        && xpad.init_state() != InitState::Ready {
+        xpad.early_input_dropped.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
 
     match data[0] {
-        GIP_CMD_VIRTUAL_KEY => {
+        GIP_CMD_ANNOUNCE => {
+            let client = data[1] & GIP_CLIENT_MASK;
+            if client == 0 {
+                xpad.advance_init(InitState::Announced);
+            } else if data.len() >= GIP_ANNOUNCE_PRODUCT_OFFSET + 2 {
+                let vendor = u16::from_le_bytes([data[GIP_ANNOUNCE_VENDOR_OFFSET], data[GIP_ANNOUNCE_VENDOR_OFFSET + 1]]);
+                let product = u16::from_le_bytes([data[GIP_ANNOUNCE_PRODUCT_OFFSET], data[GIP_ANNOUNCE_PRODUCT_OFFSET + 1]]);
+                xpad.update_accessory(client, Some((vendor, product)));
+            }
+        },
+        GIP_CMD_STATUS => {
+            let client = data[1] & GIP_CLIENT_MASK;
+            if client == 0 && data.len() > 4 {
+                xpad.update_battery(BatteryStatus::from_gip(data[4]));
+            } else if client != 0 && data.len() > 4 && data[4] & GIP_STATUS_CONNECTED == 0 {
+                xpad.update_accessory(client, None);
+            }
+        },
+        GIP_CMD_ACK => {
+            // Only the ack for the last init packet completes the handshake
+            if xpad.init_state() == InitState::Configured {
+                xpad.advance_init(InitState::Ready);
+            }
+        },
+        GIP_CMD_IDENTIFY => {
+            // The descriptor comes in chunks and isn't decoded yet, so
+            // gip_caps stays None and the table defaults apply
+            xpad_debug!("Ignored {}-byte identify report", data.len());
+        },
+        GIP_CMD_VIRTUAL_KEY if data.len() > 4 => {
             if data[1] == (GIP_OPT_ACK | GIP_OPT_INTERNAL) {
                 xpadone_ack_mode_report(xpad, data[2]);
             }
-            dev.report_key(Button::Mode, data[4] & 0x03 != 0);
-            do_sync = true;
+            xpadone_report_guide(xpad, &*dev, data[4] & 0x03 != 0);
         },
         GIP_CMD_FIRMWARE => {
-            if xpad.packet_type == PacketType::Xbe2Fw5_11 {
-                let buttons = if data[19] != 0 { 0 } else { data[18] };
+            if xpad.packet_type == PacketType::Xbe2Fw5_11 && data.len() > GIP_ELITE_PADDLES_OFFSET {
+                if let Some(&mapping) = data.get(GIP_ELITE_MAPPING_OFFSET) {
+                    xpad.elite_mapping.store(mapping, Ordering::Relaxed);
+                }
+                let buttons = if xpad.paddles_suppressed() { 0 } else { data[GIP_ELITE_PADDLES_OFFSET] };
                 dev.report_key(Button::TriggerHappy5, buttons & 0x01 != 0);
                 dev.report_key(Button::TriggerHappy6, buttons & 0x02 != 0);
                 dev.report_key(Button::TriggerHappy7, buttons & 0x04 != 0);
                 dev.report_key(Button::TriggerHappy8, buttons & 0x08 != 0);
-                do_sync = true;
             }
         },
+        GIP_CMD_INPUT if is_xboxone_wheel(xpad.id_vendor, xpad.id_product) => {
+            xpadone_process_wheel(xpad, &*dev, data);
+        },
         GIP_CMD_INPUT => {
+            // Only decode the fields this report is long enough to carry
+            let len = data.len();
+            if len < XPADONE_BUTTONS_LEN {
+                xpad_debug!("Dropped short input report: {} bytes", len);
+                return;
+            }
+
+            // Old Elite 2 firmware names the profile slot in every report
+            if xpad.packet_type == PacketType::Xbe2FwOld {
+                if let Some(&profile) = data.get(GIP_ELITE_PROFILE_OFFSET) {
+                    xpad.active_profile.store(profile, Ordering::Relaxed);
+                }
+            }
+
             // Main input processing
             dev.report_key(Button::Start, data[4] & 0x04 != 0);
             dev.report_key(Button::Select, data[4] & 0x08 != 0);
@@ -2851,122 +6782,439 @@ fn xpadone_process_packet(xpad: &UsbXpad, data: &[u8]) {
             dev.report_key(Button::X, data[4] & 0x40 != 0);
             dev.report_key(Button::Y, data[4] & 0x80 != 0);
 
-            // D-pad handling
-            if xpad.mapping.contains(MapFlags::DPAD_TO_BUTTONS) {
-                dev.report_key(Button::TriggerHappy1, data[5] & 0x04 != 0);
-                dev.report_key(Button::TriggerHappy2, data[5] & 0x08 != 0);
-                dev.report_key(Button::TriggerHappy3, data[5] & 0x01 != 0);
-                dev.report_key(Button::TriggerHappy4, data[5] & 0x02 != 0);
-            } else {
-                let hat_x = (data[5] & 0x08 != 0) as i32 - (data[5] & 0x04 != 0) as i32;
-                let hat_y = (data[5] & 0x02 != 0) as i32 - (data[5] & 0x01 != 0) as i32;
-                dev.report_abs(AbsoluteAxis::Hat0X, hat_x);
-                dev.report_abs(AbsoluteAxis::Hat0Y, hat_y);
-            }
+            // Bumpers and stick clicks share data[5] with the d-pad nibble
+            dev.report_key(Button::TL, data[5] & 0x10 != 0);
+            dev.report_key(Button::TR, data[5] & 0x20 != 0);
+            dev.report_key(Button::ThumbL, data[5] & 0x40 != 0);
+            dev.report_key(Button::ThumbR, data[5] & 0x80 != 0);
+
+            // D-pad handling
+            let left_stick = if len >= XPADONE_LEFT_STICK_LEN {
+                (i16::from_le_bytes([data[10], data[11]]), i16::from_le_bytes([data[12], data[13]]))
+            } else {
+                (0, 0)
+            };
+            report_dpad(xpad, &*dev, data[5], left_stick);
+
+            // Only firmwares with a share button send the byte for it, and
+            // it always comes after the right stick
+            if xpad.has_share() {
+                let from_end = if xpad.mapping().contains(MapFlags::SHARE_OFFSET) {
+                    GIP_SHARE_FROM_END_OFFSET
+                } else {
+                    GIP_SHARE_FROM_END
+                };
+                if len >= XPADONE_REPORT_LEN + from_end {
+                    xpad_report_share(xpad, &*dev, data[len - from_end] & 0x01 != 0);
+                }
+            }
+
+            // Triggers are 10-bit on Xbox One, compare the threshold on the top 8 bits
+            if len >= XPADONE_TRIGGERS_LEN {
+                let lt = u16::from_le_bytes([data[6], data[7]]);
+                let rt = u16::from_le_bytes([data[8], data[9]]);
+                report_triggers(xpad, &*dev, (lt, rt), 10);
+            }
+
+            // Sticks
+            if xpad.mapping().uses_analog_sticks() {
+                if len >= XPADONE_LEFT_STICK_LEN {
+                    report_stick(xpad, &*dev, Stick::Left, (left_stick.0.into(), invert_axis(left_stick.1).into()));
+                }
+                if len >= XPADONE_REPORT_LEN {
+                    let rx = i16::from_le_bytes([data[14], data[15]]);
+                    let ry = i16::from_le_bytes([data[16], data[17]]);
+                    report_stick(xpad, &*dev, Stick::Right, (rx.into(), invert_axis(ry).into()));
+                }
+            }
+        },
+        0x21 => {
+            // GHL guitar processing
+            let dpad_value = data[6] & 0x0F;
+            let (x, y) = DPAD_MAPPING[dpad_value.min(8) as usize];
+            report_abs_clamped(xpad, &*dev, AbsoluteAxis::Hat0X, x);
+            if xpad.ghl_strum_buttons.load(Ordering::Relaxed) {
+                dev.report_key(Button::TriggerHappy1, y < 0);
+                dev.report_key(Button::TriggerHappy2, y > 0);
+            } else {
+                report_abs_clamped(xpad, &*dev, AbsoluteAxis::Hat0Y, y);
+            }
+        },
+        _ => (),
+    }
+
+    frame.finish();
+}
+
+impl XpadDriver {
+    fn process_packet(&self, data: &[u8]) {
+        let dev = &self.input_dev;
+        
+        // Common button processing
+        dev.report_key(input::Key::ButtonSouth, data[4] & 0x10 != 0);
+        dev.report_key(input::Key::ButtonEast, data[4] & 0x20 != 0);
+        
+        // Analog stick handling
+        if !self.mapping.contains(MapFlags::STICKS_TO_NULL) {
+            let x = i16::from_le_bytes([data[12], data[13]]);
+            dev.report_abs(input::AbsoluteAxis::X, x.into());
+        }
+        
+        dev.synchronize();
+    }
+}
+
+impl XpadDriver {
+    fn setup_urbs(&self) -> Result<()> {
+        let mut urb_in = usb::Urb::new_interrupt(
+            &self.udev,
+            self.interface.cur_altsetting().endpoint_in(0)?,
+            64,
+        )?;
+        
+        urb_in.set_completion(|urb| {
+            let driver = urb.context::<XpadDriver>();
+            driver.process_packet(urb.data());
+            urb.submit().unwrap();
+        });
+        
+        urb_in.submit()?;
+        Ok(())
+    }
+}
+
+// URB completion handler
+fn xpad_irq_in(urb: &Urb, xpad: Arc<UsbXpad>) -> Result<(), UsbError> {
+    xpad.in_completed(urb.status(), urb.buffer(), urb)
+}
+
+// Resubmit the input URB held back by xpad_irq_in() once the poll interval
+// has passed, or right away if polling was turned off meanwhile. Returns
+// how long until it's due, None if nothing is pending. If the resubmit
+// fails no more reports arrive, and the watchdog releases the controls.
+fn xpad_poll_tick(xpad: &UsbXpad) -> Option<Duration> {
+    if xpad.is_disconnected() {
+        return None;
+    }
+    let mut resubmit_at = xpad.resubmit_at.lock().unwrap();
+    let due = (*resubmit_at)?;
+    let now = xpad.clock.now();
+    let polling = xpad.poll_interval.lock().unwrap().is_some();
+    if polling && now < due {
+        return Some(due - now);
+    }
+    *resubmit_at = None;
+    drop(resubmit_at);
+
+    let irq_in = xpad.irq_in.lock().unwrap().clone();
+    if let Some(Err(err)) = irq_in.map(|irq_in| irq_in.resubmit()) {
+        log::warn!("Failed to resubmit the input URB: {:?}", err);
+    }
+    None
+}
+
+// Everything needed to drive one kind of controller. Registered profiles
+// are tried before the built-in ones, so a controller can be supported (or
+// a built-in overridden) without touching the decoders.
+trait DeviceProfile: Send + Sync {
+    fn matches(&self, xpad: &UsbXpad) -> bool;
+
+    // Decode one input report, events go to `sink`
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], sink: &dyn InputSink);
+
+    // Output packets sent, in order, to bring the controller up
+    fn init_packets(&self, _xpad: &UsbXpad) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+}
+
+static PROFILES: Mutex<Vec<Arc<dyn DeviceProfile>>> = Mutex::new(Vec::new());
+
+// Add a profile, it takes precedence over those registered earlier.
+// Only pads created afterwards pick it up.
+fn register_profile(profile: Arc<dyn DeviceProfile>) {
+    PROFILES.lock().unwrap().insert(0, profile);
+}
+
+// First registered profile that claims the pad, then the built-ins.
+// XboxProfile matches anything, so this always finds one.
+fn resolve_profile(xpad: &UsbXpad) -> Arc<dyn DeviceProfile> {
+    if let Some(profile) = PROFILES.lock().unwrap().iter().find(|p| p.matches(xpad)) {
+        return profile.clone();
+    }
+
+    let builtins: [Arc<dyn DeviceProfile>; 5] = [
+        Arc::new(GhlProfile),
+        Arc::new(XboxOneProfile),
+        Arc::new(Xbox360WProfile),
+        Arc::new(Xbox360Profile),
+        Arc::new(XboxProfile),
+    ];
+    builtins.into_iter().find(|p| p.matches(xpad)).unwrap()
+}
+
+// The built-in decoders report through xpad.dev, which is what `sink` is
+// for them; they take the pad for its settings and state.
+
+struct XboxProfile;
+
+impl DeviceProfile for XboxProfile {
+    fn matches(&self, _xpad: &UsbXpad) -> bool {
+        true
+    }
+
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], _sink: &dyn InputSink) {
+        xpad_process_packet(xpad, data);
+    }
+}
+
+struct Xbox360Profile;
+
+impl DeviceProfile for Xbox360Profile {
+    fn matches(&self, xpad: &UsbXpad) -> bool {
+        xpad.protocol() == XType::Xbox360
+    }
+
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], _sink: &dyn InputSink) {
+        // Handhelds mix vendor reports in with the pad's, these aren't input
+        if xpad.quirk_actions().contains(&QuirkAction::StandardReportsOnly)
+            && data.get(..2) != Some(&XPAD360_INPUT_HEADER[..]) {
+            xpad_debug!("Ignored vendor report {:02x?}", data.get(..2));
+            return;
+        }
+        if let Err(err) = process_packet(xpad, data) {
+            xpad_debug!("Dropped report: {:?}", err);
+        }
+    }
+}
+
+struct Xbox360WProfile;
+
+impl DeviceProfile for Xbox360WProfile {
+    fn matches(&self, xpad: &UsbXpad) -> bool {
+        xpad.protocol() == XType::Xbox360W
+    }
+
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], _sink: &dyn InputSink) {
+        xpad360w_process_packet(xpad, data);
+    }
+}
+
+struct XboxOneProfile;
+
+impl DeviceProfile for XboxOneProfile {
+    fn matches(&self, xpad: &UsbXpad) -> bool {
+        xpad.protocol() == XType::XboxOne
+    }
+
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], _sink: &dyn InputSink) {
+        xpadone_process_packet(xpad, data);
+    }
+
+    fn init_packets(&self, xpad: &UsbXpad) -> Vec<Vec<u8>> {
+        std::iter::from_fn(|| xpad_prepare_next_init_packet(xpad)).collect()
+    }
+}
+
+// Guitar Hero Live guitar: GIP, with its own report for the fret board
+struct GhlProfile;
+
+impl DeviceProfile for GhlProfile {
+    fn matches(&self, xpad: &UsbXpad) -> bool {
+        xpad.quirks.contains(QuirkFlags::GHL_XBOXONE)
+    }
+
+    fn decode(&self, xpad: &UsbXpad, data: &[u8], _sink: &dyn InputSink) {
+        xpadone_process_packet(xpad, data);
+    }
+
+    fn init_packets(&self, xpad: &UsbXpad) -> Vec<Vec<u8>> {
+        XboxOneProfile.init_packets(xpad)
+    }
+}
+
+impl UsbXpad {
+    // Hand a report to the pad's profile
+    fn decode(&self, data: &[u8]) {
+        let first = self.last_packet_at.lock().unwrap().replace(self.clock.now()).is_none();
+        self.neutralized.store(false, Ordering::SeqCst);
+        self.profile.decode(self, data, &*self.dev);
+
+        // The watchdog only starts counting from the first report
+        if first {
+            self.kick_timer(PadTimer::Watchdog);
+        }
+    }
+
+    // Release everything if no report arrives for this long, so a pad that
+    // drops off without a disconnect doesn't leave input held. None disables it.
+    fn set_watchdog_timeout(&self, timeout: Option<Duration>) {
+        *self.watchdog_timeout.lock().unwrap() = timeout;
+        self.kick_timer(PadTimer::Watchdog);
+    }
+
+    // Report the GHL strum bar as BTN_TRIGGER_HAPPY1 (up) and 2 (down)
+    // rather than ABS_HAT0Y. Like set_mapping(), returns whether the
+    // buttons or axes changed and capabilities have to be registered again.
+    fn set_ghl_strum_buttons(&self, enable: bool) -> bool {
+        let before = xpad_capabilities(self);
+        self.ghl_strum_buttons.store(enable, Ordering::Relaxed);
+        let after = xpad_capabilities(self);
+        before.buttons != after.buttons || before.axes != after.axes
+    }
 
-            // Sticks and triggers
-            if !xpad.mapping.contains(MapFlags::STICKS_TO_NULL) {
-                dev.report_abs(AbsoluteAxis::X, i16::from_le_bytes([data[10], data[11]]).into());
-                dev.report_abs(AbsoluteAxis::Y, (!i16::from_le_bytes([data[12], data[13]])).into());
-                dev.report_abs(AbsoluteAxis::Rx, i16::from_le_bytes([data[14], data[15]]).into());
-                dev.report_abs(AbsoluteAxis::Ry, (!i16::from_le_bytes([data[16], data[17]])).into());
-            }
+    fn set_axis_config(&self, config: AxisConfig) {
+        *self.axis_config.lock().unwrap() = config;
+    }
 
-            do_sync = true;
-        },
-        0x21 => {
-            // GHL guitar processing
-            let dpad_value = data[6] & 0x0F;
-            let (x, y) = DPAD_MAPPING[dpad_value.min(8) as usize];
-            dev.report_abs(AbsoluteAxis::Hat0X, x);
-            dev.report_abs(AbsoluteAxis::Hat0Y, y);
-            do_sync = true;
-        },
-        _ => (),
+    // Time between keepalives on pads that need them
+    fn set_keepalive_interval(&self, interval: Duration) {
+        *self.keepalive_interval.lock().unwrap() = interval;
     }
 
-    if do_sync {
-        dev.synchronize();
+    // Wait this long after each report before asking for the next one,
+    // fewer wakeups for more latency. None resubmits right away.
+    fn set_poll_interval(&self, interval: Option<Duration>) {
+        *self.poll_interval.lock().unwrap() = interval;
+        // Let a URB held back under the old interval go, see xpad_poll_tick()
+        self.kick_timer(PadTimer::Poll);
     }
-}
 
-impl XpadDriver {
-    fn process_packet(&self, data: &[u8]) {
-        let dev = &self.input_dev;
-        
-        // Common button processing
-        dev.report_key(input::Key::ButtonSouth, data[4] & 0x10 != 0);
-        dev.report_key(input::Key::ButtonEast, data[4] & 0x20 != 0);
-        
-        // Analog stick handling
-        if !self.mapping.contains(MapFlags::STICKS_TO_NULL) {
-            let x = i16::from_le_bytes([data[12], data[13]]);
-            dev.report_abs(input::AbsoluteAxis::X, x.into());
+    // The input URB, so a poll interval can hold it back and resubmit it
+    // later. Without it the interval is ignored.
+    fn set_in_transport(&self, irq_in: Arc<dyn InTransport>) {
+        *self.irq_in.lock().unwrap() = Some(irq_in);
+    }
+
+    // With a poll interval set, keep the input URB after a report and have
+    // xpad_poll_tick() resubmit it once the interval is up. False means the
+    // caller resubmits it now.
+    fn hold_input(&self) -> bool {
+        let Some(interval) = *self.poll_interval.lock().unwrap() else {
+            return false;
+        };
+        if self.irq_in.lock().unwrap().is_none() {
+            return false;
         }
-        
-        dev.synchronize();
+        *self.resubmit_at.lock().unwrap() = Some(self.clock.now() + interval);
+        self.kick_timer(PadTimer::Poll);
+        true
     }
-}
 
-impl XpadDriver {
-    fn setup_urbs(&self) -> Result<()> {
-        let mut urb_in = usb::Urb::new_interrupt(
-            &self.udev,
-            self.interface.cur_altsetting().endpoint_in(0)?,
-            64,
-        )?;
-        
-        urb_in.set_completion(|urb| {
-            let driver = urb.context::<XpadDriver>();
-            driver.process_packet(urb.data());
-            urb.submit().unwrap();
-        });
-        
-        urb_in.submit()?;
-        Ok(())
+    // Report every button released and every axis at rest
+    fn report_neutral(&self) {
+        for button in self.emitted_buttons() {
+            self.dev.report_key(button, false);
+        }
+        let layout = *self.trigger_axes.lock().unwrap();
+        let config = *self.axis_config.lock().unwrap();
+        for axis in self.emitted_axes() {
+            let (min, max) = abs_range(self, axis);
+            let inverted = (axis == layout.left && config.invert_lt) || (axis == layout.right && config.invert_rt);
+            self.dev.report_abs(axis, if inverted { max } else { 0.clamp(min, max) });
+        }
+        self.dev.synchronize();
+        self.reset_filters();
     }
 }
 
-// URB completion handler
-fn xpad_irq_in(urb: &Urb, xpad: Arc<UsbXpad>) -> Result<(), UsbError> {
-    match urb.status() {
-        UsbStatus::Success => (),
-        UsbStatus::Disconnected | UsbStatus::Cancelled => return Ok(()),
-        err => {
-            log::warn!("URB error: {:?}", err);
-            return Err(err.into());
-        }
+// Emit one neutral frame once the pad has been silent for the watchdog
+// timeout. Returns how long until the next check, None while disabled or
+// until the pad has sent its first report.
+fn xpad_watchdog_tick(xpad: &UsbXpad) -> Option<Duration> {
+    if xpad.is_disconnected() {
+        return None;
     }
+    let timeout = (*xpad.watchdog_timeout.lock().unwrap())?;
+    let last = (*xpad.last_packet_at.lock().unwrap())?;
+    let now = xpad.clock.now();
+
+    // Reports can't arrive while the URB waits out the poll interval
+    let polling = xpad.poll_interval.lock().unwrap().unwrap_or_default();
+    let deadline = last + polling + timeout;
+    if now < deadline {
+        return Some(deadline - now);
+    }
+    if !xpad.neutralized.swap(true, Ordering::SeqCst) {
+        xpad_debug!("No input for {:?}, releasing all controls", timeout);
+        xpad.report_neutral();
+    }
+    Some(timeout)
+}
 
-    let data = urb.buffer();
-    log::debug!("Received packet: {:02X?}", data);
+// One step of the Xbox One init sequence. Zero vendor/product match any
+// device, non-empty quirks only devices that have all of them. Byte 2 is
+// replaced with the output sequence number when the packet is sent.
+struct XboxOneInitPacket {
+    vendor: u16,
+    product: u16,
+    quirks: QuirkFlags,
+    data: &'static [u8],
+}
 
-    match xpad.xtype {
-        XType::Xbox360 => xpad360_process_packet(&xpad.dev, data),
-        XType::Xbox360W => xpad360w_process_packet(&xpad, data),
-        XType::XboxOne => xpadone_process_packet(&xpad, data),
-        _ => xpad_process_packet(&xpad, data),
-    }
+const fn xboxone_init_pkt(vendor: u16, product: u16, data: &'static [u8]) -> XboxOneInitPacket {
+    XboxOneInitPacket { vendor, product, quirks: QuirkFlags::empty(), data }
+}
 
-    // Resubmit URB
-    urb.submit()?;
-    Ok(())
+// Power on, every pad
+const XBOXONE_POWER_ON: [u8; 5] = [GIP_CMD_POWER, GIP_OPT_INTERNAL, 0x00, 0x01, 0x00];
+// Xbox One S and Elite 2 stay silent without this
+const XBOXONE_S_INIT: [u8; 5] = [GIP_CMD_POWER, GIP_OPT_INTERNAL, 0x00, 0x0f, 0x06];
+// PDP pads: LED on, then the auth they wait for before sending input
+const XBOXONE_PDP_LED_ON: [u8; 7] = [GIP_CMD_LED, GIP_OPT_INTERNAL, 0x00, 0x03, 0x00, 0x01, 0x14];
+const XBOXONE_PDP_AUTH: [u8; 6] = [GIP_CMD_AUTHENTICATE, GIP_OPT_INTERNAL, 0x00, 0x02, 0x01, 0x00];
+
+// Sent in order, skipping the ones that don't apply to the pad
+static XBOXONE_INIT_PACKETS: [XboxOneInitPacket; 6] = [
+    xboxone_init_pkt(0x0000, 0x0000, &XBOXONE_POWER_ON),
+    xboxone_init_pkt(0x045e, 0x02ea, &XBOXONE_S_INIT),
+    xboxone_init_pkt(0x045e, 0x0b00, &XBOXONE_S_INIT),
+    xboxone_init_pkt(0x0e6f, 0x0000, &XBOXONE_PDP_LED_ON),
+    xboxone_init_pkt(0x0e6f, 0x0000, &XBOXONE_PDP_AUTH),
+    // The GHL guitar's d-pad and whammy stay dead until it gets its
+    // keepalive once; the poke timer repeats it from then on
+    XboxOneInitPacket {
+        vendor: 0x0000,
+        product: 0x0000,
+        quirks: QuirkFlags::GHL_XBOXONE,
+        data: &GHL_XBOXONE_POKE,
+    },
+];
+
+impl XboxOneInitPacket {
+    fn applies_to(&self, xpad: &UsbXpad) -> bool {
+        (self.vendor == 0 || self.vendor == xpad.id_vendor) &&
+        (self.product == 0 || self.product == xpad.id_product) &&
+        xpad.quirks.contains(self.quirks)
+    }
 }
 
-// Initialization sequence handling
+// Initialization sequence handling. The last packet that applies asks
+// the pad for an ack (GIP_OPT_ACK), which is what moves it from
+// Configured to Ready; see the GIP_CMD_ACK arm of xpadone_process_packet.
 fn xpad_prepare_next_init_packet(xpad: &UsbXpad) -> Option<Vec<u8>> {
     let mut seq = xpad.init_seq.lock().unwrap();
     while *seq < XBOXONE_INIT_PACKETS.len() {
         let packet = &XBOXONE_INIT_PACKETS[*seq];
         *seq += 1;
 
-        if (packet.vendor == 0 || packet.vendor == xpad.device.vendor_id()) &&
-           (packet.product == 0 || packet.product == xpad.device.product_id()) {
+        if packet.applies_to(xpad) {
             let mut data = packet.data.to_vec();
             data[2] = xpad.odata_serial.fetch_add(1, Ordering::SeqCst) as u8;
+
+            let last = !XBOXONE_INIT_PACKETS[*seq..].iter().any(|next| next.applies_to(xpad));
+            if last {
+                data[1] |= GIP_OPT_ACK;
+                drop(seq);
+                // Configured before it goes out, so the ack can't beat us to it
+                xpad.advance_init(InitState::Configured);
+            }
             return Some(data);
         }
     }
+    drop(seq);
+
+    xpad.advance_init(InitState::Configured);
     None
 }
 
@@ -2976,7 +7224,7 @@ fn xpad_try_sending_next_out_packet(xpad: &UsbXpad) -> Result<(), UsbError> {
     
     if let Some(init_data) = xpad_prepare_next_init_packet(xpad) {
         *odata = init_data;
-        xpad.irq_out.submit(&odata)?;
+        xpad.send_output_packet(&odata)?;
         return Ok(());
     }
 
@@ -2984,6 +7232,296 @@ fn xpad_try_sending_next_out_packet(xpad: &UsbXpad) -> Result<(), UsbError> {
     Ok(())
 }
 
+// Motor magnitudes for one rumble update, full u16 range each
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RumbleMagnitudes {
+    strong: u16,
+    weak: u16,
+    left_trigger: u16,
+    right_trigger: u16,
+}
+
+impl RumbleMagnitudes {
+    fn is_stop(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// One motor's part of an enveloped effect: ramps from attack_level to
+// magnitude, holds, then ramps to fade_level and stops after `length`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MotorEnvelope {
+    magnitude: u16,
+    attack_level: u16,
+    attack_length: Duration,
+    fade_level: u16,
+    fade_length: Duration,
+    length: Duration,
+}
+
+impl MotorEnvelope {
+    // Magnitude `elapsed` into the effect
+    fn level_at(&self, elapsed: Duration) -> u16 {
+        let lerp = |from: u16, to: u16, pos: Duration, span: Duration| {
+            let (from, to) = (i64::from(from), i64::from(to));
+            (from + (to - from) * pos.as_micros() as i64 / span.as_micros().max(1) as i64) as u16
+        };
+        let fade_start = self.length.saturating_sub(self.fade_length);
+
+        if elapsed >= self.length {
+            0
+        } else if elapsed < self.attack_length {
+            lerp(self.attack_level, self.magnitude, elapsed, self.attack_length)
+        } else if elapsed >= fade_start && !self.fade_length.is_zero() {
+            lerp(self.magnitude, self.fade_level, elapsed - fade_start, self.fade_length)
+        } else {
+            self.magnitude
+        }
+    }
+}
+
+// What a rumble request asks the motors to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FfEffect {
+    // Constant magnitudes until the next request
+    Rumble(RumbleMagnitudes),
+    // Strong, weak, left trigger and right trigger, each on its own envelope
+    Enveloped([MotorEnvelope; 4]),
+}
+
+// An enveloped effect in progress
+#[derive(Debug, Clone, Copy)]
+struct ActiveEnvelope {
+    started: Duration,
+    motors: [MotorEnvelope; 4],
+}
+
+impl ActiveEnvelope {
+    fn magnitudes_at(&self, now: Duration) -> RumbleMagnitudes {
+        let elapsed = now.saturating_sub(self.started);
+        let [strong, weak, left_trigger, right_trigger] = self.motors.map(|motor| motor.level_at(elapsed));
+        RumbleMagnitudes { strong, weak, left_trigger, right_trigger }
+    }
+
+    fn finished(&self, now: Duration) -> bool {
+        let elapsed = now.saturating_sub(self.started);
+        self.motors.iter().all(|motor| elapsed >= motor.length)
+    }
+}
+
+// Start an effect. A plain rumble replaces any envelope still running, so
+// a stop zeroes all four motors at once.
+fn xpad_upload_effect(xpad: &UsbXpad, effect: &FfEffect) -> Result<(), XpadError> {
+    match effect {
+        FfEffect::Rumble(rumble) => {
+            *xpad.rumble_envelope.lock().unwrap() = None;
+            xpad_play_effect(xpad, rumble)
+        },
+        FfEffect::Enveloped(motors) => {
+            if !xpad.supports_rumble() {
+                return Err(XpadError::NotSupported);
+            }
+            let envelope = ActiveEnvelope { started: xpad.clock.now(), motors: *motors };
+            *xpad.rumble_envelope.lock().unwrap() = Some(envelope);
+            xpad_play_effect(xpad, &envelope.magnitudes_at(envelope.started))?;
+            xpad.kick_timer(PadTimer::Envelope);
+            Ok(())
+        },
+    }
+}
+
+// Send the input device's effect uploads to xpad_upload_effect(). Done once
+// when the device is created, next to build_capabilities(); whether the pad
+// can rumble is checked per upload, the capabilities descriptor may arrive
+// later.
+fn register_force_feedback(xpad: &Arc<UsbXpad>, input: &InputDevice) -> Result<(), kernel::Error> {
+    let xpad = Arc::downgrade(xpad);
+    input.set_ff_upload(move |effect: &FfEffect| match xpad.upgrade() {
+        Some(xpad) => xpad_upload_effect(&xpad, effect),
+        None => Err(XpadError::Usb(UsbStatus::Disconnected.into())),
+    })
+}
+
+// Advance a running envelope. Returns when to tick next, None once the
+// effect is over and the motors have been stopped.
+fn xpad_envelope_tick(xpad: &UsbXpad) -> Option<Duration> {
+    let now = xpad.clock.now();
+    let mut active = xpad.rumble_envelope.lock().unwrap();
+    let envelope = (*active)?;
+
+    let finished = envelope.finished(now);
+    if finished {
+        *active = None;
+    }
+    drop(active);
+
+    if let Err(err) = xpad_play_effect(xpad, &envelope.magnitudes_at(now)) {
+        log::warn!("Envelope rumble update failed: {:?}", err);
+    }
+    if finished {
+        None
+    } else {
+        Some(Duration::from_millis(ENVELOPE_TICK_MS))
+    }
+}
+
+// When the last rumble packet went out, and the newest update held back since.
+// `deferred` is the latest update asked for before an Xbox One pad was Ready.
+// `running_since` is when the motors last went from stopped to running.
+#[derive(Debug, Default)]
+struct RumbleLimiter {
+    last_sent: Option<Duration>,
+    pending: Option<RumbleMagnitudes>,
+    deferred: Option<RumbleMagnitudes>,
+    running_since: Option<Duration>,
+}
+
+// Rate-limited rumble. Updates within the pad's rumble interval of the last
+// packet only replace the pending magnitudes, xpad_rumble_tick() sends them
+// when the interval is up. Stopping always goes out straight away.
+fn xpad_play_effect(xpad: &UsbXpad, rumble: &RumbleMagnitudes) -> Result<(), XpadError> {
+    if !xpad.supports_rumble() {
+        return Err(XpadError::NotSupported);
+    }
+
+    let now = xpad.clock.now();
+    let interval = *xpad.rumble_interval.lock().unwrap();
+    let mut limiter = xpad.rumble_limiter.lock().unwrap();
+
+    // Rumble in the middle of the GIP handshake can abort it, hold the
+    // latest request until advance_init() reaches Ready
+    if xpad.protocol() == XType::XboxOne && xpad.init_state() != InitState::Ready {
+        limiter.deferred = Some(*rumble);
+        return Ok(());
+    }
+
+    if !rumble.is_stop() {
+        if let Some(last) = limiter.last_sent {
+            if now < last + interval {
+                limiter.pending = Some(*rumble);
+                drop(limiter);
+                xpad.kick_timer(PadTimer::Rumble);
+                return Ok(());
+            }
+        }
+    }
+
+    limiter.pending = None;
+    limiter.last_sent = Some(now);
+    drop(limiter);
+    xpad_send_rumble(xpad, rumble)
+}
+
+// Send a coalesced rumble update if one is due. Returns how long until the
+// next check, None when nothing is pending.
+fn xpad_rumble_tick(xpad: &UsbXpad) -> Option<Duration> {
+    let now = xpad.clock.now();
+    let interval = *xpad.rumble_interval.lock().unwrap();
+    let mut limiter = xpad.rumble_limiter.lock().unwrap();
+    let rumble = limiter.pending?;
+
+    let due = limiter.last_sent.map_or(now, |last| last + interval);
+    if now < due {
+        return Some(due - now);
+    }
+
+    limiter.pending = None;
+    limiter.last_sent = Some(now);
+    drop(limiter);
+    if let Err(err) = xpad_send_rumble(xpad, &rumble) {
+        log::warn!("Coalesced rumble update failed: {:?}", err);
+    }
+    None
+}
+
+// Stop the motors once they have run longer than the pad's cap, whatever
+// the effect asked for, so a game that never sends a stop can't leave them
+// running. Returns how long until the cap is reached, None while stopped.
+fn xpad_rumble_cap_tick(xpad: &UsbXpad) -> Option<Duration> {
+    let cap = (*xpad.max_rumble_duration.lock().unwrap())?;
+    let since = xpad.rumble_limiter.lock().unwrap().running_since?;
+    let now = xpad.clock.now();
+
+    if now < since + cap {
+        return Some(since + cap - now);
+    }
+    log::warn!("Rumble running for {:?}, stopping the motors", cap);
+    *xpad.rumble_envelope.lock().unwrap() = None;
+    if let Err(err) = xpad_play_effect(xpad, &RumbleMagnitudes::default()) {
+        log::warn!("Stopping capped rumble failed: {:?}", err);
+    }
+    None
+}
+
+// Build and send the rumble packet for the pad's protocol
+fn xpad_send_rumble(xpad: &UsbXpad, rumble: &RumbleMagnitudes) -> Result<(), XpadError> {
+    let mut limiter = xpad.rumble_limiter.lock().unwrap();
+    let started = match (rumble.is_stop(), limiter.running_since) {
+        (true, _) => {
+            limiter.running_since = None;
+            false
+        },
+        (false, None) => {
+            limiter.running_since = Some(xpad.clock.now());
+            true
+        },
+        (false, Some(_)) => false,
+    };
+    drop(limiter);
+    // The cap counts from when the motors started
+    if started {
+        xpad.kick_timer(PadTimer::RumbleCap);
+    }
+
+    let gain = u32::from(xpad.rumble_gain.load(Ordering::Relaxed));
+    let scale = |magnitude: u16| (u32::from(magnitude) * gain / 100) as u16;
+    let rumble = &RumbleMagnitudes {
+        strong: scale(rumble.strong),
+        weak: scale(rumble.weak),
+        left_trigger: scale(rumble.left_trigger),
+        right_trigger: scale(rumble.right_trigger),
+    };
+
+    let strong = rumble.strong.to_le_bytes();
+    let weak = rumble.weak.to_le_bytes();
+
+    let packet: Vec<u8> = match xpad.protocol() {
+        XType::Xbox => vec![0x00, 0x06, 0x00, strong[0], strong[1], 0x00, weak[0], weak[1]],
+        XType::Xbox360 => vec![
+            0x00, 0x08, 0x00,
+            (rumble.strong >> 8) as u8,
+            (rumble.weak >> 8) as u8,
+            0x00, 0x00, 0x00,
+        ],
+        XType::Xbox360W => vec![
+            0x00, 0x01, 0x0f, 0xc0, 0x00,
+            (rumble.strong >> 8) as u8,
+            (rumble.weak >> 8) as u8,
+            0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+        XType::XboxOne => {
+            // Trigger motors are left out of the mask when the pad has none
+            let (mask, lt, rt) = if xpad.supports_trigger_rumble() {
+                (0x0f, (rumble.left_trigger / 512) as u8, (rumble.right_trigger / 512) as u8)
+            } else {
+                (0x03, 0x00, 0x00)
+            };
+            vec![
+                GIP_CMD_RUMBLE, 0x00,
+                xpad.odata_serial.fetch_add(1, Ordering::SeqCst),
+                0x09, 0x00, mask, lt, rt,
+                (rumble.strong / 512) as u8,
+                (rumble.weak / 512) as u8,
+                0xff, 0x00, 0xff,
+            ]
+        },
+        XType::Unknown => return Err(XpadError::NotSupported),
+    };
+
+    xpad.send_output_packet(&packet)?;
+    Ok(())
+}
+
 // Force feedback implementation
 impl input::ForceFeedback for XpadDriver {
     fn upload_effect(&self, effect: input::Effect) -> Result<()> {
@@ -3004,6 +7542,313 @@ impl input::ForceFeedback for XpadDriver {
     }
 }
 
+// Stable handle for a pad owned by an XpadManager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct XpadId(u32);
+
+// Events queued by an XpadManager before poll_events picks them up
+const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+// The manager's event stream, bounded so a stalled consumer can't make it
+// grow forever. On overflow axis updates go first, they are superseded by
+// the next report anyway; button edges are only dropped when nothing else is
+// left. Frame ends stay, dropping one would merge two frames into one.
+struct EventQueue {
+    events: Mutex<VecDeque<(XpadId, SinkEvent)>>,
+    capacity: AtomicUsize,
+    // Set from the first drop until the consumer drains the queue, so each
+    // overflow burst is logged once
+    overflowing: AtomicBool,
+}
+
+impl EventQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            capacity: AtomicUsize::new(capacity),
+            overflowing: AtomicBool::new(false),
+        }
+    }
+
+    // Queue an event, returns whether another one had to be dropped for it
+    fn push(&self, id: XpadId, event: SinkEvent) -> bool {
+        let mut events = self.events.lock().unwrap();
+        let full = events.len() >= self.capacity.load(Ordering::Relaxed).max(1);
+        if full {
+            let oldest = |wanted: fn(&SinkEvent) -> bool| events.iter().position(|(_, e)| wanted(e));
+            let victim = oldest(|e| matches!(e, SinkEvent::Abs(..)))
+                .or_else(|| oldest(|e| matches!(e, SinkEvent::Scan(..))))
+                .or_else(|| oldest(|e| matches!(e, SinkEvent::Key(..))));
+            match victim {
+                Some(victim) => {
+                    events.remove(victim);
+                },
+                // Only frame ends left, the oldest is a whole empty frame
+                None => {
+                    events.pop_front();
+                },
+            }
+
+            if !self.overflowing.swap(true, Ordering::Relaxed) {
+                log::warn!("Event queue full ({} events), dropping until it is drained", events.len());
+            }
+        }
+        events.push_back((id, event));
+        full
+    }
+
+    fn drain(&self) -> Vec<(XpadId, SinkEvent)> {
+        self.overflowing.store(false, Ordering::Relaxed);
+        self.events.lock().unwrap().drain(..).collect()
+    }
+}
+
+// Tap installed by the manager, queues a tagged copy of each event for the
+// manager's event stream
+struct ManagedSink {
+    id: XpadId,
+    queue: Arc<EventQueue>,
+    // The pad's events_dropped diagnostic
+    dropped: Arc<AtomicU64>,
+}
+
+impl ManagedSink {
+    fn push(&self, event: SinkEvent) {
+        if self.queue.push(self.id, event) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl InputSink for ManagedSink {
+    fn report_key(&self, button: Button, pressed: bool) {
+        self.push(SinkEvent::Key(button, pressed));
+    }
+
+    fn report_abs(&self, axis: AbsoluteAxis, value: i32) {
+        self.push(SinkEvent::Abs(axis, value));
+    }
+
+    fn synchronize(&self) {
+        self.push(SinkEvent::Sync);
+    }
+
+    fn report_scan(&self, scancode: u32) {
+        self.push(SinkEvent::Scan(scancode));
+    }
+}
+
+// Owns several pads, routes their URBs and merges their events into one
+// stream tagged with the pad they came from. Safe to share between threads.
+struct XpadManager {
+    pads: Mutex<BTreeMap<XpadId, Arc<UsbXpad>>>,
+    next_id: AtomicU32,
+    events: Arc<EventQueue>,
+    auto_assign_players: bool,
+    // Capabilities descriptors of pads seen before, by serial, along with
+    // the firmware they were read from
+    caps_cache: Mutex<HashMap<String, (u16, GipCapabilities)>>,
+}
+
+impl XpadManager {
+    fn new() -> Self {
+        Self {
+            pads: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU32::new(0),
+            events: Arc::new(EventQueue::new(DEFAULT_EVENT_CAPACITY)),
+            auto_assign_players: false,
+            caps_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Cache key of a pad, None if it can't be told apart from its siblings
+    fn caps_key(xpad: &UsbXpad) -> Option<(String, u16)> {
+        Some((xpad.serial.clone()?, xpad.firmware?))
+    }
+
+    // Give each attached pad the lowest free player index and light its LED
+    fn with_auto_assign_players(mut self, enable: bool) -> Self {
+        self.auto_assign_players = enable;
+        self
+    }
+
+    // Most events kept between poll_events calls
+    fn with_event_capacity(self, capacity: usize) -> Self {
+        self.events.capacity.store(capacity, Ordering::Relaxed);
+        self
+    }
+
+    // Take ownership of a pad, its events show up in poll_events from now on
+    fn attach(&self, xpad: UsbXpad) -> XpadId {
+        let id = XpadId(self.next_id.fetch_add(1, Ordering::SeqCst));
+
+        // A pad seen before on the same firmware has the same descriptor,
+        // don't leave it on the table defaults until the descriptor arrives
+        if let Some((serial, firmware)) = Self::caps_key(&xpad) {
+            let mut cache = self.caps_cache.lock().unwrap();
+            match cache.get(&serial) {
+                Some(&(cached_firmware, caps)) if cached_firmware == firmware => {
+                    xpad_debug!("Using cached capabilities for {}: {:?}", serial, caps);
+                    *xpad.gip_caps.lock().unwrap() = Some(caps);
+                },
+                Some(_) => {
+                    xpad_debug!("Firmware of {} changed, cached capabilities dropped", serial);
+                    cache.remove(&serial);
+                },
+                None => (),
+            }
+        }
+
+        // Tapped at the device end, so the stream matches what the pad's
+        // input device reports
+        *xpad.tap.lock().unwrap() = Some(Arc::new(ManagedSink {
+            id,
+            queue: self.events.clone(),
+            dropped: xpad.events_dropped.clone(),
+        }));
+
+        let mut pads = self.pads.lock().unwrap();
+        if self.auto_assign_players {
+            // Past the fourth pad there is no LED left, those get index 0
+            let index = (1..=MAX_PLAYERS)
+                .find(|&index| pads.values().all(|pad| pad.player_index() != index))
+                .unwrap_or(0);
+            if let Err(err) = xpad.set_player_index(index) {
+                log::warn!("Failed to set player {} LED: {:?}", index, err);
+            }
+        }
+        let xpad = Arc::new(xpad);
+        xpad.start_timers();
+        pads.insert(id, xpad);
+        id
+    }
+
+    // Forget a pad, events it already queued are still returned by
+    // poll_events. Its player index is free again for the next attach.
+    fn detach(&self, id: XpadId) -> Option<Arc<UsbXpad>> {
+        let xpad = self.pads.lock().unwrap().remove(&id)?;
+        *xpad.tap.lock().unwrap() = None;
+        if let (Some((serial, firmware)), Some(caps)) = (Self::caps_key(&xpad), xpad.gip_capabilities()) {
+            self.caps_cache.lock().unwrap().insert(serial, (firmware, caps));
+        }
+        Some(xpad)
+    }
+
+    fn get(&self, id: XpadId) -> Option<Arc<UsbXpad>> {
+        self.pads.lock().unwrap().get(&id).cloned()
+    }
+
+    // Hand a completed input URB to the pad it belongs to
+    fn dispatch(&self, id: XpadId, urb: &Urb) -> Result<(), UsbError> {
+        match self.get(id) {
+            Some(xpad) => xpad_irq_in(urb, xpad),
+            None => Ok(()),
+        }
+    }
+
+    // Same for a completed output URB
+    fn dispatch_out(&self, id: XpadId, urb: &Urb) {
+        if let Some(xpad) = self.get(id) {
+            xpad_irq_out(urb, &xpad);
+        }
+    }
+
+    // Drain every event queued since the last call, oldest first
+    fn poll_events(&self) -> Vec<(XpadId, SinkEvent)> {
+        self.events.drain()
+    }
+}
+
+// What the LED device can be asked to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum LedState {
+    // One of the LedCommand patterns
+    Pattern(u8),
+    // Light one ring quadrant at a time, moving clockwise every interval
+    Rotate { interval: Duration },
+}
+
+// Host-driven ring animation in progress
+#[derive(Debug, Clone, Copy)]
+struct LedAnimation {
+    interval: Duration,
+    next_at: Duration,
+    step: usize,
+}
+
+// Quadrants in clockwise order, as LedCommand values
+const LED_ROTATION: [LedCommand; 4] = [
+    LedCommand::TopLeftOn,
+    LedCommand::TopRightOn,
+    LedCommand::BottomRightOn,
+    LedCommand::BottomLeftOn,
+];
+
+// Player indices with a ring quadrant of their own
+const MAX_PLAYERS: u8 = 4;
+
+impl UsbXpad {
+    // Player 1-4, 0 when the pad has none
+    fn player_index(&self) -> u8 {
+        self.player_index.load(Ordering::Relaxed)
+    }
+
+    // Assign a player and show it on the ring the way the console does,
+    // blinking the quadrant before it stays on. Index 0 clears the
+    // assignment and leaves the LED alone. Pads without the 360 ring, Xbox
+    // One ones included, only get the index; no LED packet is sent to them.
+    fn set_player_index(&self, index: u8) -> Result<(), XpadError> {
+        if index > MAX_PLAYERS {
+            return Err(XpadError::NotSupported);
+        }
+        self.player_index.store(index, Ordering::Relaxed);
+
+        if index == 0 || !self.features().contains(FeatureSet::PLAYER_LEDS) {
+            return Ok(());
+        }
+        let pattern = LedCommand::TopLeftBlinkThenOn as u8 + index - 1;
+        xpad_set_led(self, LedState::Pattern(pattern))?;
+        Ok(())
+    }
+}
+
+// LED pattern packet for the 360 ring, wired and wireless 360 pads only
+fn create_led_packet(xpad: &UsbXpad, command: u8) -> Vec<u8> {
+    let command = command % 16;
+    match xpad.protocol() {
+        XType::Xbox360W => vec![
+            0x00, 0x00, 0x08, 0x40 + command,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+        _ => vec![0x01, 0x03, command],
+    }
+}
+
+// Advance the ring animation if it is due. Returns how long until the next
+// step, or None once no animation is running.
+fn xpad_led_animation_tick(xpad: &UsbXpad) -> Option<Duration> {
+    let now = xpad.clock.now();
+    let (command, next_at) = {
+        let mut animation = xpad.led_animation.lock().unwrap();
+        let animation = animation.as_mut()?;
+        if now < animation.next_at {
+            return Some(animation.next_at - now);
+        }
+        let command = LED_ROTATION[animation.step % LED_ROTATION.len()] as u8;
+        animation.step += 1;
+        animation.next_at = now + animation.interval;
+        (command, animation.next_at)
+    };
+
+    // Sent without the animation locked. Animation frames aren't what a
+    // reinitialize should restore, so not send_led_packet().
+    if let Err(err) = xpad.send_output_packet(&create_led_packet(xpad, command)) {
+        log::warn!("LED animation step failed: {:?}", err);
+    }
+    Some(next_at - now)
+}
+
 // LED control
 struct XpadLed {
     xpad: Arc<UsbXpad>,
@@ -3012,15 +7857,71 @@ struct XpadLed {
 
 impl LedDevice for XpadLed {
     fn set_state(&mut self, state: LedState) -> Result<(), DeviceError> {
-        let packet = match state {
-            LedState::Pattern(pattern) => create_led_packet(pattern),
-            // Other states...
-        };
-        self.xpad.send_output_packet(&packet)
+        if let Some(delay) = xpad_set_led(&self.xpad, state)? {
+            self.schedule_delayed(delay);
+        }
+        Ok(())
+    }
+}
+
+// Show an LED state. For a rotation the first step goes out right away and
+// the delay until the next one is returned for the caller to schedule.
+fn xpad_set_led(xpad: &UsbXpad, state: LedState) -> Result<Option<Duration>, UsbError> {
+    // Patterns and the rotation are commands for the 360 ring, which GIP
+    // and original Xbox pads don't have and would misread
+    let ring = matches!(state, LedState::Pattern(_) | LedState::Rotate { .. });
+    if ring && !xpad.features().contains(FeatureSet::PLAYER_LEDS) {
+        log::warn!("LED ring not supported on {:04x}:{:04x}, ignoring",
+                   xpad.id_vendor, xpad.id_product);
+        return Ok(None);
+    }
+
+    // Any new state replaces a running animation
+    let rotating = matches!(state, LedState::Rotate { .. });
+    if !rotating {
+        *xpad.led_animation.lock().unwrap() = None;
+    }
+
+    let packet = match state {
+        LedState::Pattern(pattern) => create_led_packet(xpad, pattern),
+        LedState::Rotate { interval } => {
+            *xpad.led_animation.lock().unwrap() = Some(LedAnimation {
+                interval,
+                next_at: xpad.clock.now(),
+                step: 0,
+            });
+            return Ok(xpad_led_animation_tick(xpad));
+        },
+    };
+    xpad.send_led_packet(&packet)?;
+    Ok(None)
+}
+
+// LED a device shows once init completes, unless overridden per pad.
+// Devices not listed keep whatever the controller picks itself. Microsoft's
+// wired 360 pads otherwise sit blinking all four quadrants, so they light
+// player 1 the way Linux does for the first pad. Wireless receivers aren't
+// listed, one pattern for all four of their pads would be wrong.
+static DEFAULT_LEDS: Map<(u16, u16), LedState> = phf_map! {
+    (0x045e, 0x028e) => LedState::Pattern(LedCommand::TopLeftBlinkThenOn as u8),
+    (0x045e, 0x028f) => LedState::Pattern(LedCommand::TopLeftBlinkThenOn as u8),
+};
+
+fn table_default_led(id_vendor: u16, id_product: u16) -> Option<LedState> {
+    DEFAULT_LEDS.get(&(id_vendor, id_product)).copied()
+}
+
+// Work item stepping the ring animation, requeues itself until it is cancelled
+impl Work for XpadLed {
+    fn run(&self) {
+        if let Some(delay) = xpad_led_animation_tick(&self.xpad) {
+            self.schedule_delayed(delay);
+        }
     }
 }
 
 // Define the command types for setting LEDs on the Xbox 360/Wireless Controller
+#[derive(Debug, Clone, Copy)]
 enum LedCommand {
     Off = 0,
     BlinkAllThenPrevious,